@@ -38,7 +38,26 @@ impl ShaderData {
     }
 }
 
+// Lists every enemy definition so they are all embedded, adding one doesn't need a code change.
+fn write_enemy_archetypes() -> BuildScriptResult<()> {
+    let mut entries = String::new();
+    for glob_result in glob("./assets/enemies/*.enemy")? {
+        let path = glob_result?.canonicalize()?;
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        entries += &format!(
+            "    ({:?}, include_str!({:?})),\n",
+            name,
+            path.to_str().unwrap()
+        );
+    }
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("enemy_archetypes.rs");
+    write(out_path, format!("&[\n{}]\n", entries))?;
+    Ok(())
+}
+
 fn main() -> BuildScriptResult<()> {
+    write_enemy_archetypes()?;
+
     let mut shader_paths = [
         glob("./src/shaders/*.vert")?,
         glob("./src/shaders/*.frag")?,
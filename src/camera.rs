@@ -1,14 +1,91 @@
 use rapier3d::na::Vector3;
 
+use crate::camera_effects::CameraEffects;
 use crate::frustum::{Frustum, FrustumPlane};
+use crate::sensitivity::cm_per_360;
 use crate::util::clamp;
 
+pub const FOV_TYPE_NAMES: &[&str; 4] = &[
+    "Vertical",
+    "Horizontal 4:3",
+    "Horizontal 16:9",
+    "Horizontal",
+];
+
+/// What the field of view setting measures. Games disagree on it, so the same number can mean
+/// very different views.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u32)]
+pub enum FovType {
+    Vertical = 0,
+    // Horizontal on a 4:3 screen, the vertical one is kept on other screens
+    Horizontal4By3 = 1,
+    // Horizontal on a 16:9 screen, the vertical one is kept on other screens
+    Horizontal16By9 = 2,
+    // Horizontal on the actual window, whatever its aspect ratio
+    Horizontal = 3,
+}
+
+impl From<usize> for FovType {
+    fn from(x: usize) -> Self {
+        match x {
+            0 => FovType::Vertical,
+            1 => FovType::Horizontal4By3,
+            2 => FovType::Horizontal16By9,
+            3 => FovType::Horizontal,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Vertical field of view in radians of a `fov` in degrees measured as `fov_type` says, on a
+/// screen of `aspect_ratio` width over height.
+pub fn get_vertical_fov(fov: f32, fov_type: FovType, aspect_ratio: f32) -> f32 {
+    let half_fov = (fov * 0.5).to_radians();
+    match fov_type {
+        FovType::Vertical => half_fov * 2.0,
+        FovType::Horizontal4By3 => (half_fov.tan() / (4.0 / 3.0)).atan() * 2.0,
+        FovType::Horizontal16By9 => (half_fov.tan() / (16.0 / 9.0)).atan() * 2.0,
+        FovType::Horizontal => (half_fov.tan() / aspect_ratio).atan() * 2.0,
+    }
+}
+
+/// Setting in degrees measured as `fov_type` says which gives a `vertical_fov` in radians on a
+/// screen of `aspect_ratio` width over height, the inverse of `get_vertical_fov`.
+pub fn get_fov_of_type(vertical_fov: f32, fov_type: FovType, aspect_ratio: f32) -> f32 {
+    let half_tan = (vertical_fov * 0.5).tan();
+    let half_fov = match fov_type {
+        FovType::Vertical => vertical_fov * 0.5,
+        FovType::Horizontal4By3 => (half_tan * (4.0 / 3.0)).atan(),
+        FovType::Horizontal16By9 => (half_tan * (16.0 / 9.0)).atan(),
+        FovType::Horizontal => (half_tan * aspect_ratio).atan(),
+    };
+    (half_fov * 2.0).to_degrees()
+}
+
 pub struct Camera {
     pub position: Vector3<f32>,
     yaw: f32,
     pitch: f32,
+    // In degrees, measured as `fov_type` says
     pub fov: f32,
-    pub sensitivity: f32,
+    pub fov_type: FovType,
+    // Angle the view turns by per mouse count, this is the sensitivity
+    pub degrees_per_count: f32,
+    // Only used to show the sensitivity as cm/360
+    pub mouse_dpi: f32,
+    // Magnification of the aim down sights, 1.0 when not aiming
+    pub zoom: f32,
+    // Multiplier of the sensitivity while zoomed
+    pub zoom_sensitivity: f32,
+    // Scales the zoomed sensitivity down by the magnification, so an object next to the
+    // crosshair moves the same distance on screen for the same mouse movement (0% monitor
+    // distance match)
+    pub is_zoom_sensitivity_matched: bool,
+    pub is_scope_visible: bool,
+    pub effects: CameraEffects,
+    // Sum of the recoil kicks since the last reset, as (yaw, pitch) in degrees.
+    recoil: nalgebra::Vector2<f32>,
 }
 
 impl Camera {
@@ -17,8 +94,16 @@ impl Camera {
             position: Vector3::new(0.0, 0.6, 0.0),
             yaw: 270.0,
             pitch: 0.0,
-            fov: 90.0f32.to_radians(),
-            sensitivity: 0.5,
+            fov: 90.0,
+            fov_type: FovType::Horizontal4By3,
+            degrees_per_count: 0.011,
+            mouse_dpi: 800.0,
+            zoom: 1.0,
+            zoom_sensitivity: 1.0,
+            is_zoom_sensitivity_matched: true,
+            is_scope_visible: false,
+            effects: CameraEffects::new(),
+            recoil: nalgebra::Vector2::new(0.0, 0.0),
         }
     }
 
@@ -30,14 +115,71 @@ impl Camera {
         ))
     }
 
+    /// Turns the view by `offset` mouse counts. The angle only depends on the counts and the
+    /// sensitivity, never on the frame time, so the same hand movement always turns the same.
     pub fn move_direction(&mut self, offset: nalgebra::Vector2<f32>) {
-        let offset_with_sensitivity = offset * self.sensitivity;
+        let offset_with_sensitivity = self.get_mouse_angles(offset);
         self.yaw -= offset_with_sensitivity.x;
         self.pitch += offset_with_sensitivity.y;
 
         self.pitch = clamp(self.pitch, -89.0, 89.0);
     }
 
+    /// Angles in degrees `move_direction` turns the view by for `offset` mouse counts, with the
+    /// same signs as `rotate`.
+    pub fn get_mouse_angles(&self, offset: nalgebra::Vector2<f32>) -> nalgebra::Vector2<f32> {
+        let mut degrees_per_count = self.degrees_per_count;
+        if self.zoom > 1.0 {
+            degrees_per_count *= self.zoom_sensitivity;
+            if self.is_zoom_sensitivity_matched {
+                degrees_per_count /= self.zoom;
+            }
+        }
+        offset * degrees_per_count
+    }
+
+    /// Vertical field of view narrowed by the zoom, in radians, on a screen of `aspect_ratio`
+    /// width over height.
+    pub fn get_fov(&self, aspect_ratio: f32) -> f32 {
+        let fov = get_vertical_fov(self.fov, self.fov_type, aspect_ratio);
+        2.0 * ((fov * 0.5).tan() / self.zoom).atan()
+    }
+
+    pub fn reset_zoom(&mut self) {
+        self.zoom = 1.0;
+        self.is_scope_visible = false;
+    }
+
+    /// Mouse distance of a full turn in centimeters.
+    pub fn get_cm_per_360(&self) -> f32 {
+        cm_per_360(self.degrees_per_count, self.mouse_dpi)
+    }
+
+    /// Rotates the view by angles in degrees, positive yaw turning left and positive
+    /// pitch looking up.
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.yaw -= yaw;
+        self.pitch = clamp(self.pitch + pitch, -89.0, 89.0);
+    }
+
+    pub fn add_recoil(&mut self, yaw: f32, pitch: f32) {
+        self.rotate(yaw, pitch);
+        self.recoil += nalgebra::Vector2::new(yaw, pitch);
+    }
+
+    pub fn reset_recoil(&mut self) {
+        self.recoil = nalgebra::Vector2::new(0.0, 0.0);
+    }
+
+    pub fn get_recoil(&self) -> nalgebra::Vector2<f32> {
+        self.recoil
+    }
+
+    /// Yaw and pitch in degrees, with the same signs as `rotate`.
+    pub fn get_view_angles(&self) -> nalgebra::Vector2<f32> {
+        nalgebra::Vector2::new(-self.yaw, self.pitch)
+    }
+
     pub fn get_direction(&self) -> nalgebra::Unit<nalgebra::Vector3<f32>> {
         nalgebra::Unit::new_normalize(nalgebra::Vector3::new(
             self.yaw.to_radians().cos() * self.pitch.to_radians().cos(),
@@ -46,6 +188,27 @@ impl Camera {
         ))
     }
 
+    /// Direction of the view turned by angles in degrees, with the same signs as `rotate`.
+    pub fn get_direction_with_offset(
+        &self,
+        yaw_offset: f32,
+        pitch_offset: f32,
+    ) -> nalgebra::Unit<nalgebra::Vector3<f32>> {
+        let yaw = (self.yaw - yaw_offset).to_radians();
+        let pitch = (self.pitch + pitch_offset).to_radians();
+        nalgebra::Unit::new_normalize(nalgebra::Vector3::new(
+            yaw.cos() * pitch.cos(),
+            pitch.sin(),
+            yaw.sin() * pitch.cos(),
+        ))
+    }
+
+    /// Direction the view is drawn with, the aim moved by the camera effects.
+    pub fn get_view_direction(&self) -> nalgebra::Unit<nalgebra::Vector3<f32>> {
+        let offset = self.effects.get_offset();
+        self.get_direction_with_offset(offset.x, offset.y)
+    }
+
     pub fn get_direction_right(&self) -> nalgebra::Unit<nalgebra::Vector3<f32>> {
         nalgebra::Unit::new_normalize(
             self.get_direction_without_pitch()
@@ -53,15 +216,16 @@ impl Camera {
         )
     }
 
-    pub fn get_frustum(&self) -> Frustum {
+    /// Frustum of the view on a screen of `aspect_ratio` width over height.
+    pub fn get_frustum(&self, aspect_ratio: f32) -> Frustum {
         const Z_FAR: f32 = 150.0;
         const Z_NEAR: f32 = 0.1;
-        let half_v_side = Z_FAR * (self.fov * 0.5).tan();
-        let half_h_side = half_v_side * 1.5;
-        let right = self.get_direction_right();
-        let dir = self.get_direction();
-        let front_times_far = Z_FAR * dir.into_inner();
+        let half_v_side = Z_FAR * (self.get_fov(aspect_ratio) * 0.5).tan();
+        let half_h_side = half_v_side * aspect_ratio;
         let up = nalgebra::Vector3::<f32>::new(0.0, 1.0, 0.0);
+        let dir = self.get_view_direction();
+        let right = nalgebra::Unit::new_normalize(dir.cross(&up));
+        let front_times_far = Z_FAR * dir.into_inner();
 
         Frustum {
             near: FrustumPlane::new(self.position + Z_NEAR * dir.into_inner(), dir),
@@ -0,0 +1,81 @@
+use nalgebra::Vector2;
+use rand::distributions::Uniform;
+use rand::prelude::SmallRng;
+use rand::{Rng, SeedableRng};
+
+// Fraction of the kick left after a second, the view springs back to where it aims.
+const KICK_RECOVERY: f32 = 0.0005;
+// Trauma lost per second, a full trauma shakes for one second
+const TRAUMA_DECAY: f32 = 1.0;
+// Angle in degrees the view shakes by at full trauma
+const MAX_SHAKE_ANGLE: f32 = 3.0;
+// Angle in degrees the view is thrown by when the player gets hit
+const FLINCH_ANGLE: f32 = 4.0;
+const FLINCH_TRAUMA: f32 = 0.5;
+
+/// Transient offsets added to the view, they all decay on their own. Shots follow them, so the
+/// crosshair always shows where they land.
+pub struct CameraEffects {
+    // Scales of each effect set in the settings, 0.0 turns it off
+    pub kick_intensity: f32,
+    pub shake_intensity: f32,
+    pub flinch_intensity: f32,
+    // Sum of the kicks and flinches not recovered yet, as (yaw, pitch) in degrees
+    kick: Vector2<f32>,
+    // Between 0.0 and 1.0, the shake grows with its square so small traumas stay subtle
+    trauma: f32,
+    time: f32,
+    rng: SmallRng,
+}
+
+impl CameraEffects {
+    pub fn new() -> Self {
+        Self {
+            kick_intensity: 1.0,
+            shake_intensity: 1.0,
+            flinch_intensity: 1.0,
+            kick: Vector2::new(0.0, 0.0),
+            trauma: 0.0,
+            time: 0.0,
+            rng: SmallRng::from_entropy(),
+        }
+    }
+
+    /// Punches the view by angles in degrees, with the same signs as `Camera::rotate`.
+    pub fn add_kick(&mut self, yaw: f32, pitch: f32) {
+        self.kick += Vector2::new(yaw, pitch) * self.kick_intensity;
+    }
+
+    /// Adds to the trauma, `amount` of 1.0 being the strongest shake.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount * self.shake_intensity).min(1.0);
+    }
+
+    /// Throws the view in a random direction and shakes it, for when the player gets hit.
+    pub fn add_flinch(&mut self) {
+        let angle = self.rng.sample(Uniform::new(0.0, std::f32::consts::TAU));
+        self.kick += Vector2::new(angle.cos(), angle.sin()) * FLINCH_ANGLE * self.flinch_intensity;
+        self.add_trauma(FLINCH_TRAUMA * self.flinch_intensity);
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.kick *= KICK_RECOVERY.powf(delta_time);
+        self.trauma = (self.trauma - TRAUMA_DECAY * delta_time).max(0.0);
+        self.time += delta_time;
+    }
+
+    /// Current offset of the view as (yaw, pitch) in degrees.
+    pub fn get_offset(&self) -> Vector2<f32> {
+        // Sines of unrelated frequencies give a smooth shake that doesn't look periodic
+        let shake = Vector2::new(
+            (self.time * 37.0).sin() + (self.time * 23.0).sin() * 0.5,
+            (self.time * 31.0).sin() + (self.time * 19.0).sin() * 0.5,
+        ) / 1.5;
+        self.kick + shake * self.trauma * self.trauma * MAX_SHAKE_ANGLE
+    }
+
+    pub fn clear(&mut self) {
+        self.kick = Vector2::new(0.0, 0.0);
+        self.trauma = 0.0;
+    }
+}
@@ -0,0 +1,317 @@
+use crate::physics::GamePhysics;
+use crate::systems::player::{player_capsule, PLAYER_CROUCH_DEPTH};
+use nalgebra::Vector3;
+use rapier3d::prelude::*;
+
+// Gap kept between the capsule and the geometry so the next sweep doesn't start in contact.
+const SKIN_WIDTH: f32 = 0.02;
+const MAX_SLIDE_ITERATIONS: usize = 4;
+// Highest ledge the player walks onto without jumping.
+const STEP_HEIGHT: f32 = 0.35;
+// Surfaces whose normal has a smaller y component (steeper than ~45 degrees) are walls.
+const MIN_GROUND_NORMAL_Y: f32 = 0.7;
+// How fast the eye moves between standing and crouching height, in m/s.
+const EYE_HEIGHT_SPEED: f32 = 6.0;
+
+pub struct CharacterController {
+    velocity: Vector3<f32>,
+    is_grounded: bool,
+    is_crouching: bool,
+    eye_offset: f32,
+}
+
+impl CharacterController {
+    pub fn new() -> Self {
+        Self {
+            velocity: Vector3::zeros(),
+            is_grounded: false,
+            is_crouching: false,
+            eye_offset: 0.0,
+        }
+    }
+
+    pub fn is_grounded(&self) -> bool {
+        self.is_grounded
+    }
+
+    pub fn is_crouching(&self) -> bool {
+        self.is_crouching
+    }
+
+    pub fn get_velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    /// Slows the horizontal velocity down, small speeds are stopped as if they were `stop_speed`.
+    pub fn apply_friction(&mut self, friction: f32, stop_speed: f32, delta_time: f32) {
+        let speed = self.velocity.xz().norm();
+        if speed <= f32::EPSILON {
+            return;
+        }
+        let drop = speed.max(stop_speed) * friction * delta_time;
+        let scale = (speed - drop).max(0.0) / speed;
+        self.velocity.x *= scale;
+        self.velocity.z *= scale;
+    }
+
+    /// Accelerates toward `wish_speed` along `wish_direction`. Pressing the opposite direction
+    /// first cancels the current velocity, which is what makes counter-strafing stop the player.
+    pub fn accelerate(
+        &mut self,
+        wish_direction: Vector3<f32>,
+        wish_speed: f32,
+        acceleration: f32,
+        delta_time: f32,
+    ) {
+        let current_speed = self.velocity.dot(&wish_direction);
+        let add_speed = wish_speed - current_speed;
+        if add_speed <= 0.0 {
+            return;
+        }
+        self.velocity += wish_direction * (acceleration * wish_speed * delta_time).min(add_speed);
+    }
+
+    pub fn jump(&mut self, speed: f32) {
+        if self.is_grounded {
+            self.velocity.y = speed;
+            self.is_grounded = false;
+        }
+    }
+
+    /// Replaces the horizontal velocity with a burst along `direction`, friction then brings it
+    /// back down to the normal speed.
+    pub fn dash(&mut self, direction: Vector3<f32>, speed: f32) {
+        self.velocity.x = direction.x * speed;
+        self.velocity.z = direction.z * speed;
+    }
+
+    /// Switches the body's collider between the standing and crouching capsules. Standing up is
+    /// refused while there is no room above the head.
+    pub fn set_crouching(
+        &mut self,
+        physics: &mut GamePhysics,
+        rigid_body_handle: RigidBodyHandle,
+        crouching: bool,
+    ) {
+        if crouching == self.is_crouching {
+            return;
+        }
+        let rigid_body = physics.rigid_body_set.get(rigid_body_handle).unwrap();
+        let collider_handle = rigid_body.colliders()[0];
+        let position = *rigid_body.translation();
+        let shape = player_capsule(crouching);
+        if !crouching {
+            let sweep = Sweep {
+                physics: &*physics,
+                shape: &*shape,
+                exclude: collider_handle,
+            };
+            if sweep.intersects(position) {
+                return;
+            }
+        }
+        physics
+            .collider_set
+            .get_mut(collider_handle)
+            .unwrap()
+            .set_shape(shape);
+        self.is_crouching = crouching;
+    }
+
+    /// Moves the body by its velocity and gravity, sliding along walls, climbing steps and staying
+    /// on the ground. Returns the eye position of the character.
+    pub fn update(
+        &mut self,
+        delta_time: f32,
+        physics: &mut GamePhysics,
+        rigid_body_handle: RigidBodyHandle,
+    ) -> Vector3<f32> {
+        let rigid_body = physics.rigid_body_set.get(rigid_body_handle).unwrap();
+        let collider_handle = rigid_body.colliders()[0];
+        let start = *rigid_body.translation();
+        let shape = physics
+            .collider_set
+            .get(collider_handle)
+            .unwrap()
+            .shared_shape()
+            .clone();
+        let sweep = Sweep {
+            physics: &*physics,
+            shape: &*shape,
+            exclude: collider_handle,
+        };
+
+        // Horizontal
+        let horizontal_motion = Vector3::new(self.velocity.x, 0.0, self.velocity.z) * delta_time;
+        let (mut position, normals) = sweep.slide(start, horizontal_motion, true);
+        let blocked = normals.iter().any(|normal| normal.y < MIN_GROUND_NORMAL_Y);
+        if blocked && self.is_grounded {
+            if let Some(stepped) = sweep.step_up(start, horizontal_motion) {
+                if horizontal_distance(&stepped, &start) > horizontal_distance(&position, &start) {
+                    position = stepped;
+                }
+            }
+        }
+
+        if delta_time > 0.0 {
+            // Lose the velocity that went into walls
+            self.velocity.x = (position.x - start.x) / delta_time;
+            self.velocity.z = (position.z - start.z) / delta_time;
+        }
+
+        // Vertical
+        let was_grounded = self.is_grounded;
+        self.velocity.y += physics.gravity.y * delta_time;
+        let vertical_motion = Vector3::new(0.0, self.velocity.y * delta_time, 0.0);
+        let (fallen, normals) = sweep.slide(position, vertical_motion, false);
+        position = fallen;
+        self.is_grounded =
+            self.velocity.y <= 0.0 && normals.iter().any(|normal| normal.y >= MIN_GROUND_NORMAL_Y);
+        if self.velocity.y > 0.0 && normals.iter().any(|normal| normal.y < 0.0) {
+            // Bumped the head
+            self.velocity.y = 0.0;
+        }
+        if !self.is_grounded && was_grounded && self.velocity.y <= 0.0 {
+            // Follow the ground down steps and slopes instead of launching off them
+            if let Some(snapped) = sweep.snap_to_ground(position, STEP_HEIGHT) {
+                position = snapped;
+                self.is_grounded = true;
+            }
+        }
+        if self.is_grounded {
+            self.velocity.y = 0.0;
+        }
+
+        physics
+            .rigid_body_set
+            .get_mut(rigid_body_handle)
+            .unwrap()
+            .set_next_kinematic_translation(position);
+
+        let eye_target = if self.is_crouching {
+            -PLAYER_CROUCH_DEPTH
+        } else {
+            0.0
+        };
+        let eye_step = EYE_HEIGHT_SPEED * delta_time;
+        self.eye_offset += (eye_target - self.eye_offset).clamp(-eye_step, eye_step);
+        position + Vector3::new(0.0, self.eye_offset, 0.0)
+    }
+}
+
+struct Sweep<'a> {
+    physics: &'a GamePhysics,
+    shape: &'a dyn Shape,
+    exclude: ColliderHandle,
+}
+
+impl<'a> Sweep<'a> {
+    // Dynamic bodies (enemies, bullets, projectiles) are pushed away by the kinematic body during
+    // the physics step, so only fixed geometry blocks the player.
+    fn is_blocking(&self, handle: ColliderHandle) -> bool {
+        let collider = self.physics.collider_set.get(handle).unwrap();
+        handle != self.exclude
+            && !collider.is_sensor()
+            && collider.parent().is_none_or(|parent| {
+                !self
+                    .physics
+                    .rigid_body_set
+                    .get(parent)
+                    .unwrap()
+                    .is_dynamic()
+            })
+    }
+
+    // Returns the fraction of `motion` travelled before the first hit and the hit surface normal.
+    fn cast(&self, position: Vector3<f32>, motion: Vector3<f32>) -> Option<(f32, Vector3<f32>)> {
+        let physics = self.physics;
+        let filter = |handle: ColliderHandle| self.is_blocking(handle);
+        physics
+            .query_pipeline
+            .cast_shape(
+                &physics.collider_set,
+                &Isometry::translation(position.x, position.y, position.z),
+                &motion,
+                self.shape,
+                1.0,
+                physics.interaction_groups,
+                Some(&filter),
+            )
+            .map(|(_, toi)| (toi.toi, toi.normal1.into_inner()))
+    }
+
+    fn intersects(&self, position: Vector3<f32>) -> bool {
+        let physics = self.physics;
+        let filter = |handle: ColliderHandle| self.is_blocking(handle);
+        physics
+            .query_pipeline
+            .intersection_with_shape(
+                &physics.collider_set,
+                &Isometry::translation(position.x, position.y, position.z),
+                self.shape,
+                physics.interaction_groups,
+                Some(&filter),
+            )
+            .is_some()
+    }
+
+    // Moves along `motion`, projecting what is left of it on every surface that is hit.
+    // Returns the final position and the normals of the surfaces that were hit.
+    fn slide(
+        &self,
+        mut position: Vector3<f32>,
+        mut motion: Vector3<f32>,
+        horizontal: bool,
+    ) -> (Vector3<f32>, Vec<Vector3<f32>>) {
+        let mut normals = Vec::new();
+        for _ in 0..MAX_SLIDE_ITERATIONS {
+            let distance = motion.norm();
+            if distance <= f32::EPSILON {
+                break;
+            }
+            match self.cast(position, motion) {
+                Some((toi, normal)) => {
+                    position += motion / distance * (toi * distance - SKIN_WIDTH).max(0.0);
+                    let mut slide_normal = normal;
+                    if horizontal && normal.y < MIN_GROUND_NORMAL_Y {
+                        // Too steep to walk up, push back horizontally only
+                        slide_normal.y = 0.0;
+                        slide_normal = slide_normal
+                            .try_normalize(f32::EPSILON)
+                            .unwrap_or(slide_normal);
+                    }
+                    let remaining = motion * (1.0 - toi);
+                    motion = remaining - slide_normal * remaining.dot(&slide_normal);
+                    normals.push(normal);
+                }
+                None => {
+                    position += motion;
+                    break;
+                }
+            }
+        }
+        (position, normals)
+    }
+
+    // Lifts the body by the step height, moves forward and puts it back down. Fails when there is
+    // no walkable ground under the raised body.
+    fn step_up(&self, position: Vector3<f32>, motion: Vector3<f32>) -> Option<Vector3<f32>> {
+        let (raised, _) = self.slide(position, Vector3::new(0.0, STEP_HEIGHT, 0.0), false);
+        let (advanced, _) = self.slide(raised, motion, true);
+        self.snap_to_ground(advanced, raised.y - position.y)
+    }
+
+    fn snap_to_ground(&self, position: Vector3<f32>, max_distance: f32) -> Option<Vector3<f32>> {
+        let motion = Vector3::new(0.0, -max_distance, 0.0);
+        match self.cast(position, motion) {
+            Some((toi, normal)) if normal.y >= MIN_GROUND_NORMAL_Y => {
+                Some(position - Vector3::new(0.0, (toi * max_distance - SKIN_WIDTH).max(0.0), 0.0))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn horizontal_distance(a: &Vector3<f32>, b: &Vector3<f32>) -> f32 {
+    ((a.x - b.x).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
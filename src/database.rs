@@ -1,8 +1,13 @@
 use crate::audio::AudioContext;
+use crate::camera::FovType;
+use crate::input_binding::{Action, Binding, InputBindings};
 
+use crate::renderer::rendering_info::BackgroundType;
 use crate::renderer::Renderer;
 use crate::scene::game_score_scene::{
-    ClassicGameScoreDisplay, EliminationGameScoreDisplay, HitAndDodgeGameScoreDisplay,
+    ClassicGameScoreDisplay, EliminationGameScoreDisplay, GridshotGameScoreDisplay,
+    HitAndDodgeGameScoreDisplay, LeadGameScoreDisplay, ShrinkingGameScoreDisplay,
+    SprayControlGameScoreDisplay, SurvivalGameScoreDisplay,
 };
 use crate::scene::{GameDifficulty, GameMode};
 
@@ -22,6 +27,11 @@ pub enum GameModeScores {
     Classic(Vec<ClassicGameScoreDisplay>),
     Elimination(Vec<EliminationGameScoreDisplay>),
     HitAndDodge(Vec<HitAndDodgeGameScoreDisplay>),
+    Gridshot(Vec<GridshotGameScoreDisplay>),
+    Shrinking(Vec<ShrinkingGameScoreDisplay>),
+    Survival(Vec<SurvivalGameScoreDisplay>),
+    SprayControl(Vec<SprayControlGameScoreDisplay>),
+    Lead(Vec<LeadGameScoreDisplay>),
 }
 
 impl Default for GameModeScores {
@@ -88,6 +98,12 @@ impl GameModeScores {
                                         _ => unreachable!(),
                                     };
                                 }
+                                "background_type" => {
+                                    score.background_type = match row[idx] {
+                                        Value::I64(x) => BackgroundType::from(x as usize),
+                                        _ => unreachable!(),
+                                    };
+                                }
                                 "difficulty" => {}
                                 _ => unreachable!(),
                             }
@@ -159,6 +175,12 @@ impl GameModeScores {
                                         _ => unreachable!(),
                                     };
                                 }
+                                "background_type" => {
+                                    score.background_type = match row[idx] {
+                                        Value::I64(x) => BackgroundType::from(x as usize),
+                                        _ => unreachable!(),
+                                    };
+                                }
                                 "difficulty" => {}
                                 _ => unreachable!(),
                             }
@@ -209,6 +231,19 @@ impl GameModeScores {
                                         _ => unreachable!(),
                                     } as u16;
                                 }
+                                "near_miss" => {
+                                    score.near_miss = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as u16;
+                                }
+                                "headshot_rate" => {
+                                    score.headshot_rate = match row[idx] {
+                                        Value::F64(x) => x,
+                                        _ => unreachable!(),
+                                    }
+                                        as f32;
+                                }
                                 "avg_hit_time" => {
                                     score.avg_hit_time = match row[idx] {
                                         Value::F64(x) => x,
@@ -222,6 +257,12 @@ impl GameModeScores {
                                         _ => unreachable!(),
                                     };
                                 }
+                                "background_type" => {
+                                    score.background_type = match row[idx] {
+                                        Value::I64(x) => BackgroundType::from(x as usize),
+                                        _ => unreachable!(),
+                                    };
+                                }
                                 "difficulty" => {}
                                 _ => unreachable!(),
                             }
@@ -231,6 +272,323 @@ impl GameModeScores {
                 }
                 GameModeScores::HitAndDodge(score_rows)
             }
+            GameMode::Gridshot => {
+                let output = database
+                    .glue
+                    .execute(&format!("SELECT * FROM gridshot_game_score WHERE difficulty = {} ORDER BY created_at DESC", difficulty as u8))
+                    .unwrap();
+                let mut score_rows = Vec::new();
+                if let Payload::Select { labels, rows } = output {
+                    for row in rows {
+                        let mut score = GridshotGameScoreDisplay::new();
+                        for (idx, label) in labels.iter().enumerate() {
+                            match label.as_str() {
+                                "accuracy" => {
+                                    score.accuracy = match row[idx] {
+                                        Value::F64(x) => x,
+                                        _ => unreachable!(),
+                                    } as f32;
+                                }
+                                "hit" => {
+                                    score.hit = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as u16;
+                                }
+                                "miss" => {
+                                    score.miss = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as u16;
+                                }
+                                "score" => {
+                                    score.score = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as i32;
+                                }
+                                "targets_per_second" => {
+                                    score.targets_per_second = match row[idx] {
+                                        Value::F64(x) => x,
+                                        _ => unreachable!(),
+                                    }
+                                        as f32;
+                                }
+                                "avg_inter_target_angle" => {
+                                    score.avg_inter_target_angle = match row[idx] {
+                                        Value::F64(x) => x,
+                                        _ => unreachable!(),
+                                    }
+                                        as f32;
+                                }
+                                "created_at" => {
+                                    score.created_at = match row[idx] {
+                                        Value::Timestamp(x) => x,
+                                        _ => unreachable!(),
+                                    };
+                                }
+                                "background_type" => {
+                                    score.background_type = match row[idx] {
+                                        Value::I64(x) => BackgroundType::from(x as usize),
+                                        _ => unreachable!(),
+                                    };
+                                }
+                                "difficulty" => {}
+                                _ => unreachable!(),
+                            }
+                        }
+                        score_rows.push(score);
+                    }
+                }
+                GameModeScores::Gridshot(score_rows)
+            }
+            GameMode::Shrinking => {
+                let output = database
+                    .glue
+                    .execute(&format!("SELECT * FROM shrinking_game_score WHERE difficulty = {} ORDER BY created_at DESC", difficulty as u8))
+                    .unwrap();
+                let mut score_rows = Vec::new();
+                if let Payload::Select { labels, rows } = output {
+                    for row in rows {
+                        let mut score = ShrinkingGameScoreDisplay::new();
+                        for (idx, label) in labels.iter().enumerate() {
+                            match label.as_str() {
+                                "accuracy" => {
+                                    score.accuracy = match row[idx] {
+                                        Value::F64(x) => x,
+                                        _ => unreachable!(),
+                                    } as f32;
+                                }
+                                "hit" => {
+                                    score.hit = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as u16;
+                                }
+                                "miss" => {
+                                    score.miss = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as u16;
+                                }
+                                "score" => {
+                                    score.score = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as i32;
+                                }
+                                "avg_hit_radius" => {
+                                    score.avg_hit_radius = match row[idx] {
+                                        Value::F64(x) => x,
+                                        _ => unreachable!(),
+                                    }
+                                        as f32;
+                                }
+                                "created_at" => {
+                                    score.created_at = match row[idx] {
+                                        Value::Timestamp(x) => x,
+                                        _ => unreachable!(),
+                                    };
+                                }
+                                "background_type" => {
+                                    score.background_type = match row[idx] {
+                                        Value::I64(x) => BackgroundType::from(x as usize),
+                                        _ => unreachable!(),
+                                    };
+                                }
+                                "difficulty" => {}
+                                _ => unreachable!(),
+                            }
+                        }
+                        score_rows.push(score);
+                    }
+                }
+                GameModeScores::Shrinking(score_rows)
+            }
+            GameMode::Survival => {
+                let output = database
+                    .glue
+                    .execute(&format!("SELECT * FROM survival_game_score WHERE difficulty = {} ORDER BY created_at DESC", difficulty as u8))
+                    .unwrap();
+                let mut score_rows = Vec::new();
+                if let Payload::Select { labels, rows } = output {
+                    for row in rows {
+                        let mut score = SurvivalGameScoreDisplay::new();
+                        for (idx, label) in labels.iter().enumerate() {
+                            match label.as_str() {
+                                "accuracy" => {
+                                    score.accuracy = match row[idx] {
+                                        Value::F64(x) => x,
+                                        _ => unreachable!(),
+                                    } as f32;
+                                }
+                                "hit" => {
+                                    score.hit = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as u16;
+                                }
+                                "miss" => {
+                                    score.miss = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as u16;
+                                }
+                                "waves_cleared" => {
+                                    score.waves_cleared = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    }
+                                        as u16;
+                                }
+                                "time_survived" => {
+                                    score.time_survived = match row[idx] {
+                                        Value::F64(x) => x,
+                                        _ => unreachable!(),
+                                    }
+                                        as f32;
+                                }
+                                "created_at" => {
+                                    score.created_at = match row[idx] {
+                                        Value::Timestamp(x) => x,
+                                        _ => unreachable!(),
+                                    };
+                                }
+                                "background_type" => {
+                                    score.background_type = match row[idx] {
+                                        Value::I64(x) => BackgroundType::from(x as usize),
+                                        _ => unreachable!(),
+                                    };
+                                }
+                                "difficulty" => {}
+                                _ => unreachable!(),
+                            }
+                        }
+                        score_rows.push(score);
+                    }
+                }
+                GameModeScores::Survival(score_rows)
+            }
+            GameMode::SprayControl => {
+                let output = database
+                    .glue
+                    .execute(&format!("SELECT * FROM spray_control_game_score WHERE difficulty = {} ORDER BY created_at DESC", difficulty as u8))
+                    .unwrap();
+                let mut score_rows = Vec::new();
+                if let Payload::Select { labels, rows } = output {
+                    for row in rows {
+                        let mut score = SprayControlGameScoreDisplay::new();
+                        for (idx, label) in labels.iter().enumerate() {
+                            match label.as_str() {
+                                "accuracy" => {
+                                    score.accuracy = match row[idx] {
+                                        Value::F64(x) => x,
+                                        _ => unreachable!(),
+                                    } as f32;
+                                }
+                                "hit" => {
+                                    score.hit = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as u16;
+                                }
+                                "miss" => {
+                                    score.miss = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as u16;
+                                }
+                                "avg_compensation_error" => {
+                                    score.avg_compensation_error = match row[idx] {
+                                        Value::F64(x) => x,
+                                        _ => unreachable!(),
+                                    }
+                                        as f32;
+                                }
+                                "created_at" => {
+                                    score.created_at = match row[idx] {
+                                        Value::Timestamp(x) => x,
+                                        _ => unreachable!(),
+                                    };
+                                }
+                                "background_type" => {
+                                    score.background_type = match row[idx] {
+                                        Value::I64(x) => BackgroundType::from(x as usize),
+                                        _ => unreachable!(),
+                                    };
+                                }
+                                "difficulty" => {}
+                                _ => unreachable!(),
+                            }
+                        }
+                        score_rows.push(score);
+                    }
+                }
+                GameModeScores::SprayControl(score_rows)
+            }
+            GameMode::Lead => {
+                let output = database
+                    .glue
+                    .execute(&format!("SELECT * FROM lead_game_score WHERE difficulty = {} ORDER BY created_at DESC", difficulty as u8))
+                    .unwrap();
+                let mut score_rows = Vec::new();
+                if let Payload::Select { labels, rows } = output {
+                    for row in rows {
+                        let mut score = LeadGameScoreDisplay::new();
+                        for (idx, label) in labels.iter().enumerate() {
+                            match label.as_str() {
+                                "accuracy" => {
+                                    score.accuracy = match row[idx] {
+                                        Value::F64(x) => x,
+                                        _ => unreachable!(),
+                                    } as f32;
+                                }
+                                "hit" => {
+                                    score.hit = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as u16;
+                                }
+                                "miss" => {
+                                    score.miss = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as u16;
+                                }
+                                "score" => {
+                                    score.score = match row[idx] {
+                                        Value::I64(x) => x,
+                                        _ => unreachable!(),
+                                    } as i32;
+                                }
+                                "avg_hit_distance" => {
+                                    score.avg_hit_distance = match row[idx] {
+                                        Value::F64(x) => x,
+                                        _ => unreachable!(),
+                                    }
+                                        as f32;
+                                }
+                                "created_at" => {
+                                    score.created_at = match row[idx] {
+                                        Value::Timestamp(x) => x,
+                                        _ => unreachable!(),
+                                    };
+                                }
+                                "background_type" => {
+                                    score.background_type = match row[idx] {
+                                        Value::I64(x) => BackgroundType::from(x as usize),
+                                        _ => unreachable!(),
+                                    };
+                                }
+                                "difficulty" => {}
+                                _ => unreachable!(),
+                            }
+                        }
+                        score_rows.push(score);
+                    }
+                }
+                GameModeScores::Lead(score_rows)
+            }
         }
     }
 }
@@ -318,6 +676,156 @@ impl Database {
     avg_hit_time FLOAT NOT NULL,
     hit_taken INTEGER NOT NULL,
     created_at TIMESTAMP NOT NULL,
+    headshot_rate FLOAT NOT NULL DEFAULT 0.0,
+)",
+            )
+            .unwrap();
+        // Databases created before headshots were tracked lack this column, for the others
+        // this fails because it already exists.
+        self.glue
+            .execute(
+                "ALTER TABLE hit_and_dodge_game_score ADD COLUMN headshot_rate FLOAT NOT NULL DEFAULT 0.0",
+            )
+            .ok();
+        self.glue
+            .execute(
+                "CREATE TABLE IF NOT EXISTS gridshot_game_score (
+    difficulty INTEGER NOT NULL,
+    accuracy FLOAT NOT NULL,
+    hit INTEGER NOT NULL,
+    miss INTEGER NOT NULL,
+    score INTEGER NOT NULL,
+    targets_per_second FLOAT NOT NULL,
+    avg_inter_target_angle FLOAT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+)",
+            )
+            .unwrap();
+        self.glue
+            .execute(
+                "CREATE TABLE IF NOT EXISTS shrinking_game_score (
+    difficulty INTEGER NOT NULL,
+    accuracy FLOAT NOT NULL,
+    hit INTEGER NOT NULL,
+    miss INTEGER NOT NULL,
+    score INTEGER NOT NULL,
+    avg_hit_radius FLOAT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+)",
+            )
+            .unwrap();
+        self.glue
+            .execute(
+                "CREATE TABLE IF NOT EXISTS survival_game_score (
+    difficulty INTEGER NOT NULL,
+    accuracy FLOAT NOT NULL,
+    hit INTEGER NOT NULL,
+    miss INTEGER NOT NULL,
+    waves_cleared INTEGER NOT NULL,
+    time_survived FLOAT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+)",
+            )
+            .unwrap();
+        self.glue
+            .execute(
+                "CREATE TABLE IF NOT EXISTS spray_control_game_score (
+    difficulty INTEGER NOT NULL,
+    accuracy FLOAT NOT NULL,
+    hit INTEGER NOT NULL,
+    miss INTEGER NOT NULL,
+    avg_compensation_error FLOAT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+)",
+            )
+            .unwrap();
+        self.glue
+            .execute(
+                "CREATE TABLE IF NOT EXISTS lead_game_score (
+    difficulty INTEGER NOT NULL,
+    accuracy FLOAT NOT NULL,
+    hit INTEGER NOT NULL,
+    miss INTEGER NOT NULL,
+    score INTEGER NOT NULL,
+    avg_hit_distance FLOAT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+)",
+            )
+            .unwrap();
+        // Runs saved before the environment could be picked used the fixed one of their mode.
+        // Fails for tables which already have the column.
+        for (table, background_type) in [
+            ("classic_game_score", BackgroundType::Forest),
+            ("elimination_game_score", BackgroundType::City),
+            ("hit_and_dodge_game_score", BackgroundType::Forest),
+            ("gridshot_game_score", BackgroundType::Forest),
+            ("shrinking_game_score", BackgroundType::Forest),
+            ("survival_game_score", BackgroundType::Forest),
+            ("spray_control_game_score", BackgroundType::Forest),
+            ("lead_game_score", BackgroundType::Forest),
+        ]
+        .iter()
+        {
+            self.glue
+                .execute(&format!(
+                    "ALTER TABLE {} ADD COLUMN background_type INTEGER NOT NULL DEFAULT {}",
+                    table, *background_type as u8
+                ))
+                .ok();
+        }
+        // Added after the environment column, so it has to be altered in after it.
+        self.glue
+            .execute(
+                "ALTER TABLE hit_and_dodge_game_score ADD COLUMN near_miss INTEGER NOT NULL DEFAULT 0",
+            )
+            .ok();
+        // The old sensitivity scaled the mouse movement by the frame time, so it has no exact
+        // equivalent in degrees per count. `* 0.022` is an approximation chosen so the old default
+        // of 0.5 becomes the new default of 0.011. The conversion only runs when the column was
+        // just added, it fails for databases which already have it.
+        if self
+            .glue
+            .execute(
+                "ALTER TABLE settings ADD COLUMN degrees_per_count FLOAT NOT NULL DEFAULT 0.011",
+            )
+            .is_ok()
+        {
+            self.glue
+                .execute("UPDATE settings SET degrees_per_count = mouse_sensitivity * 0.022")
+                .unwrap();
+        }
+        self.glue
+            .execute("ALTER TABLE settings ADD COLUMN mouse_dpi FLOAT NOT NULL DEFAULT 800.0")
+            .ok();
+        self.glue
+            .execute("ALTER TABLE settings ADD COLUMN zoom_sensitivity FLOAT NOT NULL DEFAULT 1.0")
+            .ok();
+        self.glue
+            .execute(
+                "ALTER TABLE settings ADD COLUMN zoom_sensitivity_matched BOOLEAN NOT NULL DEFAULT TRUE",
+            )
+            .ok();
+        self.glue
+            .execute("ALTER TABLE settings ADD COLUMN fov FLOAT NOT NULL DEFAULT 90.0")
+            .ok();
+        self.glue
+            .execute("ALTER TABLE settings ADD COLUMN fov_type INTEGER NOT NULL DEFAULT 1")
+            .ok();
+        self.glue
+            .execute("ALTER TABLE settings ADD COLUMN kick_intensity FLOAT NOT NULL DEFAULT 1.0")
+            .ok();
+        self.glue
+            .execute("ALTER TABLE settings ADD COLUMN shake_intensity FLOAT NOT NULL DEFAULT 1.0")
+            .ok();
+        self.glue
+            .execute("ALTER TABLE settings ADD COLUMN flinch_intensity FLOAT NOT NULL DEFAULT 1.0")
+            .ok();
+        // Only the actions that were rebound have a row, the others use their default binding.
+        self.glue
+            .execute(
+                "CREATE TABLE IF NOT EXISTS input_bindings (
+    action TEXT NOT NULL,
+    binding TEXT NOT NULL,
 )",
             )
             .unwrap();
@@ -331,7 +839,10 @@ impl Database {
                         TRUE, 2.0,\
                         TRUE, 6.0, 20.0, 5.0,\
                         TRUE, 3.0, 6.0, 49.0,\
-                        0.5)",
+                        0.5, 0.011, 800.0,\
+                        1.0, TRUE,\
+                        90.0, 1,\
+                        1.0, 1.0, 1.0)",
                     )
                     .unwrap();
             }
@@ -363,8 +874,58 @@ impl Database {
                                 _ => unreachable!(),
                             } as u32;
                     }
-                    "mouse_sensitivity" => {
-                        renderer.camera.sensitivity = match rows[0][idx] {
+                    // Replaced by degrees_per_count
+                    "mouse_sensitivity" => {}
+                    "degrees_per_count" => {
+                        renderer.camera.degrees_per_count = match rows[0][idx] {
+                            Value::F64(x) => x,
+                            _ => unreachable!(),
+                        } as f32;
+                    }
+                    "mouse_dpi" => {
+                        renderer.camera.mouse_dpi = match rows[0][idx] {
+                            Value::F64(x) => x,
+                            _ => unreachable!(),
+                        } as f32;
+                    }
+                    "zoom_sensitivity" => {
+                        renderer.camera.zoom_sensitivity = match rows[0][idx] {
+                            Value::F64(x) => x,
+                            _ => unreachable!(),
+                        } as f32;
+                    }
+                    "zoom_sensitivity_matched" => {
+                        renderer.camera.is_zoom_sensitivity_matched = match rows[0][idx] {
+                            Value::Bool(x) => x,
+                            _ => unreachable!(),
+                        };
+                    }
+                    "fov" => {
+                        renderer.camera.fov = match rows[0][idx] {
+                            Value::F64(x) => x,
+                            _ => unreachable!(),
+                        } as f32;
+                    }
+                    "fov_type" => {
+                        renderer.camera.fov_type = match rows[0][idx] {
+                            Value::I64(x) => FovType::from(x as usize),
+                            _ => unreachable!(),
+                        };
+                    }
+                    "kick_intensity" => {
+                        renderer.camera.effects.kick_intensity = match rows[0][idx] {
+                            Value::F64(x) => x,
+                            _ => unreachable!(),
+                        } as f32;
+                    }
+                    "shake_intensity" => {
+                        renderer.camera.effects.shake_intensity = match rows[0][idx] {
+                            Value::F64(x) => x,
+                            _ => unreachable!(),
+                        } as f32;
+                    }
+                    "flinch_intensity" => {
+                        renderer.camera.effects.flinch_intensity = match rows[0][idx] {
                             Value::F64(x) => x,
                             _ => unreachable!(),
                         } as f32;
@@ -452,4 +1013,38 @@ impl Database {
             }
         }
     }
+
+    pub fn init_bindings(&mut self, bindings: &mut InputBindings) {
+        let output = self.glue.execute("SELECT * FROM input_bindings").unwrap();
+        if let Payload::Select { rows, .. } = output {
+            for row in rows {
+                let (action, binding) = match (&row[0], &row[1]) {
+                    (Value::Str(action), Value::Str(binding)) => (action, binding),
+                    _ => unreachable!(),
+                };
+                // Rows written by a version with other actions or keys are skipped
+                if let (Some(action), Some(binding)) =
+                    (Action::from_id(action), Binding::from_name(binding))
+                {
+                    bindings.rebind(action, binding);
+                }
+            }
+        }
+    }
+
+    pub fn save_bindings(&mut self, bindings: &InputBindings) {
+        self.glue.execute("DELETE FROM input_bindings").unwrap();
+        for action in Action::ALL.iter() {
+            let binding = bindings.get(*action);
+            if binding != action.default_binding() {
+                self.glue
+                    .execute(&format!(
+                        "INSERT INTO input_bindings VALUES ('{}', '{}')",
+                        action.id(),
+                        binding.name()
+                    ))
+                    .unwrap();
+            }
+        }
+    }
 }
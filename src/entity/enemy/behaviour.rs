@@ -0,0 +1,392 @@
+use crate::renderer::render_objects::MaterialType;
+use std::sync::Arc;
+
+/// Behaviours shipped with the game, as (name, definition) pairs. The build script embeds every
+/// `.enemy` file of `assets/enemies`, named after the file.
+const BUILTIN_ARCHETYPES: &[(&str, &str)] =
+    include!(concat!(env!("OUT_DIR"), "/enemy_archetypes.rs"));
+
+// Angle in degrees under which the enemy counts as facing the player.
+const FACING_ANGLE: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnemyModel {
+    Gunman,
+    Swordman,
+}
+
+/// What the enemy keeps doing while it is in a state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Activity {
+    Idle,
+    // Turns toward the player without moving
+    Turn,
+    // Walks toward the player, around obstacles when they are out of sight
+    Chase,
+    // Walks between random points of the arena
+    Wander,
+}
+
+/// What the enemy does once when it enters a state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Fire,
+    Melee,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    Sight,
+    NoSight,
+    Facing,
+    // Seconds spent in the current state
+    Elapsed(f32),
+    // Distance to the player in meters
+    Near(f32),
+    Far(f32),
+}
+
+/// What the enemy knows about the player this frame.
+pub struct Senses {
+    pub has_line_of_sight: bool,
+    pub distance: f32,
+    // Angle between where the enemy looks and the player, in degrees
+    pub facing_angle: f32,
+}
+
+impl Condition {
+    fn holds(&self, senses: &Senses, elapsed: f32) -> bool {
+        match *self {
+            Condition::Sight => senses.has_line_of_sight,
+            Condition::NoSight => !senses.has_line_of_sight,
+            Condition::Facing => senses.facing_angle <= FACING_ANGLE,
+            Condition::Elapsed(duration) => elapsed >= duration,
+            Condition::Near(distance) => senses.distance <= distance,
+            Condition::Far(distance) => senses.distance > distance,
+        }
+    }
+}
+
+pub struct Transition {
+    pub target: usize,
+    // Every condition has to hold for the transition to be taken
+    pub conditions: Vec<Condition>,
+}
+
+pub struct BehaviourState {
+    pub name: String,
+    pub activity: Activity,
+    pub action: Option<Action>,
+    pub material: Option<MaterialType>,
+    // Checked in the order they are written, the first one that holds is taken
+    pub transitions: Vec<Transition>,
+}
+
+/// An enemy type described in data: the model it's drawn with, how fast it walks and a state
+/// machine of what it does. The first state is the one the enemy spawns in.
+///
+/// Definitions are line based, `#` starts a comment:
+///
+/// ```text
+/// model gunman|swordman
+/// speed <m/s>
+/// state <name>
+/// do idle|turn|chase|wander
+/// enter fire|melee
+/// color green|yellow|white|black|red|orange
+/// go <state> if <condition>...
+/// ```
+///
+/// Conditions are `sight`, `!sight`, `facing`, `elapsed <s>`, `near <m>` and `far <m>`. The
+/// `do`, `enter`, `color` and `go` lines apply to the state above them.
+pub struct EnemyArchetype {
+    pub model: EnemyModel,
+    pub speed: f32,
+    pub states: Vec<BehaviourState>,
+}
+
+impl EnemyArchetype {
+    pub fn parse(name: &str, source: &str) -> Result<Self, String> {
+        let mut model = None;
+        let mut speed = None;
+        let mut states = Vec::<BehaviourState>::new();
+        // Transitions keep the target name and their line until every state is known
+        let mut pending_transitions = Vec::<(usize, usize, String, usize)>::new();
+
+        for (line_index, line) in source.lines().enumerate() {
+            let line_number = line_index + 1;
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: &str| format!("{}:{}: {}", name, line_number, message);
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            let argument = |index: usize| {
+                words
+                    .get(index)
+                    .copied()
+                    .ok_or_else(|| error("missing argument"))
+            };
+            let current_state = states.len().checked_sub(1);
+
+            match words[0] {
+                "model" => {
+                    model = Some(match argument(1)? {
+                        "gunman" => EnemyModel::Gunman,
+                        "swordman" => EnemyModel::Swordman,
+                        other => return Err(error(&format!("unknown model '{}'", other))),
+                    });
+                }
+                "speed" => {
+                    speed = Some(parse_number(argument(1)?).ok_or_else(|| error("bad speed"))?);
+                }
+                "state" => {
+                    let state_name = argument(1)?;
+                    if states.iter().any(|state| state.name == state_name) {
+                        return Err(error(&format!("state '{}' is defined twice", state_name)));
+                    }
+                    states.push(BehaviourState {
+                        name: state_name.to_string(),
+                        activity: Activity::Idle,
+                        action: None,
+                        material: None,
+                        transitions: Vec::new(),
+                    });
+                }
+                keyword @ ("do" | "enter" | "color" | "go") => {
+                    let state_index = current_state
+                        .ok_or_else(|| error(&format!("'{}' outside of a state", keyword)))?;
+                    let state = &mut states[state_index];
+                    match keyword {
+                        "do" => {
+                            state.activity = match argument(1)? {
+                                "idle" => Activity::Idle,
+                                "turn" => Activity::Turn,
+                                "chase" => Activity::Chase,
+                                "wander" => Activity::Wander,
+                                other => {
+                                    return Err(error(&format!("unknown activity '{}'", other)))
+                                }
+                            };
+                        }
+                        "enter" => {
+                            state.action = Some(match argument(1)? {
+                                "fire" => Action::Fire,
+                                "melee" => Action::Melee,
+                                other => return Err(error(&format!("unknown action '{}'", other))),
+                            });
+                        }
+                        "color" => {
+                            state.material =
+                                Some(parse_material(argument(1)?).ok_or_else(|| {
+                                    error(&format!("unknown color '{}'", words[1]))
+                                })?);
+                        }
+                        _ => {
+                            let target = argument(1)?.to_string();
+                            let mut conditions = Vec::new();
+                            if words.len() > 2 {
+                                if words[2] != "if" {
+                                    return Err(error("expected 'if' after the target state"));
+                                }
+                                let mut index = 3;
+                                while index < words.len() {
+                                    let condition = match words[index] {
+                                        "sight" => Condition::Sight,
+                                        "!sight" => Condition::NoSight,
+                                        "facing" => Condition::Facing,
+                                        measure @ ("elapsed" | "near" | "far") => {
+                                            index += 1;
+                                            let value = parse_number(argument(index)?)
+                                                .ok_or_else(|| error("bad number"))?;
+                                            match measure {
+                                                "elapsed" => Condition::Elapsed(value),
+                                                "near" => Condition::Near(value),
+                                                _ => Condition::Far(value),
+                                            }
+                                        }
+                                        other => {
+                                            return Err(error(&format!(
+                                                "unknown condition '{}'",
+                                                other
+                                            )))
+                                        }
+                                    };
+                                    conditions.push(condition);
+                                    index += 1;
+                                }
+                            }
+                            state.transitions.push(Transition {
+                                target: 0,
+                                conditions,
+                            });
+                            pending_transitions.push((
+                                state_index,
+                                state.transitions.len() - 1,
+                                target,
+                                line_number,
+                            ));
+                        }
+                    }
+                }
+                other => return Err(error(&format!("unknown keyword '{}'", other))),
+            }
+        }
+
+        for (state_index, transition_index, target, line_number) in pending_transitions {
+            let target_index = states
+                .iter()
+                .position(|state| state.name == target)
+                .ok_or_else(|| format!("{}:{}: unknown state '{}'", name, line_number, target))?;
+            states[state_index].transitions[transition_index].target = target_index;
+        }
+        if states.is_empty() {
+            return Err(format!("{}: no state is defined", name));
+        }
+
+        Ok(Self {
+            model: model.ok_or_else(|| format!("{}: missing model", name))?,
+            speed: speed.ok_or_else(|| format!("{}: missing speed", name))?,
+            states,
+        })
+    }
+}
+
+fn parse_number(word: &str) -> Option<f32> {
+    word.parse::<f32>().ok().filter(|value| *value >= 0.0)
+}
+
+fn parse_material(word: &str) -> Option<MaterialType> {
+    match word {
+        "green" => Some(MaterialType::Green),
+        "yellow" => Some(MaterialType::Yellow),
+        "white" => Some(MaterialType::White),
+        "black" => Some(MaterialType::Black),
+        "red" => Some(MaterialType::Red),
+        "orange" => Some(MaterialType::Orange),
+        _ => None,
+    }
+}
+
+/// Parses the archetypes shipped with the game. They are embedded in the binary, so a broken
+/// definition is a bug and panics with the line it's on.
+pub fn load_archetypes() -> Vec<Arc<EnemyArchetype>> {
+    BUILTIN_ARCHETYPES
+        .iter()
+        .map(|(name, source)| Arc::new(EnemyArchetype::parse(name, source).unwrap()))
+        .collect()
+}
+
+/// Where an enemy is in its archetype's state machine.
+pub struct Behaviour {
+    archetype: Arc<EnemyArchetype>,
+    state: usize,
+    elapsed: f32,
+}
+
+impl Behaviour {
+    pub fn new(archetype: Arc<EnemyArchetype>) -> Self {
+        Self {
+            archetype,
+            state: 0,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn get_archetype(&self) -> &EnemyArchetype {
+        &self.archetype
+    }
+
+    pub fn get_state(&self) -> &BehaviourState {
+        &self.archetype.states[self.state]
+    }
+
+    /// Advances the state timer and takes the first transition whose conditions hold. Returns
+    /// true when a state was entered, so its action has to be performed.
+    pub fn update(&mut self, delta_time: f32, senses: &Senses) -> bool {
+        self.elapsed += delta_time;
+        let elapsed = self.elapsed;
+        let next_state = self.archetype.states[self.state]
+            .transitions
+            .iter()
+            .find(|transition| {
+                transition
+                    .conditions
+                    .iter()
+                    .all(|condition| condition.holds(senses, elapsed))
+            })
+            .map(|transition| transition.target);
+        match next_state {
+            Some(next_state) => {
+                self.state = next_state;
+                self.elapsed = 0.0;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(source: &str) -> String {
+        match EnemyArchetype::parse("test", source) {
+            Ok(_) => panic!("the definition parsed"),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn builtin_archetypes_parse() {
+        assert!(!BUILTIN_ARCHETYPES.is_empty());
+        for (name, source) in BUILTIN_ARCHETYPES {
+            if let Err(error) = EnemyArchetype::parse(name, source) {
+                panic!("{}", error);
+            }
+        }
+    }
+
+    #[test]
+    fn transitions_resolve_to_state_indices() {
+        let archetype = EnemyArchetype::parse(
+            "test",
+            "model gunman\nspeed 2\nstate a\ngo b if near 3 sight\nstate b\ngo a",
+        )
+        .unwrap();
+        let transition = &archetype.states[0].transitions[0];
+        assert_eq!(transition.target, 1);
+        assert_eq!(
+            transition.conditions,
+            vec![Condition::Near(3.0), Condition::Sight]
+        );
+        assert_eq!(archetype.states[1].transitions[0].target, 0);
+    }
+
+    #[test]
+    fn unknown_keyword() {
+        let error = parse_error("model gunman\n\njump high");
+        assert!(error.starts_with("test:3:"), "{}", error);
+    }
+
+    #[test]
+    fn transition_to_undefined_state() {
+        let error = parse_error("model gunman\nspeed 2\nstate a\ngo nowhere\nstate b");
+        assert!(error.starts_with("test:4:"), "{}", error);
+    }
+
+    #[test]
+    fn duplicate_state() {
+        let error = parse_error("model gunman\nstate a\n# comment\nstate a");
+        assert!(error.starts_with("test:4:"), "{}", error);
+    }
+
+    #[test]
+    fn state_lines_before_any_state() {
+        let error = parse_error("model gunman\ndo chase\nstate a");
+        assert!(error.starts_with("test:2:"), "{}", error);
+        let error = parse_error("enter fire\nstate a");
+        assert!(error.starts_with("test:1:"), "{}", error);
+    }
+}
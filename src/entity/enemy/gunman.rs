@@ -2,16 +2,38 @@ use crate::animation::{InOutAnimation, InOutAnimationState};
 
 use crate::renderer::render_objects::MaterialType;
 
-use crate::entity::enemy::{HITTED_MATERIAL_DURATION, ROTATION_SPEED};
+use crate::entity::enemy::{
+    Health, HitZone, ENEMY_HEALTH, HITTED_MATERIAL_DURATION, ROTATION_SPEED,
+};
 use crate::entity::HasMaterial;
 
-use crate::scene::{IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION};
+use crate::scene::{GameDifficulty, IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION};
 use crate::timer::Timer;
-use nalgebra::{distance, Point, Unit, Vector2, Vector3};
-use rand::distributions::Uniform;
-use rand::prelude::*;
+use nalgebra::{distance, Point, Unit, UnitQuaternion, Vector2, Vector3};
+use rand::prelude::SmallRng;
+use rand::Rng;
 
-pub struct Bullet;
+pub struct Bullet {
+    // Smallest gap seen so far between the bullet's swept path and the player capsule
+    closest_approach: f32,
+}
+
+impl Bullet {
+    pub fn new() -> Self {
+        Self {
+            closest_approach: f32::MAX,
+        }
+    }
+
+    pub fn update_closest_approach(&mut self, distance: f32) {
+        self.closest_approach = self.closest_approach.min(distance);
+    }
+
+    /// Whether the bullet passed within `NEAR_MISS_DISTANCE` of the player without touching them.
+    pub fn is_near_miss(&self) -> bool {
+        self.closest_approach > 0.0 && self.closest_approach < NEAR_MISS_DISTANCE
+    }
+}
 
 impl HasMaterial for Bullet {
     fn get_material(&self) -> MaterialType {
@@ -21,9 +43,66 @@ impl HasMaterial for Bullet {
 
 pub const BULLET_SPEED: f32 = 50.0;
 pub const BULLET_RAD: f32 = 0.2;
+pub const NEAR_MISS_DISTANCE: f32 = 0.5;
+// Distance at which a waypoint of the path counts as reached.
+const WAYPOINT_RADIUS: f32 = 0.2;
+// Minimum time between two path plannings. A gunman that reached its firing position without
+// getting sight of the player would otherwise plan again on every frame.
+const REPLAN_COOLDOWN: f32 = 1.0;
+
+/// How well a gunman shoots, Hard gunmen react fast and lead their shots while Easy ones are
+/// slow, inaccurate and show when they are about to fire.
+#[derive(Clone, Copy)]
+pub struct GunmanAccuracy {
+    // Time the player has to stay in sight before the gunman starts aiming
+    pub reaction_delay: f32,
+    // Fraction of the player's velocity the shots are led by, 0 aims where the player is
+    pub lead_factor: f32,
+    // Half angle of the cone the shots spread in, in degrees
+    pub error_cone: f32,
+    pub burst_count: u32,
+    pub burst_interval: f32,
+    // Turns the gunman orange while it focuses
+    pub telegraph: bool,
+}
+
+impl GunmanAccuracy {
+    pub fn new(difficulty: GameDifficulty) -> Self {
+        match difficulty {
+            GameDifficulty::Easy => Self {
+                reaction_delay: 0.6,
+                lead_factor: 0.0,
+                error_cone: 4.0,
+                burst_count: 1,
+                burst_interval: 0.0,
+                telegraph: true,
+            },
+            GameDifficulty::Medium => Self {
+                reaction_delay: 0.3,
+                lead_factor: 0.5,
+                error_cone: 2.0,
+                burst_count: 2,
+                burst_interval: 0.15,
+                telegraph: true,
+            },
+            GameDifficulty::Hard => Self {
+                reaction_delay: 0.1,
+                lead_factor: 1.0,
+                error_cone: 0.75,
+                burst_count: 3,
+                burst_interval: 0.1,
+                telegraph: false,
+            },
+        }
+    }
+}
 
 pub enum ShootState {
-    Shoot(InOutAnimation),
+    Shoot {
+        animation: InOutAnimation,
+        shots_left: u32,
+        next_shot: Timer,
+    },
     Focus(Timer),
     Idle(Timer),
 }
@@ -46,33 +125,83 @@ pub struct Gunman {
     rotation_y: f32,
     dir: Vector3<f32>,
     material_state: EnemyMaterialState,
-    next_dest: Vector2<f32>,
+    // Waypoints left to walk, the next one is last
+    path: Vec<Vector2<f32>>,
+    is_seeking_sight: bool,
+    replan_timer: Timer,
+    // How long the player has been in sight without interruption
+    sight_time: f32,
     focus_time: f32,
     speed: f32,
+    accuracy: GunmanAccuracy,
+    health: Health,
 }
 
 impl Gunman {
-    pub fn new(rng: &mut SmallRng, focus_time: f32, speed: f32) -> Self {
+    pub fn new(focus_time: f32, speed: f32, accuracy: GunmanAccuracy) -> Self {
         Self {
             speed,
             focus_time,
+            accuracy,
+            sight_time: 0.0,
+            health: Health::new(ENEMY_HEALTH),
             shoot_state: ShootState::Idle(Timer::new(0.3)),
             rotation_y: 0.0,
             dir: Vector3::new(0.0, 1.0, 0.0),
             material_state: EnemyMaterialState::None,
-            next_dest: Vector2::new(
-                rng.sample(Uniform::new(-8.0, 8.0)),
-                rng.sample(Uniform::new(-8.0, 8.0)),
-            ),
+            path: Vec::new(),
+            is_seeking_sight: false,
+            replan_timer: Timer::new_finished(),
         }
     }
 
+    /// Whether a new path has to be planned, either because the current one is walked or because
+    /// the player went out of sight and the gunman isn't already looking for a firing position.
+    /// Paths are planned at most once per `REPLAN_COOLDOWN`.
+    pub fn needs_path(&self, has_line_of_sight: bool) -> bool {
+        self.replan_timer.is_finished()
+            && (self.path.is_empty() || (!has_line_of_sight && !self.is_seeking_sight))
+    }
+
+    /// Replaces the path with `path`, ordered from the first waypoint to the destination.
+    pub fn set_path(&mut self, mut path: Vec<Vector2<f32>>, is_seeking_sight: bool) {
+        path.reverse();
+        self.path = path;
+        self.is_seeking_sight = is_seeking_sight;
+        self.replan_timer.reset(REPLAN_COOLDOWN);
+    }
+
+    /// Direction toward where the player will be when the bullet arrives, spread inside the error
+    /// cone.
+    pub fn aim(
+        accuracy: &GunmanAccuracy,
+        rng: &mut SmallRng,
+        obj_pos: &Vector3<f32>,
+        player_pos: &Vector3<f32>,
+        player_velocity: &Vector3<f32>,
+    ) -> Vector3<f32> {
+        let flight_time = (player_pos - obj_pos).norm() / BULLET_SPEED;
+        let target = player_pos + player_velocity * flight_time * accuracy.lead_factor;
+        let direction = Unit::new_normalize(target - obj_pos);
+
+        let perpendicular = Unit::try_new(direction.cross(&Vector3::y()), f32::EPSILON)
+            .unwrap_or_else(Vector3::x_axis);
+        let roll =
+            UnitQuaternion::from_axis_angle(&direction, rng.gen_range(0.0..std::f32::consts::TAU));
+        // The square root spreads the shots evenly over the cone instead of bunching them at
+        // its center
+        let error = accuracy.error_cone.to_radians() * rng.gen_range(0.0f32..1.0).sqrt();
+        UnitQuaternion::from_axis_angle(&(roll * perpendicular), error) * direction.into_inner()
+    }
+
     pub fn update(
         &mut self,
         rng: &mut SmallRng,
         delta_time: f32,
         obj_pos: &mut Vector3<f32>,
         player_pos: &Vector3<f32>,
+        player_velocity: &Vector3<f32>,
+        has_line_of_sight: bool,
     ) -> GunmanOp {
         if let EnemyMaterialState::Hitted(ref mut timer) = self.material_state {
             timer.update(delta_time);
@@ -81,17 +210,28 @@ impl Gunman {
             }
         }
 
-        let current_xz_pos = obj_pos.xz();
-        if distance(&Point::from(self.next_dest), &Point::from(current_xz_pos)) <= 0.5 {
-            self.next_dest = Vector2::new(
-                rng.sample(Uniform::new(-8.0, 8.0)),
-                rng.sample(Uniform::new(-8.0, 8.0)),
-            );
+        self.health.update(delta_time);
+        if self.health.is_dead() {
+            return GunmanOp::None;
+        }
+        self.replan_timer.update(delta_time);
+
+        if has_line_of_sight {
+            self.sight_time += delta_time;
         } else {
-            let dir = Unit::new_normalize(self.next_dest - current_xz_pos);
-            let next_pos = dir.into_inner() * self.speed * delta_time;
-            obj_pos.x += next_pos.x;
-            obj_pos.z += next_pos.y;
+            self.sight_time = 0.0;
+        }
+
+        let current_xz_pos = obj_pos.xz();
+        if let Some(waypoint) = self.path.last() {
+            if distance(&Point::from(*waypoint), &Point::from(current_xz_pos)) <= WAYPOINT_RADIUS {
+                self.path.pop();
+            } else {
+                let dir = Unit::new_normalize(waypoint - current_xz_pos);
+                let next_pos = dir.into_inner() * self.speed * delta_time;
+                obj_pos.x += next_pos.x;
+                obj_pos.z += next_pos.y;
+            }
         }
 
         let mut op = GunmanOp::None;
@@ -121,7 +261,7 @@ impl Gunman {
 
                 if is_rotating {
                     self.rotation_y = self.dir.x.atan2(self.dir.z);
-                } else if timer.is_finished() {
+                } else if timer.is_finished() && self.sight_time >= self.accuracy.reaction_delay {
                     self.shoot_state = ShootState::Focus(Timer::new(self.focus_time));
                 }
             }
@@ -129,19 +269,38 @@ impl Gunman {
                 timer.update(delta_time);
                 if timer.is_finished() {
                     op = GunmanOp::Shoot {
-                        dir: self.dir,
+                        dir: Self::aim(&self.accuracy, rng, obj_pos, player_pos, player_velocity),
                         pos: *obj_pos,
                     };
-                    self.shoot_state = ShootState::Shoot(InOutAnimation::new_started(
-                        IN_SHOOT_ANIM_DURATION,
-                        OUT_SHOOT_ANIM_DURATION,
-                    ));
+                    self.shoot_state = ShootState::Shoot {
+                        animation: InOutAnimation::new_started(
+                            IN_SHOOT_ANIM_DURATION,
+                            OUT_SHOOT_ANIM_DURATION,
+                        ),
+                        shots_left: self.accuracy.burst_count - 1,
+                        next_shot: Timer::new(self.accuracy.burst_interval),
+                    };
                 }
             }
-            ShootState::Shoot(ref mut anim) => {
-                anim.update(delta_time);
-                if let InOutAnimationState::Stopped = anim.get_state() {
-                    self.shoot_state = ShootState::Idle(Timer::new(0.5));
+            ShootState::Shoot {
+                ref mut animation,
+                ref mut shots_left,
+                ref mut next_shot,
+            } => {
+                animation.update(delta_time);
+                next_shot.update(delta_time);
+                if *shots_left > 0 && next_shot.is_finished() {
+                    *shots_left -= 1;
+                    next_shot.reset(self.accuracy.burst_interval);
+                    animation.trigger();
+                    op = GunmanOp::Shoot {
+                        dir: Self::aim(&self.accuracy, rng, obj_pos, player_pos, player_velocity),
+                        pos: *obj_pos,
+                    };
+                } else if *shots_left == 0 {
+                    if let InOutAnimationState::Stopped = animation.get_state() {
+                        self.shoot_state = ShootState::Idle(Timer::new(0.5));
+                    }
                 }
             }
         };
@@ -158,21 +317,37 @@ impl Gunman {
 
     pub fn shootanim(&self) -> f32 {
         match self.shoot_state {
-            ShootState::Shoot(ref anim) => -anim.get_value() * 1.5,
+            ShootState::Shoot { ref animation, .. } => -animation.get_value() * 1.5,
             _ => 0.0,
         }
     }
 
-    pub fn hit(&mut self) {
+    /// Returns true if the hit killed the enemy.
+    pub fn hit(&mut self, damage: f32, zone: HitZone) -> bool {
         self.material_state = EnemyMaterialState::Hitted(Timer::new(HITTED_MATERIAL_DURATION));
+        self.health.damage(damage, zone)
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health.is_dead()
+    }
+
+    pub fn is_need_to_be_deleted(&self) -> bool {
+        self.health.is_need_to_be_deleted()
     }
 }
 
 impl HasMaterial for Gunman {
     fn get_material(&self) -> MaterialType {
-        match self.material_state {
-            EnemyMaterialState::None => MaterialType::White,
-            EnemyMaterialState::Hitted(_) => MaterialType::Red,
+        if self.health.is_dead() {
+            return MaterialType::Black;
+        }
+        match (&self.material_state, &self.shoot_state) {
+            (EnemyMaterialState::Hitted(_), _) => MaterialType::Red,
+            (EnemyMaterialState::None, ShootState::Focus(_)) if self.accuracy.telegraph => {
+                MaterialType::Orange
+            }
+            (EnemyMaterialState::None, _) => MaterialType::White,
         }
     }
 }
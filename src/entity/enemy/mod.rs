@@ -1,7 +1,158 @@
+use crate::physics::GamePhysics;
+use crate::timer::Timer;
+use hecs::Entity;
+use nalgebra::{Isometry3, Vector3};
+use rapier3d::parry::shape::Cuboid;
+use rapier3d::prelude::ColliderHandle;
 
-
+pub mod behaviour;
 pub mod gunman;
+pub mod scripted;
 pub mod swordman;
 
 pub const HITTED_MATERIAL_DURATION: f32 = 0.1;
 pub const ROTATION_SPEED: f32 = 8.0;
+
+pub const ENEMY_HEALTH: f32 = 100.0;
+pub const SHOT_DAMAGE: f32 = 35.0;
+pub const DEATH_DURATION: f32 = 1.0;
+
+// A melee swing hits everything inside a box this far in front of the enemy
+const MELEE_REACH: f32 = 0.9;
+const MELEE_HALF_WIDTH: f32 = 0.6;
+const MELEE_HALF_HEIGHT: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u8)]
+pub enum HitZone {
+    Body = 1,
+    Head = 2,
+    Limb = 3,
+}
+
+impl HitZone {
+    pub fn damage_multiplier(&self) -> f32 {
+        match self {
+            HitZone::Head => 4.0,
+            HitZone::Body => 1.0,
+            HitZone::Limb => 0.6,
+        }
+    }
+
+    /// Collider user data of an enemy part. The lower 64 bits keep the entity bits, so
+    /// `Entity::from_bits(user_data as u64)` still resolves the owner, and the zone is
+    /// stored right above them.
+    pub fn user_data(&self, entity: Entity) -> u128 {
+        entity.to_bits().get() as u128 | (*self as u128) << 64
+    }
+
+    pub fn from_user_data(user_data: u128) -> Option<HitZone> {
+        match (user_data >> 64) as u8 {
+            1 => Some(HitZone::Body),
+            2 => Some(HitZone::Head),
+            3 => Some(HitZone::Limb),
+            _ => None,
+        }
+    }
+}
+
+pub struct Health {
+    health: f32,
+    death_timer: Option<Timer>,
+}
+
+impl Health {
+    pub fn new(health: f32) -> Self {
+        Self {
+            health,
+            death_timer: None,
+        }
+    }
+
+    /// Applies the damage of a shot landing on `zone` and returns true if it killed the enemy.
+    pub fn damage(&mut self, damage: f32, zone: HitZone) -> bool {
+        if self.is_dead() {
+            return false;
+        }
+        self.health -= damage * zone.damage_multiplier();
+        if self.health <= 0.0 {
+            self.death_timer = Some(Timer::new(DEATH_DURATION));
+            return true;
+        }
+        false
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        if let Some(ref mut timer) = self.death_timer {
+            timer.update(delta_time);
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.death_timer.is_some()
+    }
+
+    pub fn is_need_to_be_deleted(&self) -> bool {
+        match self.death_timer {
+            Some(ref timer) => timer.is_finished(),
+            None => false,
+        }
+    }
+}
+
+/// Whether the player collider is inside the swing volume in front of an enemy standing at
+/// `position` and looking along `direction`.
+pub fn is_melee_hitting(
+    physics: &GamePhysics,
+    position: &Vector3<f32>,
+    direction: &Vector3<f32>,
+    rotation: Vector3<f32>,
+) -> bool {
+    let facing = Vector3::new(direction.x, 0.0, direction.z)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or_else(Vector3::z);
+    let volume_position = Isometry3::new(position + facing * MELEE_REACH, rotation);
+    let filter =
+        |handle: ColliderHandle| physics.collider_set.get(handle).unwrap().user_data == u128::MAX;
+    physics
+        .query_pipeline
+        .intersection_with_shape(
+            &physics.collider_set,
+            &volume_position,
+            &Cuboid::new(Vector3::new(
+                MELEE_HALF_WIDTH,
+                MELEE_HALF_HEIGHT,
+                MELEE_HALF_WIDTH,
+            )),
+            physics.interaction_groups,
+            Some(&filter),
+        )
+        .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hecs::World;
+
+    #[test]
+    fn hit_zone_round_trips_through_user_data() {
+        let mut world = World::new();
+        let entity = world.spawn(());
+        for zone in [HitZone::Body, HitZone::Head, HitZone::Limb] {
+            let user_data = zone.user_data(entity);
+            assert_eq!(HitZone::from_user_data(user_data), Some(zone));
+            assert_eq!(Entity::from_bits(user_data as u64), Some(entity));
+        }
+    }
+
+    #[test]
+    fn plain_entity_user_data_has_no_hit_zone() {
+        let mut world = World::new();
+        let entity = world.spawn(());
+        assert_eq!(
+            HitZone::from_user_data(entity.to_bits().get() as u128),
+            None
+        );
+    }
+}
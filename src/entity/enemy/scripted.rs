@@ -0,0 +1,270 @@
+use crate::animation::{InOutAnimation, InOutAnimationState};
+use crate::entity::enemy::behaviour::{
+    Action, Activity, Behaviour, EnemyArchetype, EnemyModel, Senses,
+};
+use crate::entity::enemy::gunman::{Gunman, GunmanAccuracy};
+use crate::entity::enemy::{
+    Health, HitZone, ENEMY_HEALTH, HITTED_MATERIAL_DURATION, ROTATION_SPEED,
+};
+use crate::entity::HasMaterial;
+use crate::renderer::render_objects::MaterialType;
+use crate::scene::{IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION};
+use crate::timer::Timer;
+use nalgebra::{distance, Point, Unit, Vector2, Vector3};
+use rand::prelude::SmallRng;
+use std::sync::Arc;
+
+// Distance at which a waypoint of the path counts as reached.
+const WAYPOINT_RADIUS: f32 = 0.2;
+// Chasing enemies stop this close to the player instead of walking into them
+const CHASE_STOP_DISTANCE: f32 = 1.0;
+const SWING_DURATION: f32 = 0.2;
+// Minimum time between two path plannings while staying in the same state. A destination that
+// can't be reached gives an empty path, which would otherwise be planned again on every frame.
+const REPLAN_COOLDOWN: f32 = 1.0;
+
+pub enum ScriptedEnemyOp {
+    None,
+    Fire {
+        pos: Vector3<f32>,
+        dir: Vector3<f32>,
+    },
+    // The swing reached its peak, whatever is in front of the enemy gets hit
+    Melee,
+}
+
+pub enum EnemyMaterialState {
+    None,
+    Hitted(Timer),
+}
+
+/// Enemy driven by an `EnemyArchetype` instead of hardcoded logic.
+pub struct ScriptedEnemy {
+    behaviour: Behaviour,
+    rotation_y: f32,
+    dir: Vector3<f32>,
+    material_state: EnemyMaterialState,
+    // Waypoints left to walk, the next one is last
+    path: Vec<Vector2<f32>>,
+    replan_timer: Timer,
+    // Shoot recoil or sword swing, depending on the last action
+    animation: InOutAnimation,
+    is_swinging: bool,
+    accuracy: GunmanAccuracy,
+    health: Health,
+}
+
+impl ScriptedEnemy {
+    pub fn new(archetype: Arc<EnemyArchetype>, accuracy: GunmanAccuracy) -> Self {
+        Self {
+            behaviour: Behaviour::new(archetype),
+            rotation_y: 0.0,
+            dir: Vector3::z(),
+            material_state: EnemyMaterialState::None,
+            path: Vec::new(),
+            replan_timer: Timer::new_finished(),
+            animation: InOutAnimation::new(IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION),
+            is_swinging: false,
+            accuracy,
+            health: Health::new(ENEMY_HEALTH),
+        }
+    }
+
+    pub fn get_model(&self) -> EnemyModel {
+        self.behaviour.get_archetype().model
+    }
+
+    pub fn get_activity(&self) -> Activity {
+        self.behaviour.get_state().activity
+    }
+
+    /// Whether the current activity walks somewhere and the last path has been walked. Chasing
+    /// goes straight at the player while they are in sight, so it only needs a path out of sight.
+    /// Paths are planned at most once per `REPLAN_COOLDOWN`.
+    pub fn needs_path(&self, has_line_of_sight: bool) -> bool {
+        let is_walking = match self.get_activity() {
+            Activity::Chase => !has_line_of_sight,
+            Activity::Wander => true,
+            Activity::Idle | Activity::Turn => false,
+        };
+        is_walking && self.path.is_empty() && self.replan_timer.is_finished()
+    }
+
+    /// Replaces the path with `path`, ordered from the first waypoint to the destination.
+    pub fn set_path(&mut self, mut path: Vec<Vector2<f32>>) {
+        path.reverse();
+        self.path = path;
+        self.replan_timer.reset(REPLAN_COOLDOWN);
+    }
+
+    // Rotates the heading toward `target_dir` at the same rate as the other enemies.
+    fn turn_towards(&mut self, target_dir: Vector3<f32>, delta_time: f32) {
+        let delta_dir = match Unit::try_new(target_dir - self.dir, f32::EPSILON) {
+            Some(delta_dir) => delta_dir,
+            None => return,
+        };
+        if delta_dir.x.abs() > 0.01 {
+            self.dir.x += delta_dir.x * delta_time * ROTATION_SPEED;
+        }
+        if delta_dir.z.abs() > 0.01 {
+            self.dir.z += delta_dir.z * delta_time * ROTATION_SPEED;
+        }
+        self.rotation_y = self.dir.x.atan2(self.dir.z);
+    }
+
+    // Walks toward `target` and turns to face the way it's walking.
+    fn walk_towards(&mut self, target: Vector2<f32>, obj_pos: &mut Vector3<f32>, delta_time: f32) {
+        let speed = self.behaviour.get_archetype().speed;
+        if let Some(dir) = Unit::try_new(target - obj_pos.xz(), f32::EPSILON) {
+            let next_pos = dir.into_inner() * speed * delta_time;
+            obj_pos.x += next_pos.x;
+            obj_pos.z += next_pos.y;
+            self.turn_towards(Vector3::new(dir.x, 0.0, dir.y), delta_time);
+        }
+    }
+
+    fn follow_path(&mut self, obj_pos: &mut Vector3<f32>, delta_time: f32) {
+        if let Some(waypoint) = self.path.last().copied() {
+            if distance(&Point::from(waypoint), &Point::from(obj_pos.xz())) <= WAYPOINT_RADIUS {
+                self.path.pop();
+            } else {
+                self.walk_towards(waypoint, obj_pos, delta_time);
+            }
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        rng: &mut SmallRng,
+        delta_time: f32,
+        obj_pos: &mut Vector3<f32>,
+        player_pos: &Vector3<f32>,
+        player_velocity: &Vector3<f32>,
+        has_line_of_sight: bool,
+    ) -> ScriptedEnemyOp {
+        if let EnemyMaterialState::Hitted(ref mut timer) = self.material_state {
+            timer.update(delta_time);
+            if timer.is_finished() {
+                self.material_state = EnemyMaterialState::None;
+            }
+        }
+
+        self.health.update(delta_time);
+        if self.health.is_dead() {
+            return ScriptedEnemyOp::None;
+        }
+        self.replan_timer.update(delta_time);
+
+        let mut op = ScriptedEnemyOp::None;
+
+        let prev_animation_state = self.animation.get_state();
+        self.animation.update(delta_time);
+        let is_swing_peak = matches!(
+            (prev_animation_state, self.animation.get_state()),
+            (
+                InOutAnimationState::Foward(_),
+                InOutAnimationState::Backward(_)
+            )
+        );
+        if self.is_swinging && is_swing_peak {
+            self.is_swinging = false;
+            op = ScriptedEnemyOp::Melee;
+        }
+
+        let to_player = player_pos.xz() - obj_pos.xz();
+        let senses = Senses {
+            has_line_of_sight,
+            distance: to_player.norm(),
+            facing_angle: Vector2::new(self.dir.x, self.dir.z)
+                .angle(&to_player)
+                .to_degrees(),
+        };
+        if self.behaviour.update(delta_time, &senses) {
+            // The new activity walks somewhere else, so it plans right away
+            self.path.clear();
+            self.replan_timer = Timer::new_finished();
+            match self.behaviour.get_state().action {
+                Some(Action::Fire) => {
+                    op = ScriptedEnemyOp::Fire {
+                        dir: Gunman::aim(&self.accuracy, rng, obj_pos, player_pos, player_velocity),
+                        pos: *obj_pos,
+                    };
+                    self.animation = InOutAnimation::new_started(
+                        IN_SHOOT_ANIM_DURATION,
+                        OUT_SHOOT_ANIM_DURATION,
+                    );
+                    self.is_swinging = false;
+                }
+                Some(Action::Melee) => {
+                    self.animation = InOutAnimation::new_started(SWING_DURATION, SWING_DURATION);
+                    self.is_swinging = true;
+                }
+                None => {}
+            }
+        }
+
+        match self.get_activity() {
+            Activity::Idle => {}
+            Activity::Turn => {
+                self.turn_towards(Vector3::new(to_player.x, 0.0, to_player.y), delta_time);
+            }
+            Activity::Chase => {
+                if has_line_of_sight {
+                    // Straight at the player, the path is only needed around obstacles
+                    self.path.clear();
+                    if senses.distance > CHASE_STOP_DISTANCE {
+                        self.walk_towards(player_pos.xz(), obj_pos, delta_time);
+                    } else {
+                        self.turn_towards(Vector3::new(to_player.x, 0.0, to_player.y), delta_time);
+                    }
+                } else {
+                    self.follow_path(obj_pos, delta_time);
+                }
+            }
+            Activity::Wander => self.follow_path(obj_pos, delta_time),
+        }
+        op
+    }
+
+    pub fn get_direction(&self) -> Vector3<f32> {
+        self.dir
+    }
+
+    pub fn get_rotation(&self) -> Vector3<f32> {
+        Vector3::new(0.0, self.rotation_y, 0.0)
+    }
+
+    pub fn actionanim(&self) -> f32 {
+        -self.animation.get_value()
+    }
+
+    /// Returns true if the hit killed the enemy.
+    pub fn hit(&mut self, damage: f32, zone: HitZone) -> bool {
+        self.material_state = EnemyMaterialState::Hitted(Timer::new(HITTED_MATERIAL_DURATION));
+        self.health.damage(damage, zone)
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health.is_dead()
+    }
+
+    pub fn is_need_to_be_deleted(&self) -> bool {
+        self.health.is_need_to_be_deleted()
+    }
+}
+
+impl HasMaterial for ScriptedEnemy {
+    fn get_material(&self) -> MaterialType {
+        if self.health.is_dead() {
+            return MaterialType::White;
+        }
+        match (&self.material_state, self.behaviour.get_state().material) {
+            (EnemyMaterialState::Hitted(_), _) => MaterialType::Red,
+            (EnemyMaterialState::None, Some(material)) => material,
+            (EnemyMaterialState::None, None) => match self.get_model() {
+                EnemyModel::Gunman => MaterialType::White,
+                EnemyModel::Swordman => MaterialType::Black,
+            },
+        }
+    }
+}
@@ -1,15 +1,24 @@
 use crate::animation::{InOutAnimation, InOutAnimationState};
-use crate::entity::enemy::{HITTED_MATERIAL_DURATION, ROTATION_SPEED};
+use crate::entity::enemy::{
+    is_melee_hitting, Health, HitZone, ENEMY_HEALTH, HITTED_MATERIAL_DURATION, ROTATION_SPEED,
+};
 use crate::entity::HasMaterial;
 use crate::physics::GamePhysics;
 use crate::renderer::render_objects::MaterialType;
-use crate::scene::hit_and_dodge_scene::Score;
 use crate::timer::Timer;
-use nalgebra::{distance, Point, Unit, Vector3};
-use rapier3d::prelude::Ray;
+use nalgebra::{Rotation2, Unit, Vector2, Vector3};
+
+// Distance to the player at which the swordman stops and winds up its swing
+const ATTACK_RANGE: f32 = 1.4;
+const WIND_UP_DURATION: f32 = 0.4;
+// Beyond this distance, swordmen approach from their flank angle instead of head on
+const FLANK_DISTANCE: f32 = 4.0;
+const SEPARATION_RADIUS: f32 = 1.5;
+const SEPARATION_WEIGHT: f32 = 1.5;
 
 pub enum EnemyState {
     Attack(InOutAnimation),
+    WindUp(Timer),
     Chase,
 }
 
@@ -23,25 +32,38 @@ pub struct Swordman {
     rotation_y: f32,
     dir: Vector3<f32>,
     material_state: EnemyMaterialState,
+    health: Health,
+    speed: f32,
+    flank_angle: f32,
 }
 
 impl Swordman {
-    pub fn new() -> Self {
+    pub fn new(speed: f32) -> Self {
         Self {
+            speed,
             state: EnemyState::Chase,
             rotation_y: 0.0,
             material_state: EnemyMaterialState::None,
             dir: Vector3::new(0.0, 1.0, 0.0),
+            health: Health::new(ENEMY_HEALTH),
+            flank_angle: 0.0,
         }
     }
 
+    /// Angle in radians away from the straight line to the player the swordman approaches at,
+    /// so a group spreads around the player instead of queueing up in front of them.
+    pub fn set_flank_angle(&mut self, flank_angle: f32) {
+        self.flank_angle = flank_angle;
+    }
+
     pub fn update(
         &mut self,
         delta_time: f32,
         obj_pos: &mut Vector3<f32>,
         player_pos: &Vector3<f32>,
+        neighbours: &[Vector3<f32>],
         physics: &GamePhysics,
-        score: &mut Score,
+        hit_taken: &mut u16,
     ) {
         if let EnemyMaterialState::Hitted(ref mut timer) = self.material_state {
             timer.update(delta_time);
@@ -50,36 +72,40 @@ impl Swordman {
             }
         }
 
+        self.health.update(delta_time);
+        if self.health.is_dead() {
+            return;
+        }
+
         let current_dir = Unit::new_normalize(*player_pos - *obj_pos);
         match &mut self.state {
             EnemyState::Attack(anim) => {
                 let prev_state = anim.get_state();
                 anim.update(delta_time);
-                if let (InOutAnimationState::Foward(_), InOutAnimationState::Backward(_)) =
-                    (prev_state, anim.get_state())
+                let is_swing_peak = matches!(
+                    (prev_state, anim.get_state()),
+                    (
+                        InOutAnimationState::Foward(_),
+                        InOutAnimationState::Backward(_)
+                    )
+                );
+                let is_finished = anim.get_value() == 0.0;
+                if is_swing_peak
+                    && is_melee_hitting(physics, obj_pos, &self.dir, self.get_rotation())
                 {
-                    let ray = Ray::new(
-                        Point::from(*obj_pos) + current_dir.into_inner() * 0.6,
-                        current_dir.into_inner(),
-                    );
-                    if let Some((x, _)) = physics.query_pipeline.cast_ray(
-                        &physics.collider_set,
-                        &ray,
-                        0.5,
-                        true,
-                        physics.interaction_groups,
-                        None,
-                    ) {
-                        let collider = physics.collider_set.get(x).unwrap();
-                        if collider.user_data == u128::MAX {
-                            score.hit_taken += 1;
-                        }
-                    }
+                    *hit_taken += 1;
                 }
-                if anim.get_value() == 0.0 {
+                if is_finished {
                     self.state = EnemyState::Chase;
                 }
             }
+            EnemyState::WindUp(timer) => {
+                // The heading is locked while winding up, so stepping aside dodges the swing
+                timer.update(delta_time);
+                if timer.is_finished() {
+                    self.state = EnemyState::Attack(InOutAnimation::new_started(0.2, 0.2));
+                }
+            }
             EnemyState::Chase => {
                 // This can be normalized. However, since the player
                 // can't move fast, the enemy rotation velocity wouldn't
@@ -100,13 +126,34 @@ impl Swordman {
                 if is_rotating {
                     self.rotation_y = self.dir.x.atan2(self.dir.z);
                 }
-                let player_pos_xz = player_pos.xz();
                 let current_pos_xz = obj_pos.xz();
-                if distance(&Point::from(player_pos_xz), &Point::from(current_pos_xz)) <= 1.0 {
-                    self.state = EnemyState::Attack(InOutAnimation::new_started(0.2, 0.2));
+                let to_player = player_pos.xz() - current_pos_xz;
+                let player_distance = to_player.norm();
+                if player_distance <= ATTACK_RANGE {
+                    self.state = EnemyState::WindUp(Timer::new(WIND_UP_DURATION));
                 } else {
-                    let dir = Unit::new_normalize(player_pos_xz - current_pos_xz);
-                    let next_pos = dir.into_inner() * 3.0 * delta_time;
+                    // The flank angle fades out while closing in, so the last steps go straight
+                    // at the player
+                    let flank = self.flank_angle
+                        * ((player_distance - ATTACK_RANGE) / FLANK_DISTANCE).min(1.0);
+                    let seek = Rotation2::new(flank) * (to_player / player_distance);
+
+                    let mut separation = Vector2::zeros();
+                    for neighbour in neighbours {
+                        let away = current_pos_xz - neighbour.xz();
+                        let neighbour_distance = away.norm();
+                        if neighbour_distance > f32::EPSILON
+                            && neighbour_distance < SEPARATION_RADIUS
+                        {
+                            separation += away / neighbour_distance
+                                * (1.0 - neighbour_distance / SEPARATION_RADIUS);
+                        }
+                    }
+
+                    let dir = (seek + separation * SEPARATION_WEIGHT)
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or(seek);
+                    let next_pos = dir * self.speed * delta_time;
                     obj_pos.x += next_pos.x;
                     obj_pos.z += next_pos.y;
                 }
@@ -129,17 +176,30 @@ impl Swordman {
         }
     }
 
-    pub fn hit(&mut self) {
+    /// Returns true if the hit killed the enemy.
+    pub fn hit(&mut self, damage: f32, zone: HitZone) -> bool {
         self.material_state = EnemyMaterialState::Hitted(Timer::new(HITTED_MATERIAL_DURATION));
+        self.health.damage(damage, zone)
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health.is_dead()
+    }
+
+    pub fn is_need_to_be_deleted(&self) -> bool {
+        self.health.is_need_to_be_deleted()
     }
 }
 
 impl HasMaterial for Swordman {
     fn get_material(&self) -> MaterialType {
-        MaterialType::Black
-        // match self.material_state {
-        //     EnemyMaterialState::None => MaterialType::White,
-        //     EnemyMaterialState::Hitted(_) => MaterialType::Red,
-        // }
+        if self.health.is_dead() {
+            return MaterialType::White;
+        }
+        match (&self.material_state, &self.state) {
+            (EnemyMaterialState::Hitted(_), _) => MaterialType::Red,
+            (EnemyMaterialState::None, EnemyState::WindUp(_)) => MaterialType::Orange,
+            (EnemyMaterialState::None, _) => MaterialType::Black,
+        }
     }
 }
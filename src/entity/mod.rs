@@ -2,7 +2,9 @@ use crate::renderer::render_objects::MaterialType;
 
 pub mod enemy;
 
+pub mod projectile;
 pub mod target;
+pub mod target_grid;
 
 pub struct Wall;
 pub struct Crate;
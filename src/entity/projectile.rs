@@ -0,0 +1,41 @@
+use crate::entity::HasMaterial;
+use crate::renderer::render_objects::MaterialType;
+use crate::timer::Timer;
+use nalgebra::Vector3;
+
+pub const PROJECTILE_RAD: f32 = 0.1;
+// Projectiles which didn't hit anything by then are dropped as misses.
+pub const PROJECTILE_LIFETIME: f32 = 3.0;
+
+/// Shot fired by the player which travels through the world until it touches something.
+pub struct Projectile {
+    origin: Vector3<f32>,
+    lifetime: Timer,
+}
+
+impl HasMaterial for Projectile {
+    fn get_material(&self) -> MaterialType {
+        MaterialType::Orange
+    }
+}
+
+impl Projectile {
+    pub fn new(origin: Vector3<f32>) -> Self {
+        Self {
+            origin,
+            lifetime: Timer::new(PROJECTILE_LIFETIME),
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.lifetime.update(delta_time);
+    }
+
+    pub fn get_origin(&self) -> Vector3<f32> {
+        self.origin
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.lifetime.is_finished()
+    }
+}
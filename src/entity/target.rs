@@ -1,6 +1,7 @@
 use crate::audio::{AudioContext, Sink, AUDIO_FILE_SHOOTED};
 use crate::renderer::render_objects::MaterialType;
 use crate::timer::Timer;
+use crate::util::lerp;
 use nalgebra::{distance, Point3, Unit, Vector3};
 use std::io::{BufReader, Cursor};
 
@@ -33,6 +34,15 @@ pub enum Patrol {
     },
 }
 
+/// Radius animation of a target which shrinks from `start_radius` to `end_radius`
+/// over `duration` seconds.
+#[derive(Clone)]
+pub struct Shrink {
+    pub start_radius: f32,
+    pub end_radius: f32,
+    pub duration: f32,
+}
+
 #[derive(Clone)]
 enum PatrolState {
     AToB,
@@ -48,10 +58,14 @@ pub struct SphereTarget {
     validity_state: ValidityState,
     patrol: Patrol,
     patrol_state: PatrolState,
+    radius: f32,
+    shrink: Option<(Shrink, Timer)>,
+    linear_speed: f32,
 }
 
 pub const SPEED_LIN: f32 = 5.0;
 pub const SPEED_POL: f32 = 0.3;
+pub const TARGET_RADIUS: f32 = 0.5;
 
 impl SphereTarget {
     pub fn new(validity: Option<Validity>, patrol: Patrol) -> Self {
@@ -71,6 +85,9 @@ impl SphereTarget {
             validity_state,
             patrol,
             patrol_state,
+            radius: TARGET_RADIUS,
+            shrink: None,
+            linear_speed: SPEED_LIN,
         }
     }
 
@@ -95,6 +112,41 @@ impl SphereTarget {
             validity_state,
             patrol,
             patrol_state,
+            radius: TARGET_RADIUS,
+            shrink: None,
+            linear_speed: SPEED_LIN,
+        }
+    }
+
+    pub fn new_shrinking(shrink: Shrink, validity: Option<Validity>, patrol: Patrol) -> Self {
+        let mut target = Self::new(validity, patrol);
+        target.radius = shrink.start_radius;
+        target.shrink = Some((shrink.clone(), Timer::new(shrink.duration)));
+        target
+    }
+
+    /// Same target moving at `speed` instead of `SPEED_LIN` along a `Patrol::Linear`.
+    pub fn with_linear_speed(mut self, speed: f32) -> Self {
+        self.linear_speed = speed;
+        self
+    }
+
+    pub fn get_radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Ratio between the radius the target spawned with and its current radius.
+    pub fn get_shrink_ratio(&self) -> f32 {
+        match self.shrink {
+            Some((ref shrink, _)) => shrink.start_radius / self.radius,
+            None => 1.0,
+        }
+    }
+
+    pub fn is_shrunk(&self) -> bool {
+        match self.shrink {
+            Some((_, ref timer)) => timer.is_finished(),
+            None => false,
         }
     }
 
@@ -166,7 +218,7 @@ impl SphereTarget {
                             self.patrol_state = PatrolState::BToA;
                         } else {
                             let dir = Unit::new_normalize(*b - *obj_pos);
-                            let next_pos = dir.into_inner() * SPEED_LIN * delta_time;
+                            let next_pos = dir.into_inner() * self.linear_speed * delta_time;
                             obj_pos.x += next_pos.x;
                             obj_pos.z += next_pos.y;
                         }
@@ -176,7 +228,7 @@ impl SphereTarget {
                             self.patrol_state = PatrolState::AToB;
                         } else {
                             let dir = Unit::new_normalize(*a - *obj_pos);
-                            let next_pos = dir.into_inner() * SPEED_LIN * delta_time;
+                            let next_pos = dir.into_inner() * self.linear_speed * delta_time;
                             obj_pos.x += next_pos.x;
                             obj_pos.z += next_pos.y;
                         }
@@ -225,6 +277,17 @@ impl SphereTarget {
         if let Some(ref mut timer) = self.delete_timer {
             timer.update(delta_time);
         }
+
+        if !self.shooted {
+            if let Some((ref shrink, ref mut timer)) = self.shrink {
+                timer.update(delta_time);
+                self.radius = lerp(
+                    shrink.end_radius,
+                    shrink.start_radius,
+                    timer.get_duration() / shrink.duration,
+                );
+            }
+        }
     }
 
     pub fn is_need_to_be_deleted(&self) -> bool {
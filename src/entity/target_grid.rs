@@ -0,0 +1,72 @@
+use nalgebra::Vector3;
+use rand::prelude::SmallRng;
+use rand::seq::SliceRandom;
+
+/// Slot index of a target that was placed by a [`TargetGrid`].
+#[derive(Clone, Copy)]
+pub struct GridSlot(pub usize);
+
+pub struct TargetGrid {
+    center: Vector3<f32>,
+    columns: usize,
+    rows: usize,
+    spacing: f32,
+    occupied: Vec<bool>,
+}
+
+impl TargetGrid {
+    pub fn new(center: Vector3<f32>, columns: usize, rows: usize, spacing: f32) -> Self {
+        Self {
+            center,
+            columns,
+            rows,
+            spacing,
+            occupied: vec![false; columns * rows],
+        }
+    }
+
+    pub fn slot_position(&self, slot: usize) -> Vector3<f32> {
+        let column = (slot % self.columns) as f32;
+        let row = (slot / self.columns) as f32;
+        Vector3::new(
+            self.center.x + (column - (self.columns - 1) as f32 * 0.5) * self.spacing,
+            self.center.y + (row - (self.rows - 1) as f32 * 0.5) * self.spacing,
+            self.center.z,
+        )
+    }
+
+    // Two slots are adjacent when they touch horizontally, vertically or diagonally.
+    fn is_adjacent(&self, a: usize, b: usize) -> bool {
+        let (a_column, a_row) = ((a % self.columns) as i32, (a / self.columns) as i32);
+        let (b_column, b_row) = ((b % self.columns) as i32, (b / self.columns) as i32);
+        (a_column - b_column).abs() <= 1 && (a_row - b_row).abs() <= 1
+    }
+
+    /// Picks a random free slot that is not adjacent to `previous`. When every free slot
+    /// touches `previous`, any free slot is used instead.
+    pub fn allocate(&mut self, rng: &mut SmallRng, previous: Option<usize>) -> Option<usize> {
+        let free_slots = (0..self.occupied.len())
+            .filter(|slot| !self.occupied[*slot])
+            .collect::<Vec<usize>>();
+        let far_slots = free_slots
+            .iter()
+            .copied()
+            .filter(|slot| match previous {
+                Some(previous) => !self.is_adjacent(*slot, previous),
+                None => true,
+            })
+            .collect::<Vec<usize>>();
+
+        let slot = if far_slots.is_empty() {
+            *free_slots.choose(rng)?
+        } else {
+            *far_slots.choose(rng)?
+        };
+        self.occupied[slot] = true;
+        Some(slot)
+    }
+
+    pub fn free(&mut self, slot: usize) {
+        self.occupied[slot] = false;
+    }
+}
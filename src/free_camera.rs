@@ -0,0 +1,148 @@
+use crate::camera::Camera;
+use crate::input_binding::Action;
+use crate::input_manager::InputManager;
+use crate::renderer::render_objects::RenderObjects;
+use crate::systems::update_player_movement::action_axis;
+use nalgebra::{distance, Point, Vector3};
+use winit::event::VirtualKeyCode;
+
+// Speeds are in m/s
+const DEFAULT_SPEED: f32 = 8.0;
+const MIN_SPEED: f32 = 0.5;
+const MAX_SPEED: f32 = 100.0;
+// Factor the speed changes by per press of the speed keys
+const SPEED_STEP: f32 = 1.25;
+const FAST_MULTIPLIER: f32 = 4.0;
+
+// Objects this close to the center of the view, in degrees, can be picked as orbit target.
+const PICK_ANGLE: f32 = 10.0;
+// The orbit follows the object nearest to where the target was last frame, up to this distance.
+const TRACK_RADIUS: f32 = 2.0;
+const MIN_ORBIT_DISTANCE: f32 = 1.0;
+
+pub enum FreeCameraMode {
+    // Noclip flight, going through everything
+    Fly,
+    // Turns around a point that follows the picked object
+    Orbit { target: Vector3<f32>, distance: f32 },
+}
+
+/// Camera detached from the player, to look at a scene from anywhere while it runs or is
+/// paused. Movement uses the player's bindings, jump and crouch go up and down, sprint goes
+/// faster, `+` and `-` change the speed and `F10` picks or drops an orbit target.
+pub struct FreeCamera {
+    pub camera: Camera,
+    speed: f32,
+    mode: FreeCameraMode,
+}
+
+impl FreeCamera {
+    /// Starts where `player_camera` is, looking the same way.
+    pub fn new(player_camera: &Camera) -> Self {
+        let mut camera = Camera::new();
+        camera.position = player_camera.position;
+        let view_angles = player_camera.get_view_angles() - camera.get_view_angles();
+        camera.rotate(view_angles.x, view_angles.y);
+        Self {
+            camera,
+            speed: DEFAULT_SPEED,
+            mode: FreeCameraMode::Fly,
+        }
+    }
+
+    pub fn get_mode(&self) -> &FreeCameraMode {
+        &self.mode
+    }
+
+    pub fn update(
+        &mut self,
+        input_manager: &InputManager,
+        player_camera: &Camera,
+        render_objects: &RenderObjects,
+        delta_time: f32,
+    ) {
+        // Looks the same as the player camera, without the zoom and effects
+        self.camera.fov = player_camera.fov;
+        self.camera.fov_type = player_camera.fov_type;
+        self.camera.degrees_per_count = player_camera.degrees_per_count;
+        self.camera.move_direction(input_manager.mouse_movement);
+
+        if input_manager.is_keyboard_pressed(&VirtualKeyCode::Equals) {
+            self.speed = (self.speed * SPEED_STEP).min(MAX_SPEED);
+        }
+        if input_manager.is_keyboard_pressed(&VirtualKeyCode::Minus) {
+            self.speed = (self.speed / SPEED_STEP).max(MIN_SPEED);
+        }
+        let speed = if input_manager.is_action_press(Action::Sprint) {
+            self.speed * FAST_MULTIPLIER
+        } else {
+            self.speed
+        };
+
+        if input_manager.is_keyboard_pressed(&VirtualKeyCode::F10) {
+            self.mode = match self.mode {
+                FreeCameraMode::Fly => match self.pick_target(render_objects) {
+                    Some(target) => FreeCameraMode::Orbit {
+                        distance: distance(
+                            &Point::from(target),
+                            &Point::from(self.camera.position),
+                        )
+                        .max(MIN_ORBIT_DISTANCE),
+                        target,
+                    },
+                    None => FreeCameraMode::Fly,
+                },
+                FreeCameraMode::Orbit { .. } => FreeCameraMode::Fly,
+            };
+        }
+
+        let forward = action_axis(input_manager, Action::MoveBackward, Action::MoveForward);
+        match self.mode {
+            FreeCameraMode::Fly => {
+                let direction = forward * *self.camera.get_direction()
+                    + action_axis(input_manager, Action::MoveLeft, Action::MoveRight)
+                        * *self.camera.get_direction_right()
+                    + action_axis(input_manager, Action::Crouch, Action::Jump) * Vector3::y();
+                if let Some(direction) = direction.try_normalize(f32::EPSILON) {
+                    self.camera.position += direction * speed * delta_time;
+                }
+            }
+            FreeCameraMode::Orbit {
+                ref mut target,
+                ref mut distance,
+            } => {
+                if let Some(position) = render_objects
+                    .get_positions()
+                    .filter(|position| (position - *target).norm() <= TRACK_RADIUS)
+                    .min_by(|a, b| {
+                        (a - *target)
+                            .norm()
+                            .partial_cmp(&(b - *target).norm())
+                            .unwrap()
+                    })
+                {
+                    *target = position;
+                }
+                // Forward gets closer to the target
+                *distance = (*distance - forward * speed * delta_time).max(MIN_ORBIT_DISTANCE);
+                self.camera.position = *target - *self.camera.get_direction() * *distance;
+            }
+        }
+    }
+
+    // Object nearest to the center of the view, if any is close enough to it.
+    fn pick_target(&self, render_objects: &RenderObjects) -> Option<Vector3<f32>> {
+        let direction = self.camera.get_direction();
+        render_objects
+            .get_positions()
+            .map(|position| {
+                let angle = direction
+                    .angle(&(position - self.camera.position))
+                    .to_degrees();
+                (position, angle)
+            })
+            .filter(|(_, angle)| *angle <= PICK_ANGLE)
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(position, _)| position)
+    }
+}
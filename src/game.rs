@@ -1,6 +1,8 @@
 use crate::audio::AudioContext;
 use crate::database::Database;
+use crate::free_camera::{FreeCamera, FreeCameraMode};
 use crate::gui::ConrodHandle;
+use crate::input_binding::Action;
 use crate::input_manager::InputManager;
 use crate::renderer::Renderer;
 
@@ -17,6 +19,10 @@ use winit::window::Window as WinitWindow;
 
 conrod_winit::v023_conversion_fns!();
 
+// Mouse counts per second the look keys emulate, about 200 degrees per second at the default
+// sensitivity.
+const KEYBOARD_LOOK_SPEED: f32 = 18000.0;
+
 pub struct Game {
     scene_stack: VecDeque<Box<dyn Scene>>,
     renderer: Renderer,
@@ -28,6 +34,11 @@ pub struct Game {
     audio_context: AudioContext,
     database: Database,
     debug: bool,
+    // Debug only: F8 toggles the free camera and F9 pauses the scene while it's on
+    is_scene_paused: bool,
+    was_cursor_grabbed: bool,
+    // Given to the scene instead of the real input while the free camera is on
+    idle_input_manager: InputManager,
 }
 
 impl Game {
@@ -49,6 +60,8 @@ impl Game {
         let mut database = Database::new();
         database.init();
         database.init_settings(&mut audio_context, &mut renderer);
+        let mut input_manager = InputManager::new();
+        database.init_bindings(&mut input_manager.bindings);
 
         let mut scene_stack = VecDeque::<Box<dyn Scene>>::new();
         let mut first_scene = MainMenuScene::new(&mut renderer, &mut conrod_handle); // ClassicScoreScene::new(&mut renderer, &mut conrod_handle);
@@ -70,12 +83,30 @@ impl Game {
             scene_stack,
             conrod_handle,
             renderer,
-            input_manager: InputManager::new(),
+            input_manager,
             last_time: Instant::now(),
             running_time: 0.0,
             audio_context,
             database,
             debug,
+            is_scene_paused: false,
+            was_cursor_grabbed: false,
+            idle_input_manager: InputManager::new(),
+        }
+    }
+
+    fn toggle_free_camera(&mut self) {
+        match self.renderer.free_camera {
+            Some(_) => {
+                self.renderer.free_camera = None;
+                self.is_scene_paused = false;
+                self.window.set_is_cursor_grabbed(self.was_cursor_grabbed);
+            }
+            None => {
+                self.renderer.free_camera = Some(FreeCamera::new(&self.renderer.camera));
+                self.was_cursor_grabbed = self.window.is_cursor_grabbed();
+                self.window.set_is_cursor_grabbed(true);
+            }
         }
     }
 
@@ -101,39 +132,73 @@ impl Game {
                     _ => {}
                 };
             }
+            Event::DeviceEvent { event, .. } => {
+                self.input_manager.process_device(event, &self.window);
+            }
             Event::MainEventsCleared => {
                 let current_time = Instant::now();
                 let delta_time = current_time.duration_since(self.last_time).as_secs_f32();
                 self.last_time = current_time;
                 self.running_time += delta_time;
+                self.input_manager.frame_time = current_time;
+                self.renderer.camera.effects.update(delta_time);
+
+                if self.debug && self.input_manager.is_keyboard_pressed(&VirtualKeyCode::F8) {
+                    self.toggle_free_camera();
+                }
+                let is_spectating = self.renderer.free_camera.is_some();
+                if is_spectating && self.input_manager.is_keyboard_pressed(&VirtualKeyCode::F9) {
+                    self.is_scene_paused = !self.is_scene_paused;
+                }
 
                 if self.window.is_cursor_grabbed() {
                     let mut dir_diff = nalgebra::Vector2::new(0.0, 0.0);
-                    if self.input_manager.is_keyboard_press(&VirtualKeyCode::Left) {
-                        dir_diff.x += 400.0;
-                    } else if self.input_manager.is_keyboard_press(&VirtualKeyCode::Right) {
-                        dir_diff.x -= 400.0;
+                    if self.input_manager.is_action_press(Action::LookLeft) {
+                        dir_diff.x += KEYBOARD_LOOK_SPEED;
+                    } else if self.input_manager.is_action_press(Action::LookRight) {
+                        dir_diff.x -= KEYBOARD_LOOK_SPEED;
                     }
 
-                    if self.input_manager.is_keyboard_press(&VirtualKeyCode::Up) {
-                        dir_diff.y += 400.0;
-                    } else if self.input_manager.is_keyboard_press(&VirtualKeyCode::Down) {
-                        dir_diff.y -= 400.0;
+                    if self.input_manager.is_action_press(Action::LookUp) {
+                        dir_diff.y += KEYBOARD_LOOK_SPEED;
+                    } else if self.input_manager.is_action_press(Action::LookDown) {
+                        dir_diff.y -= KEYBOARD_LOOK_SPEED;
                     }
 
-                    self.input_manager.mouse_movement += dir_diff;
+                    // Mouse counts are turned into angles without the frame time, held keys
+                    // have to be scaled by it here
+                    self.input_manager.mouse_movement += dir_diff * delta_time;
                 }
 
-                let scene_op = self.scene_stack.back_mut().unwrap().update(
-                    &mut self.window,
-                    &mut self.renderer,
-                    &self.input_manager,
-                    delta_time,
-                    &mut self.conrod_handle,
-                    &mut self.audio_context,
-                    control_flow,
-                    &mut self.database,
-                );
+                // While spectating, the input drives the free camera and the scene gets none
+                let scene_input_manager = if is_spectating {
+                    self.idle_input_manager.frame_time = current_time;
+                    &mut self.idle_input_manager
+                } else {
+                    &mut self.input_manager
+                };
+
+                let scene_op = if self.is_scene_paused {
+                    SceneOp::None
+                } else {
+                    self.scene_stack.back_mut().unwrap().update(
+                        &mut self.window,
+                        &mut self.renderer,
+                        scene_input_manager,
+                        delta_time,
+                        &mut self.conrod_handle,
+                        &mut self.audio_context,
+                        control_flow,
+                        &mut self.database,
+                    )
+                };
+
+                // The free camera belongs to the scene it was opened in, the next scene sets
+                // up the cursor for itself
+                if !matches!(scene_op, SceneOp::None) {
+                    self.renderer.free_camera = None;
+                    self.is_scene_paused = false;
+                }
 
                 match scene_op {
                     SceneOp::None => {}
@@ -200,12 +265,22 @@ impl Game {
 
                 self.scene_stack.back_mut().unwrap().prerender(
                     &mut self.renderer,
-                    &self.input_manager,
+                    scene_input_manager,
                     delta_time,
                     &mut self.conrod_handle,
                     &mut self.audio_context,
                 );
 
+                // After prerender, so that objects can be picked from what is queued
+                if let Some(ref mut free_camera) = self.renderer.free_camera {
+                    free_camera.update(
+                        &self.input_manager,
+                        &self.renderer.camera,
+                        &self.renderer.render_objects,
+                        delta_time,
+                    );
+                }
+
                 match self
                     .renderer
                     .render(self.running_time, &mut self.conrod_handle)
@@ -226,10 +301,28 @@ impl Game {
                 };
 
                 self.input_manager.clear();
+                self.idle_input_manager.clear();
                 self.audio_context.clear();
 
                 if self.debug {
-                    self.window.set_title(&format!("FPS: {}", 1.0 / delta_time));
+                    let camera_state = match self.renderer.free_camera {
+                        Some(ref free_camera) => match free_camera.get_mode() {
+                            FreeCameraMode::Fly => " - free camera",
+                            FreeCameraMode::Orbit { .. } => " - orbit camera",
+                        },
+                        None => "",
+                    };
+                    let pause_state = if self.is_scene_paused {
+                        " - paused"
+                    } else {
+                        ""
+                    };
+                    self.window.set_title(&format!(
+                        "FPS: {}{}{}",
+                        1.0 / delta_time,
+                        camera_state,
+                        pause_state
+                    ));
                 }
             }
             _ => {}
@@ -0,0 +1,356 @@
+use winit::event::{MouseButton, VirtualKeyCode};
+
+/// Something the player does in a game, independently of the key or button it's bound to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Crouch,
+    Sprint,
+    Dash,
+    Fire,
+    Aim,
+    Reload,
+    Pause,
+    LookUp,
+    LookDown,
+    LookLeft,
+    LookRight,
+}
+
+impl Action {
+    pub const ALL: [Action; 16] = [
+        Action::MoveForward,
+        Action::MoveBackward,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Crouch,
+        Action::Sprint,
+        Action::Dash,
+        Action::Fire,
+        Action::Aim,
+        Action::Reload,
+        Action::Pause,
+        Action::LookUp,
+        Action::LookDown,
+        Action::LookLeft,
+        Action::LookRight,
+    ];
+
+    /// Name shown in the settings.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveForward => "Move forward",
+            Action::MoveBackward => "Move backward",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Jump => "Jump",
+            Action::Crouch => "Crouch",
+            Action::Sprint => "Sprint",
+            Action::Dash => "Dash",
+            Action::Fire => "Fire",
+            Action::Aim => "Aim down sights",
+            Action::Reload => "Reload",
+            Action::Pause => "Pause",
+            Action::LookUp => "Look up",
+            Action::LookDown => "Look down",
+            Action::LookLeft => "Look left",
+            Action::LookRight => "Look right",
+        }
+    }
+
+    /// Name stored in the database, it must never change once released.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Action::MoveForward => "move_forward",
+            Action::MoveBackward => "move_backward",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Jump => "jump",
+            Action::Crouch => "crouch",
+            Action::Sprint => "sprint",
+            Action::Dash => "dash",
+            Action::Fire => "fire",
+            Action::Aim => "aim",
+            Action::Reload => "reload",
+            Action::Pause => "pause",
+            Action::LookUp => "look_up",
+            Action::LookDown => "look_down",
+            Action::LookLeft => "look_left",
+            Action::LookRight => "look_right",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|action| action.id() == id)
+    }
+
+    pub fn default_binding(&self) -> Binding {
+        match self {
+            Action::MoveForward => Binding::Key(VirtualKeyCode::W),
+            Action::MoveBackward => Binding::Key(VirtualKeyCode::S),
+            Action::MoveLeft => Binding::Key(VirtualKeyCode::A),
+            Action::MoveRight => Binding::Key(VirtualKeyCode::D),
+            Action::Jump => Binding::Key(VirtualKeyCode::Space),
+            Action::Crouch => Binding::Key(VirtualKeyCode::LControl),
+            Action::Sprint => Binding::Key(VirtualKeyCode::LShift),
+            Action::Dash => Binding::Key(VirtualKeyCode::Q),
+            Action::Fire => Binding::Mouse(MouseButton::Left),
+            Action::Aim => Binding::Mouse(MouseButton::Right),
+            Action::Reload => Binding::Key(VirtualKeyCode::R),
+            Action::Pause => Binding::Key(VirtualKeyCode::Escape),
+            Action::LookUp => Binding::Key(VirtualKeyCode::Up),
+            Action::LookDown => Binding::Key(VirtualKeyCode::Down),
+            Action::LookLeft => Binding::Key(VirtualKeyCode::Left),
+            Action::LookRight => Binding::Key(VirtualKeyCode::Right),
+        }
+    }
+}
+
+// Keys that can be bound to an action. Others are ignored while rebinding, since they can't be
+// read back from the database.
+const BINDABLE_KEYS: [VirtualKeyCode; 92] = [
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6,
+    VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8,
+    VirtualKeyCode::Key9,
+    VirtualKeyCode::Key0,
+    VirtualKeyCode::A,
+    VirtualKeyCode::B,
+    VirtualKeyCode::C,
+    VirtualKeyCode::D,
+    VirtualKeyCode::E,
+    VirtualKeyCode::F,
+    VirtualKeyCode::G,
+    VirtualKeyCode::H,
+    VirtualKeyCode::I,
+    VirtualKeyCode::J,
+    VirtualKeyCode::K,
+    VirtualKeyCode::L,
+    VirtualKeyCode::M,
+    VirtualKeyCode::N,
+    VirtualKeyCode::O,
+    VirtualKeyCode::P,
+    VirtualKeyCode::Q,
+    VirtualKeyCode::R,
+    VirtualKeyCode::S,
+    VirtualKeyCode::T,
+    VirtualKeyCode::U,
+    VirtualKeyCode::V,
+    VirtualKeyCode::W,
+    VirtualKeyCode::X,
+    VirtualKeyCode::Y,
+    VirtualKeyCode::Z,
+    VirtualKeyCode::F1,
+    VirtualKeyCode::F2,
+    VirtualKeyCode::F3,
+    VirtualKeyCode::F4,
+    VirtualKeyCode::F5,
+    VirtualKeyCode::F6,
+    VirtualKeyCode::F7,
+    VirtualKeyCode::F8,
+    VirtualKeyCode::F9,
+    VirtualKeyCode::F10,
+    VirtualKeyCode::F11,
+    VirtualKeyCode::F12,
+    VirtualKeyCode::Escape,
+    VirtualKeyCode::Tab,
+    VirtualKeyCode::Capital,
+    VirtualKeyCode::Space,
+    VirtualKeyCode::Return,
+    VirtualKeyCode::Back,
+    VirtualKeyCode::Insert,
+    VirtualKeyCode::Delete,
+    VirtualKeyCode::Home,
+    VirtualKeyCode::End,
+    VirtualKeyCode::PageUp,
+    VirtualKeyCode::PageDown,
+    VirtualKeyCode::Left,
+    VirtualKeyCode::Up,
+    VirtualKeyCode::Right,
+    VirtualKeyCode::Down,
+    VirtualKeyCode::LShift,
+    VirtualKeyCode::RShift,
+    VirtualKeyCode::LControl,
+    VirtualKeyCode::RControl,
+    VirtualKeyCode::LAlt,
+    VirtualKeyCode::RAlt,
+    VirtualKeyCode::Numpad0,
+    VirtualKeyCode::Numpad1,
+    VirtualKeyCode::Numpad2,
+    VirtualKeyCode::Numpad3,
+    VirtualKeyCode::Numpad4,
+    VirtualKeyCode::Numpad5,
+    VirtualKeyCode::Numpad6,
+    VirtualKeyCode::Numpad7,
+    VirtualKeyCode::Numpad8,
+    VirtualKeyCode::Numpad9,
+    VirtualKeyCode::Comma,
+    VirtualKeyCode::Period,
+    VirtualKeyCode::Semicolon,
+    VirtualKeyCode::Colon,
+    VirtualKeyCode::Slash,
+    VirtualKeyCode::Backslash,
+    VirtualKeyCode::Apostrophe,
+    VirtualKeyCode::LBracket,
+    VirtualKeyCode::RBracket,
+    VirtualKeyCode::Minus,
+    VirtualKeyCode::Equals,
+    VirtualKeyCode::Grave,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+}
+
+impl Binding {
+    pub fn is_bindable(&self) -> bool {
+        match self {
+            Binding::Key(key) => BINDABLE_KEYS.contains(key),
+            Binding::Mouse(_) => true,
+        }
+    }
+
+    /// Name shown in the settings and stored in the database.
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Mouse(MouseButton::Left) => "Mouse Left".to_string(),
+            Binding::Mouse(MouseButton::Right) => "Mouse Right".to_string(),
+            Binding::Mouse(MouseButton::Middle) => "Mouse Middle".to_string(),
+            Binding::Mouse(MouseButton::Other(button)) => format!("Mouse {}", button),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Binding> {
+        match name {
+            "Mouse Left" => Some(Binding::Mouse(MouseButton::Left)),
+            "Mouse Right" => Some(Binding::Mouse(MouseButton::Right)),
+            "Mouse Middle" => Some(Binding::Mouse(MouseButton::Middle)),
+            _ => match name.strip_prefix("Mouse ") {
+                Some(button) => button
+                    .parse()
+                    .ok()
+                    .map(|button| Binding::Mouse(MouseButton::Other(button))),
+                None => BINDABLE_KEYS
+                    .iter()
+                    .find(|key| format!("{:?}", key) == name)
+                    .map(|key| Binding::Key(*key)),
+            },
+        }
+    }
+}
+
+/// The binding of every action, indexed in the order of `Action::ALL`.
+pub struct InputBindings {
+    bindings: Vec<Binding>,
+}
+
+impl InputBindings {
+    pub fn new() -> Self {
+        Self {
+            bindings: Action::ALL
+                .iter()
+                .map(|action| action.default_binding())
+                .collect(),
+        }
+    }
+
+    fn index(action: Action) -> usize {
+        Action::ALL.iter().position(|a| *a == action).unwrap()
+    }
+
+    pub fn get(&self, action: Action) -> Binding {
+        self.bindings[Self::index(action)]
+    }
+
+    /// Binds `binding` to `action`. If another action already used it, that action takes the
+    /// previous binding of `action` so no two actions share an input, and it is returned.
+    pub fn rebind(&mut self, action: Action, binding: Binding) -> Option<Action> {
+        let previous = self.get(action);
+        let conflict = Action::ALL
+            .iter()
+            .copied()
+            .find(|other| *other != action && self.get(*other) == binding);
+        if let Some(other) = conflict {
+            self.bindings[Self::index(other)] = previous;
+        }
+        self.bindings[Self::index(action)] = binding;
+        conflict
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebind_to_a_free_input() {
+        let mut bindings = InputBindings::new();
+        let conflict = bindings.rebind(Action::Jump, Binding::Key(VirtualKeyCode::F));
+        assert_eq!(conflict, None);
+        assert_eq!(bindings.get(Action::Jump), Binding::Key(VirtualKeyCode::F));
+    }
+
+    #[test]
+    fn rebind_swaps_with_the_conflicting_action() {
+        let mut bindings = InputBindings::new();
+        let conflict = bindings.rebind(Action::Jump, Binding::Mouse(MouseButton::Left));
+        assert_eq!(conflict, Some(Action::Fire));
+        assert_eq!(
+            bindings.get(Action::Jump),
+            Binding::Mouse(MouseButton::Left)
+        );
+        assert_eq!(
+            bindings.get(Action::Fire),
+            Binding::Key(VirtualKeyCode::Space)
+        );
+    }
+
+    #[test]
+    fn rebind_to_the_same_input_has_no_conflict() {
+        let mut bindings = InputBindings::new();
+        let conflict = bindings.rebind(Action::Jump, Binding::Key(VirtualKeyCode::Space));
+        assert_eq!(conflict, None);
+        assert_eq!(
+            bindings.get(Action::Jump),
+            Binding::Key(VirtualKeyCode::Space)
+        );
+    }
+
+    #[test]
+    fn reset_restores_the_defaults() {
+        let mut bindings = InputBindings::new();
+        bindings.rebind(Action::Jump, Binding::Mouse(MouseButton::Left));
+        bindings.reset();
+        for action in Action::ALL {
+            assert_eq!(bindings.get(action), action.default_binding());
+        }
+    }
+
+    #[test]
+    fn binding_names_round_trip() {
+        for action in Action::ALL {
+            let binding = action.default_binding();
+            assert_eq!(Binding::from_name(&binding.name()), Some(binding));
+            assert_eq!(Action::from_id(action.id()), Some(action));
+        }
+        let other = Binding::Mouse(MouseButton::Other(4));
+        assert_eq!(Binding::from_name(&other.name()), Some(other));
+    }
+}
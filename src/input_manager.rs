@@ -1,7 +1,21 @@
+use crate::input_binding::{Action, Binding, InputBindings};
 use crate::window::Window;
+use instant::Instant;
 use std::collections::HashSet;
 use winit::dpi::PhysicalPosition;
-use winit::event::{ElementState, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent};
+use winit::event::{
+    DeviceEvent, ElementState, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent,
+};
+
+/// A key or mouse button going down, recorded when it happened instead of when the frame reads
+/// it.
+#[derive(Debug, Clone, Copy)]
+pub struct PressEvent {
+    pub binding: Binding,
+    pub time: Instant,
+    // Mouse movement of the frame up to the press, the view pointed there at that instant
+    pub mouse_movement: nalgebra::Vector2<f32>,
+}
 
 pub struct InputManager {
     pub keyboard_buttons: HashSet<VirtualKeyCode>,
@@ -9,6 +23,13 @@ pub struct InputManager {
     pub mouse_buttons: HashSet<MouseButton>,
     pub mouse_buttons_pressed: HashSet<MouseButton>,
     pub mouse_movement: nalgebra::Vector2<f32>,
+    pub bindings: InputBindings,
+    // Set once the platform reported raw mouse motion, cursor movement is ignored from then on
+    pub is_raw_mouse_motion: bool,
+    // Presses since the last frame, oldest first
+    pub press_events: Vec<PressEvent>,
+    // When the frame reading the input started
+    pub frame_time: Instant,
 }
 
 impl InputManager {
@@ -19,14 +40,20 @@ impl InputManager {
             mouse_buttons_pressed: HashSet::new(),
             mouse_buttons: HashSet::new(),
             mouse_movement: nalgebra::Vector2::new(0.0, 0.0),
+            bindings: InputBindings::new(),
+            is_raw_mouse_motion: false,
+            press_events: Vec::new(),
+            frame_time: Instant::now(),
         }
     }
 
     pub fn process(&mut self, event: &WindowEvent, window: &Window) -> bool {
         match event {
             WindowEvent::CursorMoved { position, .. } => {
+                // Fallback where raw motion is unavailable: measure how far the cursor moved from
+                // the center of the window and put it back there
                 #[cfg(not(target_arch = "wasm32"))]
-                if window.is_cursor_grabbed() {
+                if window.is_cursor_grabbed() && !self.is_raw_mouse_motion {
                     let window_size = window.inner_size();
                     let center = nalgebra::Vector2::<f32>::new(
                         window_size.width as f32 / 2.0,
@@ -56,7 +83,10 @@ impl InputManager {
                     },
                 ..
             } => {
-                self.keyboard_buttons.insert(*key);
+                // Key repeat sends presses while the key is held, only the first one counts
+                if self.keyboard_buttons.insert(*key) {
+                    self.push_press_event(Binding::Key(*key));
+                }
                 self.keyboard_buttons_pressed.insert(*key);
                 true
             }
@@ -79,6 +109,7 @@ impl InputManager {
             } => {
                 self.mouse_buttons.insert(*button);
                 self.mouse_buttons_pressed.insert(*button);
+                self.push_press_event(Binding::Mouse(*button));
                 true
             }
             WindowEvent::MouseInput {
@@ -93,8 +124,35 @@ impl InputManager {
         }
     }
 
+    /// Reads unaccelerated mouse deltas straight from the device, they don't depend on the
+    /// cursor position so nothing is lost at high polling rates. They are only read while the
+    /// cursor is grabbed, menus keep following the cursor.
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    pub fn process_device(&mut self, event: &DeviceEvent, window: &Window) -> bool {
+        match event {
+            #[cfg(not(target_arch = "wasm32"))]
+            DeviceEvent::MouseMotion { delta } => {
+                if window.is_cursor_grabbed() {
+                    self.is_raw_mouse_motion = true;
+                    self.mouse_movement -= nalgebra::Vector2::new(delta.0 as f32, delta.1 as f32);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn push_press_event(&mut self, binding: Binding) {
+        self.press_events.push(PressEvent {
+            binding,
+            time: Instant::now(),
+            mouse_movement: self.mouse_movement,
+        });
+    }
+
     pub fn clear(&mut self) {
         self.mouse_movement.data.0 = [[0.0, 0.0]];
+        self.press_events.clear();
         self.keyboard_buttons_pressed.clear();
         self.mouse_buttons_pressed.clear();
     }
@@ -115,6 +173,53 @@ impl InputManager {
         self.keyboard_buttons_pressed.contains(key)
     }
 
+    /// Whether the input bound to `action` is held down.
+    pub fn is_action_press(&self, action: Action) -> bool {
+        match self.bindings.get(action) {
+            Binding::Key(key) => self.is_keyboard_press(&key),
+            Binding::Mouse(button) => self.is_mouse_press(&button),
+        }
+    }
+
+    /// Whether the input bound to `action` went down this frame.
+    pub fn is_action_pressed(&self, action: Action) -> bool {
+        match self.bindings.get(action) {
+            Binding::Key(key) => self.is_keyboard_pressed(&key),
+            Binding::Mouse(button) => self.is_mouse_pressed(&button),
+        }
+    }
+
+    /// First press of the input bound to `action` since the last frame.
+    pub fn get_action_press_event(&self, action: Action) -> Option<&PressEvent> {
+        let binding = self.bindings.get(action);
+        self.press_events
+            .iter()
+            .find(|event| event.binding == binding)
+    }
+
+    /// Seconds between `event` and the start of the frame reading it.
+    pub fn get_event_delay(&self, event: &PressEvent) -> f32 {
+        self.frame_time.duration_since(event.time).as_secs_f32()
+    }
+
+    /// Mouse movement of the frame that happened after `event`.
+    pub fn get_mouse_movement_since(&self, event: &PressEvent) -> nalgebra::Vector2<f32> {
+        self.mouse_movement - event.mouse_movement
+    }
+
+    /// First bindable key or mouse button that went down this frame.
+    pub fn get_pressed_binding(&self) -> Option<Binding> {
+        self.keyboard_buttons_pressed
+            .iter()
+            .map(|key| Binding::Key(*key))
+            .chain(
+                self.mouse_buttons_pressed
+                    .iter()
+                    .map(|button| Binding::Mouse(*button)),
+            )
+            .find(|binding| binding.is_bindable())
+    }
+
     pub fn is_any_press(&self) -> bool {
         !self.keyboard_buttons.is_empty() || !self.mouse_buttons.is_empty()
     }
@@ -1,18 +1,25 @@
 mod animation;
 mod audio;
 mod camera;
+mod camera_effects;
+mod character_controller;
 mod database;
 mod entity;
+mod free_camera;
 mod frustum;
 mod game;
 mod gui;
+mod input_binding;
 mod input_manager;
+mod navigation;
 mod physics;
 mod renderer;
 mod scene;
+mod sensitivity;
 mod systems;
 mod timer;
 mod util;
+mod weapon;
 mod window;
 
 use crate::game::Game;
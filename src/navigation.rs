@@ -0,0 +1,326 @@
+use crate::physics::GamePhysics;
+use nalgebra::{Point, Vector2, Vector3};
+use rand::prelude::SmallRng;
+use rand::Rng;
+use rapier3d::parry::query;
+use rapier3d::parry::shape::Cuboid;
+use rapier3d::prelude::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+const NAV_CELL_SIZE: f32 = 0.5;
+// Cells closer than this to an obstacle are blocked, so enemies don't clip walls while walking.
+const ENEMY_RADIUS: f32 = 0.4;
+// Vertical range checked for obstacles, above the ground top and up to an enemy's head.
+const OBSTACLE_MIN_Y: f32 = 1.2;
+const OBSTACLE_MAX_Y: f32 = 3.0;
+// How far from its current position an enemy looks for a spot to shoot from.
+const MAX_FIRING_POSITION_DISTANCE: f32 = 8.0;
+
+// Integer step costs keep the open set ordering exact, diagonal steps cost ~sqrt(2).
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+/// Walkable cells of a horizontal area, built once from the colliders that never move.
+pub struct NavGrid {
+    origin: Vector2<f32>,
+    width: usize,
+    depth: usize,
+    walkable: Vec<bool>,
+    walkable_cells: Vec<usize>,
+}
+
+impl NavGrid {
+    /// Covers the square of `half_extent` around `center`. Call this after the walls, crates and
+    /// containers of the scene are spawned.
+    pub fn new(physics: &GamePhysics, center: Vector2<f32>, half_extent: f32) -> Self {
+        let width = (half_extent * 2.0 / NAV_CELL_SIZE).ceil() as usize;
+        let depth = width;
+        let origin = center - Vector2::new(half_extent, half_extent);
+        let cell_volume = Cuboid::new(Vector3::new(
+            NAV_CELL_SIZE * 0.5 + ENEMY_RADIUS,
+            (OBSTACLE_MAX_Y - OBSTACLE_MIN_Y) * 0.5,
+            NAV_CELL_SIZE * 0.5 + ENEMY_RADIUS,
+        ));
+        let obstacles = physics
+            .collider_set
+            .iter()
+            .filter(|(_, collider)| {
+                !collider.is_sensor()
+                    && collider.parent().is_none_or(|parent| {
+                        physics.rigid_body_set.get(parent).unwrap().is_static()
+                    })
+            })
+            .map(|(_, collider)| collider)
+            .collect::<Vec<_>>();
+
+        let mut walkable = vec![true; width * depth];
+        let mut walkable_cells = Vec::new();
+        for z in 0..depth {
+            for x in 0..width {
+                let cell_center =
+                    origin + Vector2::new(x as f32 + 0.5, z as f32 + 0.5) * NAV_CELL_SIZE;
+                let cell_position = Isometry::translation(
+                    cell_center.x,
+                    (OBSTACLE_MAX_Y + OBSTACLE_MIN_Y) * 0.5,
+                    cell_center.y,
+                );
+                let blocked = obstacles.iter().any(|collider| {
+                    query::intersection_test(
+                        &cell_position,
+                        &cell_volume,
+                        collider.position(),
+                        collider.shape(),
+                    )
+                    .unwrap()
+                });
+                walkable[z * width + x] = !blocked;
+                if !blocked {
+                    walkable_cells.push(z * width + x);
+                }
+            }
+        }
+
+        Self {
+            origin,
+            width,
+            depth,
+            walkable,
+            walkable_cells,
+        }
+    }
+
+    fn cell_of(&self, position: Vector2<f32>) -> (usize, usize) {
+        let cell = (position - self.origin) / NAV_CELL_SIZE;
+        (
+            (cell.x.max(0.0) as usize).min(self.width - 1),
+            (cell.y.max(0.0) as usize).min(self.depth - 1),
+        )
+    }
+
+    fn cell_center(&self, index: usize) -> Vector2<f32> {
+        let (x, z) = (index % self.width, index / self.width);
+        self.origin + Vector2::new(x as f32 + 0.5, z as f32 + 0.5) * NAV_CELL_SIZE
+    }
+
+    /// Center of a random walkable cell, `None` when the whole grid is blocked.
+    pub fn random_walkable_position(&self, rng: &mut SmallRng) -> Option<Vector2<f32>> {
+        if self.walkable_cells.is_empty() {
+            return None;
+        }
+        let index = self.walkable_cells[rng.gen_range(0..self.walkable_cells.len())];
+        Some(self.cell_center(index))
+    }
+
+    /// `position` when its cell is walkable, otherwise the center of the closest walkable cell.
+    pub fn nearest_walkable_position(&self, position: Vector2<f32>) -> Option<Vector2<f32>> {
+        let (x, z) = self.cell_of(position);
+        if self.walkable[z * self.width + x] {
+            return Some(position);
+        }
+        self.walkable_cells
+            .iter()
+            .map(|index| self.cell_center(*index))
+            .min_by(|a, b| {
+                (a - position)
+                    .norm()
+                    .partial_cmp(&(b - position).norm())
+                    .unwrap()
+            })
+    }
+
+    /// A* over the grid, returning the waypoints from `from` to `to`, or an empty path when `to`
+    /// can't be reached.
+    pub fn find_path(&self, from: Vector2<f32>, to: Vector2<f32>) -> Vec<Vector2<f32>> {
+        let (start_x, start_z) = self.cell_of(from);
+        let (goal_x, goal_z) = self.cell_of(to);
+        let start = start_z * self.width + start_x;
+        let goal = goal_z * self.width + goal_x;
+        if !self.walkable[goal] {
+            return Vec::new();
+        }
+
+        let heuristic = |index: usize| {
+            let dx = ((index % self.width) as i32 - goal_x as i32).unsigned_abs();
+            let dz = ((index / self.width) as i32 - goal_z as i32).unsigned_abs();
+            STRAIGHT_COST * dx.max(dz) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dz)
+        };
+
+        let mut costs = vec![u32::MAX; self.walkable.len()];
+        let mut came_from = vec![usize::MAX; self.walkable.len()];
+        let mut open = BinaryHeap::new();
+        costs[start] = 0;
+        open.push(Reverse((heuristic(start), start)));
+
+        while let Some(Reverse((_, current))) = open.pop() {
+            if current == goal {
+                let mut path = vec![to];
+                let mut index = came_from[current];
+                while index != usize::MAX && index != start {
+                    path.push(self.cell_center(index));
+                    index = came_from[index];
+                }
+                path.reverse();
+                return path;
+            }
+            let (x, z) = ((current % self.width) as i32, (current / self.width) as i32);
+            for dz in -1..=1 {
+                for dx in -1..=1 {
+                    if (dx == 0 && dz == 0) || !self.is_walkable(x + dx, z + dz) {
+                        continue;
+                    }
+                    // Don't cut corners of obstacles
+                    if dx != 0
+                        && dz != 0
+                        && (!self.is_walkable(x + dx, z) || !self.is_walkable(x, z + dz))
+                    {
+                        continue;
+                    }
+                    let neighbour = ((z + dz) as usize) * self.width + (x + dx) as usize;
+                    let cost = costs[current]
+                        + if dx != 0 && dz != 0 {
+                            DIAGONAL_COST
+                        } else {
+                            STRAIGHT_COST
+                        };
+                    if cost < costs[neighbour] {
+                        costs[neighbour] = cost;
+                        came_from[neighbour] = current;
+                        open.push(Reverse((cost + heuristic(neighbour), neighbour)));
+                    }
+                }
+            }
+        }
+        Vec::new()
+    }
+
+    fn is_walkable(&self, x: i32, z: i32) -> bool {
+        x >= 0
+            && z >= 0
+            && (x as usize) < self.width
+            && (z as usize) < self.depth
+            && self.walkable[z as usize * self.width + x as usize]
+    }
+
+    /// Closest walkable cell to `from` with a clear line of sight to `target`, at the height of
+    /// `from`.
+    pub fn find_firing_position(
+        &self,
+        physics: &GamePhysics,
+        from: Vector3<f32>,
+        target: Vector3<f32>,
+    ) -> Option<Vector2<f32>> {
+        let mut candidates = self
+            .walkable_cells
+            .iter()
+            .map(|index| self.cell_center(*index))
+            .filter(|position| (position - from.xz()).norm() <= MAX_FIRING_POSITION_DISTANCE)
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| {
+            (a - from.xz())
+                .norm()
+                .partial_cmp(&(b - from.xz()).norm())
+                .unwrap()
+        });
+        candidates.into_iter().find(|position| {
+            has_line_of_sight(
+                physics,
+                Vector3::new(position.x, from.y, position.y),
+                target,
+            )
+        })
+    }
+}
+
+/// Whether no static geometry (walls, crates, containers) stands between `from` and `to`.
+pub fn has_line_of_sight(physics: &GamePhysics, from: Vector3<f32>, to: Vector3<f32>) -> bool {
+    let delta = to - from;
+    let distance = delta.norm();
+    if distance <= f32::EPSILON {
+        return true;
+    }
+    let filter = |handle: ColliderHandle| {
+        physics
+            .collider_set
+            .get(handle)
+            .unwrap()
+            .parent()
+            .is_none_or(|parent| physics.rigid_body_set.get(parent).unwrap().is_static())
+    };
+    physics
+        .query_pipeline
+        .cast_ray(
+            &physics.collider_set,
+            &Ray::new(Point::from(from), delta / distance),
+            distance,
+            true,
+            physics.interaction_groups,
+            Some(&filter),
+        )
+        .is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    // A wall across the grid at x = 0, open only where z > 3
+    fn physics_with_wall() -> GamePhysics {
+        let mut physics = GamePhysics::new();
+        let wall = ColliderBuilder::cuboid(0.25, 2.0, 4.0)
+            .translation(vector![0.0, 2.0, -1.0])
+            .build();
+        physics.collider_set.insert(wall);
+        physics
+    }
+
+    #[test]
+    fn path_on_an_open_grid_reaches_the_target() {
+        let grid = NavGrid::new(&GamePhysics::new(), Vector2::new(0.0, 0.0), 5.0);
+        let to = Vector2::new(3.0, 0.0);
+        let path = grid.find_path(Vector2::new(-3.0, 0.0), to);
+        assert_eq!(path.last(), Some(&to));
+        assert!(path.iter().all(|waypoint| waypoint.y.abs() < NAV_CELL_SIZE));
+    }
+
+    #[test]
+    fn path_goes_around_a_wall() {
+        let grid = NavGrid::new(&physics_with_wall(), Vector2::new(0.0, 0.0), 5.0);
+        let to = Vector2::new(3.0, -3.0);
+        let path = grid.find_path(Vector2::new(-3.0, -3.0), to);
+        assert_eq!(path.last(), Some(&to));
+        assert!(path.iter().any(|waypoint| waypoint.y > 3.0));
+    }
+
+    #[test]
+    fn no_path_to_a_blocked_target() {
+        let grid = NavGrid::new(&physics_with_wall(), Vector2::new(0.0, 0.0), 5.0);
+        let path = grid.find_path(Vector2::new(-3.0, 0.0), Vector2::new(0.0, 0.0));
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn nearest_walkable_position_of_a_blocked_target_is_reachable() {
+        let grid = NavGrid::new(&physics_with_wall(), Vector2::new(0.0, 0.0), 5.0);
+        let open = Vector2::new(-3.0, 0.0);
+        assert_eq!(grid.nearest_walkable_position(open), Some(open));
+
+        let blocked = Vector2::new(0.0, 0.0);
+        let nearest = grid.nearest_walkable_position(blocked).unwrap();
+        assert!((nearest - blocked).norm() < 1.5);
+        assert!(!grid.find_path(open, nearest).is_empty());
+    }
+
+    #[test]
+    fn fully_blocked_grid_has_no_walkable_position() {
+        let mut physics = GamePhysics::new();
+        physics
+            .collider_set
+            .insert(ColliderBuilder::cuboid(10.0, 2.0, 10.0).build());
+        let grid = NavGrid::new(&physics, Vector2::new(0.0, 0.0), 5.0);
+        let mut rng = SmallRng::seed_from_u64(0);
+        assert_eq!(grid.random_walkable_position(&mut rng), None);
+        assert_eq!(grid.nearest_walkable_position(Vector2::new(0.0, 0.0)), None);
+    }
+}
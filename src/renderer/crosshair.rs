@@ -2,6 +2,10 @@ use crate::renderer::vertex::CoordColorVertex;
 use crate::util::any_slice_as_u8_slice;
 use nalgebra::{Rotation2, Vector2, Vector3, Vector4};
 
+// Segments of the circle cut out of the scope overlay.
+const SCOPE_SEGMENTS: usize = 64;
+pub const SCOPE_VERTICES_LEN: u32 = SCOPE_SEGMENTS as u32 * 6 + 12;
+
 pub struct Crosshair {
     pub color: Vector3<f32>,
     pub inner_line_enabled: bool,
@@ -235,3 +239,50 @@ impl Crosshair {
             + if self.center_dot_enabled { 6 } else { 0 }
     }
 }
+
+/// Scope overlay for a screen of `width` by `height` pixels: black all around a circle in the
+/// middle, with thin lines crossing it in place of the crosshair.
+pub fn get_scope_vertices(width: f32, height: f32) -> Vec<CoordColorVertex> {
+    let color = Vector4::new(0.0, 0.0, 0.0, 1.0);
+    let radius = height * 0.45;
+    // Past the corners of the screen
+    let outer_radius = width + height;
+    let line_thickness = 1.0;
+
+    let mut vertices = Vec::<CoordColorVertex>::new();
+    for idx in 0..SCOPE_SEGMENTS {
+        let start = Rotation2::new(std::f32::consts::TAU * idx as f32 / SCOPE_SEGMENTS as f32);
+        let end = Rotation2::new(std::f32::consts::TAU * (idx + 1) as f32 / SCOPE_SEGMENTS as f32);
+        let inner_start = start * Vector2::new(radius, 0.0);
+        let inner_end = end * Vector2::new(radius, 0.0);
+        let outer_start = start * Vector2::new(outer_radius, 0.0);
+        let outer_end = end * Vector2::new(outer_radius, 0.0);
+        for position in [
+            inner_start,
+            outer_start,
+            outer_end,
+            outer_end,
+            inner_end,
+            inner_start,
+        ] {
+            vertices.push(CoordColorVertex { position, color });
+        }
+    }
+
+    for (half_width, half_height) in [(radius, line_thickness), (line_thickness, radius)] {
+        for (x, y) in [
+            (-1.0, -1.0),
+            (1.0, -1.0),
+            (1.0, 1.0),
+            (1.0, 1.0),
+            (-1.0, 1.0),
+            (-1.0, -1.0),
+        ] {
+            vertices.push(CoordColorVertex {
+                position: Vector2::new(x * half_width, y * half_height),
+                color,
+            });
+        }
+    }
+    vertices
+}
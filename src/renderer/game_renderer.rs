@@ -2,14 +2,36 @@ use image::GenericImage;
 use wgpu::util::DeviceExt;
 
 
-use crate::camera::Camera;
 use crate::gui::ConrodHandle;
-use crate::renderer::crosshair::Crosshair;
+use crate::renderer::crosshair::{get_scope_vertices, Crosshair, SCOPE_VERTICES_LEN};
+use crate::renderer::render_objects::RenderQueueData;
 use crate::renderer::rendering_info::RenderingInfo;
 use crate::renderer::vertex::{CoordColorVertex, CoordVertex, QUAD_VERTICES};
-use crate::renderer::{RenderObjects, SurfaceAndWindowConfig};
+use crate::renderer::SurfaceAndWindowConfig;
 use crate::util::{any_sized_as_u8_slice, any_slice_as_u8_slice};
 
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// The web binds the render objects as a uniform buffer, which is small, so they are drawn in
+// chunks of this many objects. It has to match QUEUE_CHUNK_SIZE in common.glsl. Each chunk only
+// sees its own objects, so shadows and ambient occlusion don't cross chunks.
+#[cfg(target_arch = "wasm32")]
+const QUEUE_CHUNK_SIZE: usize = 60;
+// Objects the render objects buffer has room for at first, it grows when a frame needs more.
+#[cfg(not(target_arch = "wasm32"))]
+const INITIAL_RENDER_OBJECTS_CAPACITY: usize = 128;
+#[cfg(target_arch = "wasm32")]
+const INITIAL_RENDER_OBJECTS_CAPACITY: usize = 2 * QUEUE_CHUNK_SIZE;
+// Every chunk raymarches the whole screen again, past this the rest of the objects are dropped.
+#[cfg(target_arch = "wasm32")]
+const MAX_QUEUE_CHUNKS: usize = 8;
+// Each chunk starts with its object count and index, as a uvec4
+#[cfg(target_arch = "wasm32")]
+const QUEUE_CHUNK_HEADER_SIZE: usize = std::mem::size_of::<[u32; 4]>();
+#[cfg(target_arch = "wasm32")]
+const QUEUE_CHUNK_SIZE_BYTES: usize =
+    QUEUE_CHUNK_HEADER_SIZE + QUEUE_CHUNK_SIZE * std::mem::size_of::<RenderQueueData>();
+
 struct TerrainResolution {
     width: f32,
     height: f32
@@ -22,10 +44,28 @@ pub struct GameSceneRenderer {
     pub main_bind_group: wgpu::BindGroup,
     pub rendering_info_buffer: wgpu::Buffer,
     pub render_objects_buffer: wgpu::Buffer,
+    render_objects_bind_group_layout: wgpu::BindGroupLayout,
+    render_objects_bind_group: wgpu::BindGroup,
+    render_objects_capacity: usize,
+    // Objects left out of the last frame, the warning is only logged when it changes
+    dropped_objects_len: usize,
+    // Bytes from one chunk to the next, dynamic offsets have to be aligned
+    #[cfg(target_arch = "wasm32")]
+    queue_chunk_stride: usize,
+    // The main pipeline is drawn once per chunk
+    #[cfg(target_arch = "wasm32")]
+    queue_chunk_count: u32,
+    // Only the web draws in several passes, it keeps the closest hit of every chunk
+    depth_texture_view: Option<wgpu::TextureView>,
     pub quad_vertex_buffer: wgpu::Buffer,
     pub crosshair_vertex_buffer: wgpu::Buffer,
+    pub scope_vertex_buffer: wgpu::Buffer,
     pub terrain_texture: wgpu::Texture,
     pub render_crosshair: bool,
+    // Draws the scope overlay instead of the crosshair
+    pub render_scope: bool,
+    // The crosshair belongs to the player camera, it's hidden while looking through another one
+    pub is_spectating: bool,
 }
 
 impl GameSceneRenderer {
@@ -34,8 +74,6 @@ impl GameSceneRenderer {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         rendering_info: &RenderingInfo,
-        render_objects: &mut RenderObjects,
-        camera: &Camera,
         crosshair: &Crosshair,
     ) -> Self {
         let main_fragment_shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
@@ -77,20 +115,66 @@ impl GameSceneRenderer {
                 usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             });
 
-        let render_objects_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Rendering objects"),
+        let scope_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Scope vertex buffer"),
             contents: any_slice_as_u8_slice(
-                render_objects
-                    .get_objects_and_active_len(&camera.get_frustum())
+                get_scope_vertices(surface_config.width as f32, surface_config.height as f32)
                     .as_slice(),
             ),
-            usage: if cfg!(target_arch = "wasm32") {
-                wgpu::BufferUsages::UNIFORM
-            } else {
-                wgpu::BufferUsages::STORAGE
-            } | wgpu::BufferUsages::COPY_DST,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         });
 
+        let render_objects_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Bind group layout descriptor render objects"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    count: None,
+                    binding: 0,
+                    ty: if cfg!(target_arch = "wasm32") {
+                        wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: true,
+                            min_binding_size: None,
+                        }
+                    } else {
+                        wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        }
+                    },
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                }],
+            });
+
+        #[cfg(target_arch = "wasm32")]
+        let queue_chunk_stride = {
+            let alignment = device.limits().min_uniform_buffer_offset_alignment as usize;
+            (QUEUE_CHUNK_SIZE_BYTES + alignment - 1) / alignment * alignment
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        let render_objects_size =
+            INITIAL_RENDER_OBJECTS_CAPACITY / QUEUE_CHUNK_SIZE * queue_chunk_stride;
+        #[cfg(not(target_arch = "wasm32"))]
+        let render_objects_size =
+            INITIAL_RENDER_OBJECTS_CAPACITY * std::mem::size_of::<RenderQueueData>();
+        let (render_objects_buffer, render_objects_bind_group) = create_render_objects_buffer(
+            device,
+            &render_objects_bind_group_layout,
+            render_objects_size,
+        );
+
+        let depth_texture_view = if cfg!(target_arch = "wasm32") {
+            Some(create_depth_texture_view(
+                device,
+                surface_config.width,
+                surface_config.height,
+            ))
+        } else {
+            None
+        };
+
         let rendering_info_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Rendering info buffer"),
             contents: any_sized_as_u8_slice(rendering_info),
@@ -345,20 +429,6 @@ impl GameSceneRenderer {
                         },
                         visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::VERTEX,
                     },
-                    wgpu::BindGroupLayoutEntry {
-                        count: None,
-                        binding: 1,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: if cfg!(target_arch = "wasm32") {
-                                wgpu::BufferBindingType::Uniform
-                            } else {
-                                wgpu::BufferBindingType::Storage { read_only: true }
-                            },
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                    },
                     wgpu::BindGroupLayoutEntry {
                         count: None,
                         binding: 2,
@@ -417,7 +487,7 @@ impl GameSceneRenderer {
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Render pipeline layout"),
-                bind_group_layouts: &[&main_bindgroup_layout],
+                bind_group_layouts: &[&main_bindgroup_layout, &render_objects_bind_group_layout],
                 push_constant_ranges: &[],
             });
 
@@ -429,10 +499,6 @@ impl GameSceneRenderer {
                     binding: 0,
                     resource: rendering_info_buffer.as_entire_binding(),
                 },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: render_objects_buffer.as_entire_binding(),
-                },
                 wgpu::BindGroupEntry {
                     binding: 2,
                     resource: wgpu::BindingResource::Sampler(&texture_sampler),
@@ -534,7 +600,7 @@ impl GameSceneRenderer {
                 clamp_depth: false,
                 conservative: false,
             },
-            depth_stencil: None,
+            depth_stencil: depth_stencil_state(true),
             multisample: wgpu::MultisampleState {
                 count: 1,
                 mask: !0,
@@ -569,7 +635,7 @@ impl GameSceneRenderer {
                     clamp_depth: false,
                     conservative: false,
                 },
-                depth_stencil: None,
+                depth_stencil: depth_stencil_state(false),
                 multisample: wgpu::MultisampleState {
                     count: 1,
                     mask: !0,
@@ -604,7 +670,7 @@ impl GameSceneRenderer {
                     clamp_depth: false,
                     conservative: false,
                 },
-                depth_stencil: None,
+                depth_stencil: depth_stencil_state(false),
                 multisample: wgpu::MultisampleState {
                     count: 1,
                     mask: !0,
@@ -618,14 +684,126 @@ impl GameSceneRenderer {
             screen_render_pipeline,
             crosshair_render_pipeline,
             render_objects_buffer,
+            render_objects_bind_group_layout,
+            render_objects_bind_group,
+            render_objects_capacity: INITIAL_RENDER_OBJECTS_CAPACITY,
+            dropped_objects_len: 0,
+            #[cfg(target_arch = "wasm32")]
+            queue_chunk_stride,
+            #[cfg(target_arch = "wasm32")]
+            queue_chunk_count: 1,
+            depth_texture_view,
             rendering_info_buffer,
             quad_vertex_buffer,
             crosshair_vertex_buffer,
+            scope_vertex_buffer,
             terrain_texture,
             render_crosshair: false,
+            render_scope: false,
+            is_spectating: false,
+        }
+    }
+
+    /// The scope overlay and the depth buffer are sized to the screen, they have to be rebuilt
+    /// when the window resizes.
+    pub fn resize(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, width: u32, height: u32) {
+        queue.write_buffer(
+            &self.scope_vertex_buffer,
+            0,
+            any_slice_as_u8_slice(get_scope_vertices(width as f32, height as f32).as_slice()),
+        );
+        if self.depth_texture_view.is_some() {
+            self.depth_texture_view = Some(create_depth_texture_view(device, width, height));
         }
     }
 
+    /// Uploads the objects to draw this frame, only the range they use. The buffer grows when
+    /// they don't fit, up to what the device allows. Returns how many objects were uploaded.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn write_render_objects(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        objects: &[RenderQueueData],
+    ) -> usize {
+        let max_len = device.limits().max_storage_buffer_binding_size as usize
+            / std::mem::size_of::<RenderQueueData>();
+        let len = objects.len().min(max_len);
+        self.set_dropped_objects_len(objects.len() - len);
+
+        if len > self.render_objects_capacity {
+            self.grow_render_objects_buffer(device, len.next_power_of_two().min(max_len));
+        }
+        queue.write_buffer(
+            &self.render_objects_buffer,
+            0,
+            any_slice_as_u8_slice(&objects[..len]),
+        );
+        len
+    }
+
+    /// Uploads the objects to draw this frame in chunks, only the chunks they use. The buffer
+    /// grows when they don't fit, up to `MAX_QUEUE_CHUNKS`. Returns how many objects were
+    /// uploaded.
+    #[cfg(target_arch = "wasm32")]
+    pub fn write_render_objects(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        objects: &[RenderQueueData],
+    ) -> usize {
+        let max_len = MAX_QUEUE_CHUNKS * QUEUE_CHUNK_SIZE;
+        let len = objects.len().min(max_len);
+        self.set_dropped_objects_len(objects.len() - len);
+
+        // The first chunk also draws the background, it's there even without any object
+        let chunk_count = ((len + QUEUE_CHUNK_SIZE - 1) / QUEUE_CHUNK_SIZE).max(1);
+        if chunk_count * QUEUE_CHUNK_SIZE > self.render_objects_capacity {
+            self.grow_render_objects_buffer(
+                device,
+                chunk_count.next_power_of_two().min(MAX_QUEUE_CHUNKS) * QUEUE_CHUNK_SIZE,
+            );
+        }
+        for index in 0..chunk_count {
+            let chunk = &objects[index * QUEUE_CHUNK_SIZE..len.min((index + 1) * QUEUE_CHUNK_SIZE)];
+            let offset = (index * self.queue_chunk_stride) as wgpu::BufferAddress;
+            queue.write_buffer(
+                &self.render_objects_buffer,
+                offset,
+                any_sized_as_u8_slice(&[chunk.len() as u32, index as u32, 0, 0]),
+            );
+            queue.write_buffer(
+                &self.render_objects_buffer,
+                offset + QUEUE_CHUNK_HEADER_SIZE as wgpu::BufferAddress,
+                any_slice_as_u8_slice(chunk),
+            );
+        }
+        self.queue_chunk_count = chunk_count as u32;
+        len
+    }
+
+    fn grow_render_objects_buffer(&mut self, device: &wgpu::Device, capacity: usize) {
+        #[cfg(target_arch = "wasm32")]
+        let size = capacity / QUEUE_CHUNK_SIZE * self.queue_chunk_stride;
+        #[cfg(not(target_arch = "wasm32"))]
+        let size = capacity * std::mem::size_of::<RenderQueueData>();
+        let (render_objects_buffer, render_objects_bind_group) =
+            create_render_objects_buffer(device, &self.render_objects_bind_group_layout, size);
+        self.render_objects_buffer = render_objects_buffer;
+        self.render_objects_bind_group = render_objects_bind_group;
+        self.render_objects_capacity = capacity;
+    }
+
+    fn set_dropped_objects_len(&mut self, dropped_objects_len: usize) {
+        if dropped_objects_len > 0 && dropped_objects_len != self.dropped_objects_len {
+            log::warn!(
+                "{} render objects don't fit in the render queue and are not drawn",
+                dropped_objects_len
+            );
+        }
+        self.dropped_objects_len = dropped_objects_len;
+    }
+
     pub fn render(
         &mut self,
         view: &wgpu::TextureView,
@@ -650,25 +828,123 @@ impl GameSceneRenderer {
                     store: true,
                 },
             }],
-            depth_stencil_attachment: None,
+            depth_stencil_attachment: self.depth_texture_view.as_ref().map(|view| {
+                wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: false,
+                    }),
+                    stencil_ops: None,
+                }
+            }),
         });
 
         render_pass.set_bind_group(0, &self.main_bind_group, &[]);
         render_pass.set_pipeline(&self.main_render_pipeline);
         render_pass.set_vertex_buffer(0, self.quad_vertex_buffer.slice(..));
-        render_pass.draw(0..4, 0..1);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            render_pass.set_bind_group(1, &self.render_objects_bind_group, &[]);
+            render_pass.draw(0..4, 0..1);
+        }
+        #[cfg(target_arch = "wasm32")]
+        for chunk_index in 0..self.queue_chunk_count {
+            let offset = chunk_index * self.queue_chunk_stride as wgpu::DynamicOffset;
+            render_pass.set_bind_group(1, &self.render_objects_bind_group, &[offset]);
+            render_pass.draw(0..4, 0..1);
+        }
 
         render_pass.set_pipeline(&self.screen_render_pipeline);
         render_pass.draw(0..4, 0..1);
 
-        if self.render_crosshair {
+        if self.render_crosshair && !self.is_spectating {
             render_pass.set_pipeline(&self.crosshair_render_pipeline);
-            render_pass.set_vertex_buffer(0, self.crosshair_vertex_buffer.slice(..));
-            render_pass.draw(0..crosshair.vertices_len(), 0..1);
+            if self.render_scope {
+                render_pass.set_vertex_buffer(0, self.scope_vertex_buffer.slice(..));
+                render_pass.draw(0..SCOPE_VERTICES_LEN, 0..1);
+            } else {
+                render_pass.set_vertex_buffer(0, self.crosshair_vertex_buffer.slice(..));
+                render_pass.draw(0..crosshair.vertices_len(), 0..1);
+            }
         }
     }
 }
 
+// On the web, only one chunk is bound at a time, picked with a dynamic offset.
+fn create_render_objects_buffer(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    size: usize,
+) -> (wgpu::Buffer, wgpu::BindGroup) {
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Rendering objects"),
+        size: size as wgpu::BufferAddress,
+        usage: if cfg!(target_arch = "wasm32") {
+            wgpu::BufferUsages::UNIFORM
+        } else {
+            wgpu::BufferUsages::STORAGE
+        } | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    #[cfg(target_arch = "wasm32")]
+    let resource = wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+        buffer: &buffer,
+        offset: 0,
+        size: wgpu::BufferSize::new(QUEUE_CHUNK_SIZE_BYTES as u64),
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    let resource = buffer.as_entire_binding();
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Render objects bind group"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource,
+        }],
+    });
+    (buffer, bind_group)
+}
+
+// Only the web has a depth buffer. Only the main pipeline tests and writes it, to keep the
+// closest hit of every chunk.
+fn depth_stencil_state(is_depth_tested: bool) -> Option<wgpu::DepthStencilState> {
+    if !cfg!(target_arch = "wasm32") {
+        return None;
+    }
+    Some(wgpu::DepthStencilState {
+        format: DEPTH_FORMAT,
+        depth_write_enabled: is_depth_tested,
+        depth_compare: if is_depth_tested {
+            wgpu::CompareFunction::LessEqual
+        } else {
+            wgpu::CompareFunction::Always
+        },
+        stencil: wgpu::StencilState::default(),
+        bias: wgpu::DepthBiasState::default(),
+    })
+}
+
+fn create_depth_texture_view(device: &wgpu::Device, width: u32, height: u32) -> wgpu::TextureView {
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: Some("Depth texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
 fn default_texture_view_descriptor(_label: &str) -> wgpu::TextureViewDescriptor {
     wgpu::TextureViewDescriptor::default()
     // {
@@ -6,11 +6,12 @@ use game_renderer::GameSceneRenderer;
 use render_objects::RenderObjects;
 
 use crate::camera::Camera;
+use crate::free_camera::FreeCamera;
 
 use crate::gui::ConrodHandle;
 use crate::renderer::crosshair::Crosshair;
 use crate::renderer::rendering_info::RenderingInfo;
-use crate::util::{any_sized_as_u8_slice, any_slice_as_u8_slice};
+use crate::util::any_sized_as_u8_slice;
 
 pub mod conrod_renderer;
 pub mod crosshair;
@@ -24,10 +25,19 @@ pub struct SurfaceAndWindowConfig {
     pub window_scale_factor: f64,
 }
 
+impl SurfaceAndWindowConfig {
+    /// Width over height of the surface.
+    pub fn get_aspect_ratio(&self) -> f32 {
+        self.surface.width as f32 / self.surface.height as f32
+    }
+}
+
 pub struct Renderer {
     pub render_objects: RenderObjects,
     pub rendering_info: RenderingInfo,
     pub camera: Camera,
+    // Replaces the player camera for drawing while it is set
+    pub free_camera: Option<FreeCamera>,
     pub crosshair: Crosshair,
     pub is_render_game: bool,
     pub is_render_gui: bool,
@@ -81,7 +91,7 @@ impl Renderer {
         };
         surface.configure(&device, &surface_config);
 
-        let mut render_objects = RenderObjects::new();
+        let render_objects = RenderObjects::new();
         let rendering_info = RenderingInfo::new(window_size);
 
         let camera = Camera::new();
@@ -93,8 +103,6 @@ impl Renderer {
                 &device,
                 &queue,
                 &rendering_info,
-                &mut render_objects,
-                &camera,
                 &crosshair,
             ),
             crosshair,
@@ -115,6 +123,7 @@ impl Renderer {
             device,
             queue,
             camera,
+            free_camera: None,
         }
     }
 
@@ -124,6 +133,8 @@ impl Renderer {
             self.surface_and_window_config.surface.height = new_size.height;
             self.surface_and_window_config.window_scale_factor = scale_factor;
             self.rendering_info.resize(new_size);
+            self.game_renderer
+                .resize(&self.device, &self.queue, new_size.width, new_size.height);
             self.surface
                 .configure(&self.device, &self.surface_and_window_config.surface);
         }
@@ -135,26 +146,31 @@ impl Renderer {
         conrod_handle: &mut ConrodHandle,
     ) -> Result<(), wgpu::SurfaceError> {
         if self.is_render_game {
+            let camera = match self.free_camera {
+                Some(ref free_camera) => &free_camera.camera,
+                None => &self.camera,
+            };
+            let aspect_ratio = self.surface_and_window_config.get_aspect_ratio();
             let objects = self
                 .render_objects
-                .get_objects_and_active_len(&self.camera.get_frustum());
-            self.rendering_info.fov_shootanim.x = self.camera.fov;
-            self.rendering_info.cam_pos = self.camera.position;
-            self.rendering_info.cam_dir = *self.camera.get_direction();
+                .get_objects_and_active_len(&camera.get_frustum(aspect_ratio));
+            self.rendering_info.fov_shootanim.x = camera.get_fov(aspect_ratio);
+            self.game_renderer.render_scope = camera.is_scope_visible;
+            self.game_renderer.is_spectating = self.free_camera.is_some();
+            self.rendering_info.cam_pos = camera.position;
+            self.rendering_info.cam_dir = *camera.get_view_direction();
             self.rendering_info.reso_time.x = self.surface_and_window_config.surface.width as f32;
             self.rendering_info.reso_time.y = self.surface_and_window_config.surface.height as f32;
             self.rendering_info.reso_time.z = app_run_time;
-            self.rendering_info.queuecount_raymarchmaxstep_aostep.x = objects.len() as u32;
+            let objects_len =
+                self.game_renderer
+                    .write_render_objects(&self.device, &self.queue, objects);
+            self.rendering_info.queuecount_raymarchmaxstep_aostep.x = objects_len as u32;
             self.queue.write_buffer(
                 &self.game_renderer.rendering_info_buffer,
                 0,
                 any_sized_as_u8_slice(&self.rendering_info),
             );
-            self.queue.write_buffer(
-                &self.game_renderer.render_objects_buffer,
-                0,
-                any_slice_as_u8_slice(objects.as_slice()),
-            );
         }
 
         let frame = self.surface.get_current_texture()?;
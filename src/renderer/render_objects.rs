@@ -38,6 +38,7 @@ pub enum MaterialType {
     RGBANoiseMedium = 18,
     GrayNoiseSmall = 19,
     Asphalt = 20,
+    Snow = 21,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -80,14 +81,10 @@ impl RenderQueueData {
     }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-pub const QUEUE_SIZE: usize = 130;
-#[cfg(target_arch = "wasm32")]
-pub const QUEUE_SIZE: usize = 60;
-
 pub struct RenderObjects {
     render_objects: Vec<(RenderQueueData, ObjectBound)>,
     render_objects_static: Vec<(RenderQueueData, ObjectBound)>,
+    // Kept between frames so that it doesn't reallocate
     resulted_objects: Vec<RenderQueueData>,
 }
 
@@ -96,7 +93,7 @@ impl RenderObjects {
         Self {
             render_objects: Vec::new(),
             render_objects_static: Vec::new(),
-            resulted_objects: vec![RenderQueueData::new_none(); QUEUE_SIZE],
+            resulted_objects: Vec::new(),
         }
     }
 
@@ -122,36 +119,36 @@ impl RenderObjects {
         self.render_objects_static.get_mut(length).unwrap()
     }
 
+    /// Positions of everything queued to be drawn this frame.
+    pub fn get_positions(&self) -> impl Iterator<Item = Vector3<f32>> + '_ {
+        self.render_objects_static
+            .iter()
+            .chain(self.render_objects.iter())
+            .filter(|(object, _)| !matches!(object.shape_type_material_ids.0, ShapeType::None))
+            .map(|(object, _)| object.position)
+    }
+
     pub fn clear(&mut self) {
         self.render_objects.clear();
         self.render_objects_static.clear();
     }
 
-    pub fn get_objects_and_active_len(&mut self, frustum: &Frustum) -> Vec<RenderQueueData> {
-        for object in self.resulted_objects.iter_mut() {
-            *object = RenderQueueData::new_none();
-        }
-
-        let mut index = 0;
-        for (object, bound) in self.render_objects_static.iter() {
-            if index >= QUEUE_SIZE {
-                break;
-            }
-            if frustum.is_on_frustum(&object.position, bound) {
-                self.resulted_objects[index] = *object;
-                index += 1;
-            }
-        }
-        for (object, bound) in self.render_objects.iter() {
-            if index >= QUEUE_SIZE {
-                break;
-            }
-            if frustum.is_on_frustum(&object.position, bound) {
-                self.resulted_objects[index] = *object;
-                index += 1;
-            }
-        }
+    /// Objects to draw this frame, the ones out of the frustum are left out. Static objects come
+    /// first. The dynamic ones are cleared, they have to be queued again for the next frame.
+    pub fn get_objects_and_active_len(&mut self, frustum: &Frustum) -> &[RenderQueueData] {
+        self.resulted_objects.clear();
+        self.resulted_objects.extend(
+            self.render_objects_static
+                .iter()
+                .chain(self.render_objects.iter())
+                .filter(|(object, bound)| {
+                    // The shader stops at the first object without a shape
+                    !matches!(object.shape_type_material_ids.0, ShapeType::None)
+                        && frustum.is_on_frustum(&object.position, bound)
+                })
+                .map(|(object, _)| *object),
+        );
         self.render_objects.clear();
-        self.resulted_objects.clone()
+        &self.resulted_objects
     }
 }
@@ -1,13 +1,45 @@
 use winit::dpi::PhysicalSize;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(u32)]
 pub enum BackgroundType {
     None = 0,
     Forest = 1,
     City = 2,
+    Snow = 3,
+}
+
+impl From<usize> for BackgroundType {
+    fn from(x: usize) -> Self {
+        match x {
+            0 => BackgroundType::None,
+            1 => BackgroundType::Forest,
+            2 => BackgroundType::City,
+            3 => BackgroundType::Snow,
+            _ => unreachable!(),
+        }
+    }
 }
 
+impl BackgroundType {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            BackgroundType::None => "None",
+            BackgroundType::Forest => "Forest",
+            BackgroundType::City => "City",
+            BackgroundType::Snow => "Snow",
+        }
+    }
+}
+
+/// Environments which can be picked for a run, in the order of the selection list.
+pub const ENVIRONMENTS: [BackgroundType; 3] = [
+    BackgroundType::Forest,
+    BackgroundType::City,
+    BackgroundType::Snow,
+];
+pub const ENVIRONMENT_NAMES: &[&str; 3] = &["Forest", "City", "Snow"];
+
 #[derive(Debug, Clone)]
 pub struct RenderingInfo {
     pub reso_time: nalgebra::Vector3<f32>,
@@ -17,7 +49,8 @@ pub struct RenderingInfo {
     pub cam_dir: nalgebra::Vector3<f32>,
     _p3: [i32; 1],
     pub fov_shootanim: nalgebra::Vector2<f32>,
-    _p4: [i32; 2],
+    pub weapon_type: u32,
+    _p4: [i32; 1],
     pub queuecount_raymarchmaxstep_aostep: nalgebra::Vector3<u32>,
     pub background_type: BackgroundType,
 }
@@ -35,9 +68,10 @@ impl RenderingInfo {
             fov_shootanim: nalgebra::Vector2::new(90.0f32.to_radians(), 0.0),
             queuecount_raymarchmaxstep_aostep: nalgebra::Vector3::new(0, 50, 3),
             background_type: BackgroundType::None,
+            weapon_type: 0,
             _p2: [0; 1],
             _p3: [0; 1],
-            _p4: [0; 2],
+            _p4: [0; 1],
             _p1: [0; 1],
         }
     }
@@ -6,7 +6,6 @@ use std::io::{BufReader, Cursor};
 use hecs::{Entity, World};
 
 use rapier3d::prelude::*;
-use winit::event::{MouseButton, VirtualKeyCode};
 use winit::event_loop::ControlFlow;
 
 use crate::animation::InOutAnimation;
@@ -16,6 +15,7 @@ use crate::database::Database;
 use crate::entity::target::{Patrol, SphereTarget, Validity};
 
 use crate::gui::ConrodHandle;
+use crate::input_binding::Action;
 use crate::input_manager::InputManager;
 use crate::physics::GamePhysics;
 
@@ -25,8 +25,8 @@ use crate::renderer::Renderer;
 use crate::scene::game_score_scene::{ClassicGameScoreDisplay, GameModeScore, GameScoreScene};
 use crate::scene::pause_scene::PauseScene;
 use crate::scene::{
-    GameDifficulty, GameState, MaybeMessage, Scene, SceneOp, FINISHING_DURATION,
-    IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION, PREPARE_DURATION,
+    GameDifficulty, GameState, MaybeMessage, Scene, SceneOp, Value, FINISHING_DURATION,
+    IN_SHOOT_ANIM_DURATION, MARGIN, OUT_SHOOT_ANIM_DURATION, PREPARE_DURATION,
 };
 use crate::systems::container::{enqueue_container, spawn_container};
 use crate::systems::crate_box::{enqueue_crate, spawn_crate};
@@ -36,12 +36,14 @@ use crate::systems::update_player_movement::update_player_position;
 use crate::systems::wall::{enqueue_wall, spawn_wall};
 use crate::timer::{Stopwatch, Timer};
 
+use crate::weapon::{Weapon, WeaponType};
 use crate::window::Window;
 use conrod_core::widget::envelope_editor::EnvelopePoint;
 use conrod_core::widget::{Canvas, Text};
 use conrod_core::widget_ids;
 
 use crate::camera::Camera;
+use crate::character_controller::CharacterController;
 use crate::systems::shoot_ray::shoot_ray;
 use crate::systems::shootanim::shootanim;
 use nalgebra::Vector3;
@@ -59,6 +61,7 @@ widget_ids! {
         // The main canvas
         canvas,
         start_duration_label,
+        ammo_label,
 
         indicator_canvas,
 
@@ -96,9 +99,11 @@ pub struct ClassicGameScene {
     world: World,
     physics: GamePhysics,
     player_rigid_body_handle: RigidBodyHandle,
+    player_controller: CharacterController,
     game_state: GameState,
     delta_shoot_time: Stopwatch,
-    shoot_timer: Timer,
+    weapon: Weapon,
+    background_type: BackgroundType,
     score: Score,
     rng: SmallRng,
     round_timer: Timer,
@@ -125,7 +130,7 @@ impl ClassicGameScene {
             .insert(ColliderBuilder::new(SharedShape::cuboid(20.0, 1.0, 10.0)).build());
 
         let player_rigid_body_handle =
-            setup_player_collider(&mut physics, Vector3::new(0.0, 1.0, 0.0));
+            setup_player_collider(&mut physics, Vector3::new(0.0, 2.6, 0.0));
 
         spawn_target(
             &mut world,
@@ -201,11 +206,13 @@ impl ClassicGameScene {
             world,
             physics,
             player_rigid_body_handle,
+            player_controller: CharacterController::new(),
             ids: ClassicGameSceneIds::new(conrod_handle.get_ui_mut().widget_id_generator()),
             score: Score::new(),
             delta_shoot_time: Stopwatch::new(),
             game_state: GameState::Preround,
-            shoot_timer: Timer::new_finished(),
+            weapon: Weapon::new(WeaponType::Pistol),
+            background_type: BackgroundType::Forest,
             target_spawn_state: TargetSpawnState::Primary,
             rng: SmallRng::from_entropy(),
             shoot_animation: InOutAnimation::new(IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION),
@@ -231,8 +238,6 @@ impl Scene for ClassicGameScene {
         renderer.is_render_gui = true;
         renderer.is_render_game = true;
 
-        renderer.rendering_info.background_type = BackgroundType::Forest;
-
         init_player(&mut self.physics, renderer, self.player_rigid_body_handle);
 
         // Ground
@@ -255,19 +260,28 @@ impl Scene for ClassicGameScene {
         audio_context.global_sinks_map.remove("bgm");
 
         if let Some(m) = message {
+            if let Some(Value::I64(background_type)) = m.get("background_type") {
+                self.background_type = BackgroundType::from(*background_type as usize);
+            }
+            if let Some(Value::I64(weapon_type)) = m.get("weapon_type") {
+                self.weapon = Weapon::new(WeaponType::from(*weapon_type as usize));
+            }
             if m.contains_key("from_pause") {
                 self.freeze = true;
                 renderer.game_renderer.render_crosshair = false;
                 self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION))
             }
         }
+
+        renderer.rendering_info.background_type = self.background_type;
+        renderer.rendering_info.weapon_type = self.weapon.get_type() as u32;
     }
 
     fn update(
         &mut self,
         _window: &mut Window,
         renderer: &mut Renderer,
-        input_manager: &InputManager,
+        input_manager: &mut InputManager,
         delta_time: f32,
         conrod_handle: &mut ConrodHandle,
         audio_context: &mut AudioContext,
@@ -334,14 +348,18 @@ impl Scene for ClassicGameScene {
             .set(self.ids.accuracy_label, &mut ui_cell);
         }
 
+        Text::new(&self.weapon.get_ammo_text())
+            .font_size(14)
+            .color(color::WHITE)
+            .bottom_right_with_margin_on(self.ids.canvas, MARGIN)
+            .set(self.ids.ammo_label, &mut ui_cell);
+
         let mut game_finished = false;
 
         let mut scene_op = SceneOp::None;
 
         if !self.freeze {
-            renderer
-                .camera
-                .move_direction(input_manager.mouse_movement * delta_time);
+            renderer.camera.move_direction(input_manager.mouse_movement);
 
             let _player_position = update_player_position(
                 delta_time,
@@ -349,6 +367,7 @@ impl Scene for ClassicGameScene {
                 &mut renderer.camera,
                 &mut self.physics,
                 self.player_rigid_body_handle,
+                &mut self.player_controller,
             );
         }
 
@@ -379,7 +398,8 @@ impl Scene for ClassicGameScene {
             }
             GameState::Round => {
                 self.round_timer.update(delta_time);
-                self.shoot_timer.update(delta_time);
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
                 self.delta_shoot_time.update(delta_time);
 
                 update_target(
@@ -405,7 +425,8 @@ impl Scene for ClassicGameScene {
             }
             GameState::Finishing(ref mut timer) => {
                 renderer.game_renderer.render_crosshair = false;
-                self.shoot_timer.update(delta_time);
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
                 timer.update(delta_time);
 
                 Text::new("Time out!")
@@ -440,7 +461,12 @@ impl Scene for ClassicGameScene {
             );
 
             if shoot_trigger {
-                self.shoot(input_manager, audio_context, &renderer.camera, delta_time);
+                self.shoot(
+                    input_manager,
+                    audio_context,
+                    &mut renderer.camera,
+                    delta_time,
+                );
             }
         }
 
@@ -459,6 +485,7 @@ impl Scene for ClassicGameScene {
                         score: self.score.score,
                         avg_hit_time: GAME_DURATION / self.score.hit.max(1) as f32,
                         created_at: Utc::now().naive_utc(),
+                        background_type: self.background_type,
                     }),
                     self.difficulty,
                 )),
@@ -466,7 +493,7 @@ impl Scene for ClassicGameScene {
             );
         }
 
-        if input_manager.is_keyboard_press(&VirtualKeyCode::Escape) {
+        if input_manager.is_action_press(Action::Pause) {
             scene_op = SceneOp::Push(Box::new(PauseScene::new(renderer, conrod_handle)), None);
         }
 
@@ -501,6 +528,8 @@ impl Scene for ClassicGameScene {
         _database: &mut Database,
     ) {
         renderer.rendering_info.fov_shootanim.y = 0.0;
+        renderer.camera.reset_zoom();
+        renderer.camera.effects.clear();
         renderer.render_objects.clear();
         renderer.game_renderer.render_crosshair = false;
         window.set_is_cursor_grabbed(false);
@@ -512,12 +541,11 @@ impl ClassicGameScene {
         &mut self,
         input_manager: &InputManager,
         audio_context: &mut AudioContext,
-        camera: &Camera,
+        camera: &mut Camera,
         _delta_time: f32,
     ) {
-        if input_manager.is_mouse_press(&MouseButton::Left) && self.shoot_timer.is_finished() {
+        if let Some(direction) = self.weapon.fire(input_manager, camera) {
             self.shoot_animation.trigger();
-            self.shoot_timer.reset(0.4);
 
             #[cfg(not(target_arch = "wasm32"))]
             {
@@ -529,14 +557,15 @@ impl ClassicGameScene {
                 audio_context.push(Sink::Regular(sink));
             }
 
-            if let Some((handle, _distance)) = shoot_ray(&self.physics, camera) {
+            if let Some((handle, _distance)) = shoot_ray(&self.physics, camera, direction) {
                 let collider = self.physics.collider_set.get(handle).unwrap();
                 let entity = Entity::from_bits(collider.user_data as u64).unwrap();
 
                 let mut need_to_spawn = false;
                 if let Ok(mut target) = self.world.get_mut::<SphereTarget>(entity) {
                     if target.try_shoot(audio_context) {
-                        let shoot_time = self.delta_shoot_time.get_duration();
+                        let shoot_time = self.delta_shoot_time.get_duration()
+                            - self.weapon.get_last_shot_delay();
                         self.delta_shoot_time.reset();
 
                         need_to_spawn = true;
@@ -7,7 +7,6 @@ use std::io::{BufReader, Cursor};
 use hecs::{Entity, World};
 
 use rapier3d::prelude::*;
-use winit::event::{MouseButton, VirtualKeyCode};
 use winit::event_loop::ControlFlow;
 
 use crate::animation::InOutAnimation;
@@ -16,6 +15,7 @@ use crate::audio::{Sink};
 use crate::database::Database;
 
 use crate::gui::ConrodHandle;
+use crate::input_binding::Action;
 use crate::input_manager::InputManager;
 use crate::physics::GamePhysics;
 use crate::renderer::render_objects::MaterialType;
@@ -23,11 +23,12 @@ use crate::renderer::render_objects::ShapeType;
 use crate::renderer::Renderer;
 use crate::scene::pause_scene::PauseScene;
 use crate::scene::{
-    GameDifficulty, GameState, MaybeMessage, Scene, SceneOp, FINISHING_DURATION,
-    IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION, PREPARE_DURATION,
+    GameDifficulty, GameState, MaybeMessage, Scene, SceneOp, Value, FINISHING_DURATION,
+    IN_SHOOT_ANIM_DURATION, MARGIN, OUT_SHOOT_ANIM_DURATION, PREPARE_DURATION,
 };
 use crate::timer::{Stopwatch, Timer};
 
+use crate::weapon::{Weapon, WeaponType};
 use crate::window::Window;
 use conrod_core::widget::{Canvas, Text};
 use conrod_core::widget_ids;
@@ -35,6 +36,7 @@ use conrod_core::widget_ids;
 use crate::entity::target::{Patrol, SphereTarget, Validity};
 
 use crate::camera::Camera;
+use crate::character_controller::CharacterController;
 use crate::renderer::rendering_info::BackgroundType;
 use crate::scene::game_score_scene::{EliminationGameScoreDisplay, GameModeScore, GameScoreScene};
 use crate::systems::player::{init_player, setup_player_collider};
@@ -53,6 +55,7 @@ widget_ids! {
         // The main canvas
         canvas,
         start_duration_label,
+        ammo_label,
 
         indicator_canvas,
 
@@ -88,8 +91,10 @@ pub struct EliminationGameScene {
     world: World,
     physics: GamePhysics,
     player_rigid_body_handle: RigidBodyHandle,
+    player_controller: CharacterController,
     delta_shoot_time: Stopwatch,
-    shoot_timer: Timer,
+    weapon: Weapon,
+    background_type: BackgroundType,
     score: Score,
     rng: SmallRng,
     shoot_animation: InOutAnimation,
@@ -148,16 +153,18 @@ impl EliminationGameScene {
 
         // Player
         let player_rigid_body_handle =
-            setup_player_collider(&mut physics, Vector3::new(0.0, 71.0, 0.0));
+            setup_player_collider(&mut physics, Vector3::new(0.0, 71.6, 0.0));
 
         Self {
             world,
             physics,
             player_rigid_body_handle,
+            player_controller: CharacterController::new(),
             ids: EliminationGameSceneIds::new(conrod_handle.get_ui_mut().widget_id_generator()),
             score: Score::new(),
             delta_shoot_time: Stopwatch::new(),
-            shoot_timer: Timer::new_finished(),
+            weapon: Weapon::new(WeaponType::Pistol),
+            background_type: BackgroundType::City,
             rng: SmallRng::from_entropy(),
             shoot_animation: InOutAnimation::new(IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION),
             freeze: false,
@@ -182,8 +189,6 @@ impl Scene for EliminationGameScene {
         renderer.is_render_gui = true;
         renderer.is_render_game = true;
 
-        renderer.rendering_info.background_type = BackgroundType::City;
-
         init_player(&mut self.physics, renderer, self.player_rigid_body_handle);
 
         // Ground
@@ -199,19 +204,28 @@ impl Scene for EliminationGameScene {
         audio_context.global_sinks_map.remove("bgm");
 
         if let Some(m) = message {
+            if let Some(Value::I64(background_type)) = m.get("background_type") {
+                self.background_type = BackgroundType::from(*background_type as usize);
+            }
+            if let Some(Value::I64(weapon_type)) = m.get("weapon_type") {
+                self.weapon = Weapon::new(WeaponType::from(*weapon_type as usize));
+            }
             if m.contains_key("from_pause") {
                 self.freeze = true;
                 renderer.game_renderer.render_crosshair = false;
                 self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION))
             }
         }
+
+        renderer.rendering_info.background_type = self.background_type;
+        renderer.rendering_info.weapon_type = self.weapon.get_type() as u32;
     }
 
     fn update(
         &mut self,
         _window: &mut Window,
         renderer: &mut Renderer,
-        input_manager: &InputManager,
+        input_manager: &mut InputManager,
         delta_time: f32,
         conrod_handle: &mut ConrodHandle,
         audio_context: &mut AudioContext,
@@ -280,14 +294,18 @@ impl Scene for EliminationGameScene {
             .set(self.ids.accuracy_label, &mut ui_cell);
         }
 
+        Text::new(&self.weapon.get_ammo_text())
+            .font_size(14)
+            .color(color::WHITE)
+            .bottom_right_with_margin_on(self.ids.canvas, MARGIN)
+            .set(self.ids.ammo_label, &mut ui_cell);
+
         let mut game_finished = false;
 
         let mut scene_op = SceneOp::None;
 
         if !self.freeze {
-            renderer
-                .camera
-                .move_direction(input_manager.mouse_movement * delta_time);
+            renderer.camera.move_direction(input_manager.mouse_movement);
 
             let _player_position = update_player_position(
                 delta_time,
@@ -295,6 +313,7 @@ impl Scene for EliminationGameScene {
                 &mut renderer.camera,
                 &mut self.physics,
                 self.player_rigid_body_handle,
+                &mut self.player_controller,
             );
         }
 
@@ -328,7 +347,8 @@ impl Scene for EliminationGameScene {
             }
             GameState::Round => {
                 self.round_stopwatch.update(delta_time);
-                self.shoot_timer.update(delta_time);
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
                 self.delta_shoot_time.update(delta_time);
 
                 update_target(
@@ -371,7 +391,8 @@ impl Scene for EliminationGameScene {
             }
             GameState::Finishing(ref mut timer) => {
                 renderer.game_renderer.render_crosshair = false;
-                self.shoot_timer.update(delta_time);
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
                 timer.update(delta_time);
 
                 Text::new("Finished!")
@@ -406,7 +427,12 @@ impl Scene for EliminationGameScene {
             );
 
             if shoot_trigger {
-                self.shoot(input_manager, audio_context, &renderer.camera, delta_time);
+                self.shoot(
+                    input_manager,
+                    audio_context,
+                    &mut renderer.camera,
+                    delta_time,
+                );
             }
         }
 
@@ -428,6 +454,7 @@ impl Scene for EliminationGameScene {
                             / self.score.hit.max(1) as f32,
                         running_time: self.round_stopwatch.get_duration(),
                         created_at: Utc::now().naive_utc(),
+                        background_type: self.background_type,
                     }),
                     self.difficulty,
                 )),
@@ -435,7 +462,7 @@ impl Scene for EliminationGameScene {
             );
         }
 
-        if input_manager.is_keyboard_press(&VirtualKeyCode::Escape) {
+        if input_manager.is_action_press(Action::Pause) {
             scene_op = SceneOp::Push(Box::new(PauseScene::new(renderer, conrod_handle)), None);
         }
 
@@ -468,6 +495,8 @@ impl Scene for EliminationGameScene {
         _database: &mut Database,
     ) {
         renderer.rendering_info.fov_shootanim.y = 0.0;
+        renderer.camera.reset_zoom();
+        renderer.camera.effects.clear();
         renderer.render_objects.clear();
         renderer.game_renderer.render_crosshair = false;
         window.set_is_cursor_grabbed(false);
@@ -557,12 +586,11 @@ impl EliminationGameScene {
         &mut self,
         input_manager: &InputManager,
         audio_context: &mut AudioContext,
-        camera: &Camera,
+        camera: &mut Camera,
         _delta_time: f32,
     ) {
-        if input_manager.is_mouse_press(&MouseButton::Left) && self.shoot_timer.is_finished() {
+        if let Some(direction) = self.weapon.fire(input_manager, camera) {
             self.shoot_animation.trigger();
-            self.shoot_timer.reset(0.4);
 
             #[cfg(not(target_arch = "wasm32"))]
             {
@@ -573,13 +601,14 @@ impl EliminationGameScene {
                 );
                 audio_context.push(Sink::Regular(sink));
             }
-            if let Some((handle, _distance)) = shoot_ray(&self.physics, camera) {
+            if let Some((handle, _distance)) = shoot_ray(&self.physics, camera, direction) {
                 let collider = self.physics.collider_set.get(handle).unwrap();
                 let entity = Entity::from_bits(collider.user_data as u64).unwrap();
 
                 if let Ok(mut target) = self.world.get_mut::<SphereTarget>(entity) {
                     if target.try_shoot(audio_context) {
-                        let shoot_time = self.delta_shoot_time.get_duration();
+                        let shoot_time = self.delta_shoot_time.get_duration()
+                            - self.weapon.get_last_shot_delay();
                         self.delta_shoot_time.reset();
 
                         self.score.hit += 1;
@@ -2,16 +2,18 @@ use std::collections::HashMap;
 
 use chrono::NaiveDateTime;
 use conrod_core::widget::envelope_editor::EnvelopePoint;
-use conrod_core::widget::{Button, Canvas, Text};
-use conrod_core::{Labelable, Positionable, Sizeable, Widget};
+use conrod_core::widget::{Button, Canvas, Circle, PointPath, Text};
+use conrod_core::{color, Color, Colorable, Labelable, Positionable, Sizeable, Widget};
 
 use gluesql::prelude::Value;
+use nalgebra::Vector2;
 use winit::event_loop::ControlFlow;
 
 use crate::audio::AudioContext;
 use crate::database::Database;
 use crate::gui::ConrodHandle;
 use crate::input_manager::InputManager;
+use crate::renderer::rendering_info::BackgroundType;
 use crate::renderer::Renderer;
 
 use crate::scene::{
@@ -34,6 +36,7 @@ pub struct ClassicGameScoreDisplay {
     pub score: i32,
     pub avg_hit_time: f32,
     pub created_at: NaiveDateTime,
+    pub background_type: BackgroundType,
 }
 
 impl ClassicGameScoreDisplay {
@@ -45,6 +48,7 @@ impl ClassicGameScoreDisplay {
             score: 0,
             avg_hit_time: 0.0,
             created_at: Utc::now().naive_utc(),
+            background_type: BackgroundType::Forest,
         }
     }
 }
@@ -58,6 +62,7 @@ pub struct EliminationGameScoreDisplay {
     pub avg_hit_time: f32,
     pub running_time: f32,
     pub created_at: NaiveDateTime,
+    pub background_type: BackgroundType,
 }
 
 impl EliminationGameScoreDisplay {
@@ -71,6 +76,7 @@ impl EliminationGameScoreDisplay {
             hit_fake_target: 0,
             running_time: 0.0,
             created_at: Utc::now().naive_utc(),
+            background_type: BackgroundType::Forest,
         }
     }
 }
@@ -82,7 +88,10 @@ pub struct HitAndDodgeGameScoreDisplay {
     pub score: i32,
     pub hit_taken: u16,
     pub avg_hit_time: f32,
+    pub headshot_rate: f32,
     pub created_at: NaiveDateTime,
+    pub background_type: BackgroundType,
+    pub near_miss: u16,
 }
 
 impl HitAndDodgeGameScoreDisplay {
@@ -94,7 +103,146 @@ impl HitAndDodgeGameScoreDisplay {
             score: 0,
             avg_hit_time: 0.0,
             hit_taken: 0,
+            headshot_rate: 0.0,
             created_at: Utc::now().naive_utc(),
+            background_type: BackgroundType::Forest,
+            near_miss: 0,
+        }
+    }
+}
+
+pub struct GridshotGameScoreDisplay {
+    pub accuracy: f32,
+    pub hit: u16,
+    pub miss: u16,
+    pub score: i32,
+    pub targets_per_second: f32,
+    pub avg_inter_target_angle: f32,
+    pub created_at: NaiveDateTime,
+    pub background_type: BackgroundType,
+}
+
+impl GridshotGameScoreDisplay {
+    pub fn new() -> Self {
+        Self {
+            accuracy: 0.0,
+            hit: 0,
+            miss: 0,
+            score: 0,
+            targets_per_second: 0.0,
+            avg_inter_target_angle: 0.0,
+            created_at: Utc::now().naive_utc(),
+            background_type: BackgroundType::Forest,
+        }
+    }
+}
+
+pub struct ShrinkingGameScoreDisplay {
+    pub accuracy: f32,
+    pub hit: u16,
+    pub miss: u16,
+    pub score: i32,
+    pub avg_hit_radius: f32,
+    pub created_at: NaiveDateTime,
+    pub background_type: BackgroundType,
+}
+
+impl ShrinkingGameScoreDisplay {
+    pub fn new() -> Self {
+        Self {
+            accuracy: 0.0,
+            hit: 0,
+            miss: 0,
+            score: 0,
+            avg_hit_radius: 0.0,
+            created_at: Utc::now().naive_utc(),
+            background_type: BackgroundType::Forest,
+        }
+    }
+}
+
+pub struct SurvivalGameScoreDisplay {
+    pub accuracy: f32,
+    pub hit: u16,
+    pub miss: u16,
+    pub waves_cleared: u16,
+    pub time_survived: f32,
+    pub created_at: NaiveDateTime,
+    pub background_type: BackgroundType,
+}
+
+impl SurvivalGameScoreDisplay {
+    pub fn new() -> Self {
+        Self {
+            accuracy: 0.0,
+            hit: 0,
+            miss: 0,
+            waves_cleared: 0,
+            time_survived: 0.0,
+            created_at: Utc::now().naive_utc(),
+            background_type: BackgroundType::Forest,
+        }
+    }
+}
+
+/// One shot of a spray, as (right, up) offsets in degrees.
+#[derive(Debug, Clone, Copy)]
+pub struct SprayShot {
+    /// Pull-down needed to cancel the recoil of the shots before this one.
+    pub ideal: Vector2<f32>,
+    /// Pull-down the player actually did since the first shot of the spray.
+    pub actual: Vector2<f32>,
+    /// Where the shot landed on the wall, relative to the center of the target.
+    pub impact: Option<Vector2<f32>>,
+}
+
+pub struct SprayControlGameScoreDisplay {
+    pub accuracy: f32,
+    pub hit: u16,
+    pub miss: u16,
+    pub avg_compensation_error: f32,
+    pub created_at: NaiveDateTime,
+    pub background_type: BackgroundType,
+    // Only shown right after the run, not stored in the database.
+    pub target_radius: f32,
+    pub last_spray: Vec<SprayShot>,
+}
+
+impl SprayControlGameScoreDisplay {
+    pub fn new() -> Self {
+        Self {
+            accuracy: 0.0,
+            hit: 0,
+            miss: 0,
+            avg_compensation_error: 0.0,
+            created_at: Utc::now().naive_utc(),
+            background_type: BackgroundType::Forest,
+            target_radius: 0.0,
+            last_spray: Vec::new(),
+        }
+    }
+}
+
+pub struct LeadGameScoreDisplay {
+    pub accuracy: f32,
+    pub hit: u16,
+    pub miss: u16,
+    pub score: i32,
+    pub avg_hit_distance: f32,
+    pub created_at: NaiveDateTime,
+    pub background_type: BackgroundType,
+}
+
+impl LeadGameScoreDisplay {
+    pub fn new() -> Self {
+        Self {
+            accuracy: 0.0,
+            hit: 0,
+            miss: 0,
+            score: 0,
+            avg_hit_distance: 0.0,
+            created_at: Utc::now().naive_utc(),
+            background_type: BackgroundType::Forest,
         }
     }
 }
@@ -103,6 +251,11 @@ pub enum GameModeScore {
     Classic(ClassicGameScoreDisplay),
     Elimination(EliminationGameScoreDisplay),
     HitAndDodge(HitAndDodgeGameScoreDisplay),
+    Gridshot(GridshotGameScoreDisplay),
+    Shrinking(ShrinkingGameScoreDisplay),
+    Survival(SurvivalGameScoreDisplay),
+    SprayControl(SprayControlGameScoreDisplay),
+    Lead(LeadGameScoreDisplay),
 }
 
 pub struct GameScoreScene {
@@ -143,6 +296,14 @@ widget_ids! {
         hit_taken_label,
         hit_taken_value_label,
 
+        headshot_rate_canvas,
+        headshot_rate_label,
+        headshot_rate_value_label,
+
+        near_miss_canvas,
+        near_miss_label,
+        near_miss_value_label,
+
         hit_fake_target_canvas,
         hit_fake_target_label,
         hit_fake_target_value_label,
@@ -150,6 +311,41 @@ widget_ids! {
         running_time_canvas,
         running_time_label,
         running_time_value_label,
+
+        targets_per_second_canvas,
+        targets_per_second_label,
+        targets_per_second_value_label,
+
+        avg_inter_target_angle_canvas,
+        avg_inter_target_angle_label,
+        avg_inter_target_angle_value_label,
+
+        avg_hit_radius_canvas,
+        avg_hit_radius_label,
+        avg_hit_radius_value_label,
+
+        waves_cleared_canvas,
+        waves_cleared_label,
+        waves_cleared_value_label,
+
+        time_survived_canvas,
+        time_survived_label,
+        time_survived_value_label,
+
+        avg_compensation_error_canvas,
+        avg_compensation_error_label,
+        avg_compensation_error_value_label,
+
+        avg_hit_distance_canvas,
+        avg_hit_distance_label,
+        avg_hit_distance_value_label,
+
+        spray_canvas,
+        spray_title_label,
+        spray_target,
+        spray_ideal_path,
+        spray_actual_path,
+        spray_impacts[],
     }
 }
 
@@ -191,14 +387,16 @@ impl Scene for GameScoreScene {
                 {},\
                 {},\
                 {},\
-                \"{}\")",
+                \"{}\",\
+                {})",
                     self.difficulty as u8,
                     score.accuracy,
                     score.hit,
                     score.miss,
                     score.score,
                     score.avg_hit_time,
-                    score.created_at
+                    score.created_at,
+                    score.background_type as u8
                 )
             }
             GameModeScore::Elimination(score) => {
@@ -212,7 +410,8 @@ impl Scene for GameScoreScene {
                 {},\
                 {},\
                 {},\
-                \"{}\")",
+                \"{}\",\
+                {})",
                     self.difficulty as u8,
                     score.accuracy,
                     score.hit,
@@ -221,7 +420,8 @@ impl Scene for GameScoreScene {
                     score.avg_hit_time,
                     score.hit_fake_target,
                     score.running_time,
-                    Utc::now().naive_utc()
+                    Utc::now().naive_utc(),
+                    score.background_type as u8
                 )
             }
             GameModeScore::HitAndDodge(score) => {
@@ -234,7 +434,10 @@ impl Scene for GameScoreScene {
                 {},\
                 {},\
                 {},\
-                \"{}\")",
+                \"{}\",\
+                {},\
+                {},\
+                {})",
                     self.difficulty as u8,
                     score.accuracy,
                     score.hit,
@@ -242,7 +445,115 @@ impl Scene for GameScoreScene {
                     score.score,
                     score.avg_hit_time,
                     score.hit_taken,
-                    Utc::now().naive_utc()
+                    Utc::now().naive_utc(),
+                    score.headshot_rate,
+                    score.background_type as u8,
+                    score.near_miss
+                )
+            }
+            GameModeScore::Gridshot(score) => {
+                format!(
+                    "INSERT INTO gridshot_game_score VALUES (\
+                {},\
+                {},\
+                {},\
+                {},\
+                {},\
+                {},\
+                {},\
+                \"{}\",\
+                {})",
+                    self.difficulty as u8,
+                    score.accuracy,
+                    score.hit,
+                    score.miss,
+                    score.score,
+                    score.targets_per_second,
+                    score.avg_inter_target_angle,
+                    Utc::now().naive_utc(),
+                    score.background_type as u8
+                )
+            }
+            GameModeScore::Shrinking(score) => {
+                format!(
+                    "INSERT INTO shrinking_game_score VALUES (\
+                {},\
+                {},\
+                {},\
+                {},\
+                {},\
+                {},\
+                \"{}\",\
+                {})",
+                    self.difficulty as u8,
+                    score.accuracy,
+                    score.hit,
+                    score.miss,
+                    score.score,
+                    score.avg_hit_radius,
+                    Utc::now().naive_utc(),
+                    score.background_type as u8
+                )
+            }
+            GameModeScore::Survival(score) => {
+                format!(
+                    "INSERT INTO survival_game_score VALUES (\
+                {},\
+                {},\
+                {},\
+                {},\
+                {},\
+                {},\
+                \"{}\",\
+                {})",
+                    self.difficulty as u8,
+                    score.accuracy,
+                    score.hit,
+                    score.miss,
+                    score.waves_cleared,
+                    score.time_survived,
+                    Utc::now().naive_utc(),
+                    score.background_type as u8
+                )
+            }
+            GameModeScore::SprayControl(score) => {
+                format!(
+                    "INSERT INTO spray_control_game_score VALUES (\
+                {},\
+                {},\
+                {},\
+                {},\
+                {},\
+                \"{}\",\
+                {})",
+                    self.difficulty as u8,
+                    score.accuracy,
+                    score.hit,
+                    score.miss,
+                    score.avg_compensation_error,
+                    Utc::now().naive_utc(),
+                    score.background_type as u8
+                )
+            }
+            GameModeScore::Lead(score) => {
+                format!(
+                    "INSERT INTO lead_game_score VALUES (\
+                {},\
+                {},\
+                {},\
+                {},\
+                {},\
+                {},\
+                \"{}\",\
+                {})",
+                    self.difficulty as u8,
+                    score.accuracy,
+                    score.hit,
+                    score.miss,
+                    score.score,
+                    score.avg_hit_distance,
+                    Utc::now().naive_utc(),
+                    score.background_type as u8
                 )
             }
         };
@@ -253,7 +564,7 @@ impl Scene for GameScoreScene {
         &mut self,
         _window: &mut Window,
         _renderer: &mut Renderer,
-        input_manager: &InputManager,
+        input_manager: &mut InputManager,
         _delta_time: f32,
         conrod_handle: &mut ConrodHandle,
         _audio_context: &mut AudioContext,
@@ -469,6 +780,406 @@ impl Scene for GameScoreScene {
                             &self.ids.hit_taken_value_label,
                             &format!("{}", score.hit_taken),
                         ),
+                        (
+                            &self.ids.headshot_rate_canvas,
+                            &self.ids.headshot_rate_label,
+                            "Headshot rate",
+                            &self.ids.headshot_rate_value_label,
+                            &format!("{:.2}%", score.headshot_rate),
+                        ),
+                        (
+                            &self.ids.near_miss_canvas,
+                            &self.ids.near_miss_label,
+                            "Near miss",
+                            &self.ids.near_miss_value_label,
+                            &format!("{}", score.near_miss),
+                        ),
+                    ];
+
+                    for (i, (id_canvas, _id_label, _label_text, _id_value_label, _value_text)) in
+                        l.iter().enumerate()
+                    {
+                        Canvas::new()
+                            .down_from(prev_canvas_id, if i == 0 { 50.0 } else { GAP_BETWEEN_ITEM })
+                            .align_middle_x()
+                            .w(ITEM_WIDTH)
+                            .set(**id_canvas, &mut ui_cell);
+
+                        prev_canvas_id = **id_canvas;
+                    }
+                    for (id_canvas, id_label, label_text, id_value_label, value_text) in l.iter() {
+                        Text::new(label_text)
+                            .mid_left_of(**id_canvas)
+                            .left_justify()
+                            .set(**id_label, &mut ui_cell);
+
+                        Text::new(value_text)
+                            .mid_right_of(**id_canvas)
+                            .left_justify()
+                            .set(**id_value_label, &mut ui_cell);
+                    }
+                }
+                GameModeScore::Gridshot(score) => {
+                    let mut prev_canvas_id = self.ids.title_label;
+
+                    let l = [
+                        (
+                            &self.ids.hit_canvas,
+                            &self.ids.hit_label,
+                            "Hit",
+                            &self.ids.hit_value_label,
+                            &format!("{}", score.hit),
+                        ),
+                        (
+                            &self.ids.score_canvas,
+                            &self.ids.score_label,
+                            "Score",
+                            &self.ids.score_value_label,
+                            &format!("{}", score.score),
+                        ),
+                        (
+                            &self.ids.targets_per_second_canvas,
+                            &self.ids.targets_per_second_label,
+                            "Targets per second",
+                            &self.ids.targets_per_second_value_label,
+                            &format!("{:.2}", score.targets_per_second),
+                        ),
+                        (
+                            &self.ids.avg_inter_target_angle_canvas,
+                            &self.ids.avg_inter_target_angle_label,
+                            "Avg angle between targets",
+                            &self.ids.avg_inter_target_angle_value_label,
+                            &format!("{:.1}°", score.avg_inter_target_angle),
+                        ),
+                        (
+                            &self.ids.miss_canvas,
+                            &self.ids.miss_label,
+                            "Miss",
+                            &self.ids.miss_value_label,
+                            &format!("{}", score.miss),
+                        ),
+                        (
+                            &self.ids.accuracy_canvas,
+                            &self.ids.accuracy_label,
+                            "Accuracy",
+                            &self.ids.accuracy_value_label,
+                            &format!("{:.2}%", score.accuracy),
+                        ),
+                    ];
+
+                    for (i, (id_canvas, _id_label, _label_text, _id_value_label, _value_text)) in
+                        l.iter().enumerate()
+                    {
+                        Canvas::new()
+                            .down_from(prev_canvas_id, if i == 0 { 50.0 } else { GAP_BETWEEN_ITEM })
+                            .align_middle_x()
+                            .w(ITEM_WIDTH)
+                            .set(**id_canvas, &mut ui_cell);
+
+                        prev_canvas_id = **id_canvas;
+                    }
+                    for (id_canvas, id_label, label_text, id_value_label, value_text) in l.iter() {
+                        Text::new(label_text)
+                            .mid_left_of(**id_canvas)
+                            .left_justify()
+                            .set(**id_label, &mut ui_cell);
+
+                        Text::new(value_text)
+                            .mid_right_of(**id_canvas)
+                            .left_justify()
+                            .set(**id_value_label, &mut ui_cell);
+                    }
+                }
+                GameModeScore::Shrinking(score) => {
+                    let mut prev_canvas_id = self.ids.title_label;
+
+                    let l = [
+                        (
+                            &self.ids.hit_canvas,
+                            &self.ids.hit_label,
+                            "Hit",
+                            &self.ids.hit_value_label,
+                            &format!("{}", score.hit),
+                        ),
+                        (
+                            &self.ids.score_canvas,
+                            &self.ids.score_label,
+                            "Score",
+                            &self.ids.score_value_label,
+                            &format!("{}", score.score),
+                        ),
+                        (
+                            &self.ids.avg_hit_radius_canvas,
+                            &self.ids.avg_hit_radius_label,
+                            "Avg target radius on hit",
+                            &self.ids.avg_hit_radius_value_label,
+                            &format!("{:.2}m", score.avg_hit_radius),
+                        ),
+                        (
+                            &self.ids.miss_canvas,
+                            &self.ids.miss_label,
+                            "Miss",
+                            &self.ids.miss_value_label,
+                            &format!("{}", score.miss),
+                        ),
+                        (
+                            &self.ids.accuracy_canvas,
+                            &self.ids.accuracy_label,
+                            "Accuracy",
+                            &self.ids.accuracy_value_label,
+                            &format!("{:.2}%", score.accuracy),
+                        ),
+                    ];
+
+                    for (i, (id_canvas, _id_label, _label_text, _id_value_label, _value_text)) in
+                        l.iter().enumerate()
+                    {
+                        Canvas::new()
+                            .down_from(prev_canvas_id, if i == 0 { 50.0 } else { GAP_BETWEEN_ITEM })
+                            .align_middle_x()
+                            .w(ITEM_WIDTH)
+                            .set(**id_canvas, &mut ui_cell);
+
+                        prev_canvas_id = **id_canvas;
+                    }
+                    for (id_canvas, id_label, label_text, id_value_label, value_text) in l.iter() {
+                        Text::new(label_text)
+                            .mid_left_of(**id_canvas)
+                            .left_justify()
+                            .set(**id_label, &mut ui_cell);
+
+                        Text::new(value_text)
+                            .mid_right_of(**id_canvas)
+                            .left_justify()
+                            .set(**id_value_label, &mut ui_cell);
+                    }
+                }
+                GameModeScore::Survival(score) => {
+                    let mut prev_canvas_id = self.ids.title_label;
+
+                    let l = [
+                        (
+                            &self.ids.waves_cleared_canvas,
+                            &self.ids.waves_cleared_label,
+                            "Waves cleared",
+                            &self.ids.waves_cleared_value_label,
+                            &format!("{}", score.waves_cleared),
+                        ),
+                        (
+                            &self.ids.time_survived_canvas,
+                            &self.ids.time_survived_label,
+                            "Time survived",
+                            &self.ids.time_survived_value_label,
+                            &format!(
+                                "{:02}:{:02}",
+                                (score.time_survived / 60.0) as i32,
+                                (score.time_survived % 60.0) as i32
+                            ),
+                        ),
+                        (
+                            &self.ids.hit_canvas,
+                            &self.ids.hit_label,
+                            "Hit",
+                            &self.ids.hit_value_label,
+                            &format!("{}", score.hit),
+                        ),
+                        (
+                            &self.ids.miss_canvas,
+                            &self.ids.miss_label,
+                            "Miss",
+                            &self.ids.miss_value_label,
+                            &format!("{}", score.miss),
+                        ),
+                        (
+                            &self.ids.accuracy_canvas,
+                            &self.ids.accuracy_label,
+                            "Accuracy",
+                            &self.ids.accuracy_value_label,
+                            &format!("{:.2}%", score.accuracy),
+                        ),
+                    ];
+
+                    for (i, (id_canvas, _id_label, _label_text, _id_value_label, _value_text)) in
+                        l.iter().enumerate()
+                    {
+                        Canvas::new()
+                            .down_from(prev_canvas_id, if i == 0 { 50.0 } else { GAP_BETWEEN_ITEM })
+                            .align_middle_x()
+                            .w(ITEM_WIDTH)
+                            .set(**id_canvas, &mut ui_cell);
+
+                        prev_canvas_id = **id_canvas;
+                    }
+                    for (id_canvas, id_label, label_text, id_value_label, value_text) in l.iter() {
+                        Text::new(label_text)
+                            .mid_left_of(**id_canvas)
+                            .left_justify()
+                            .set(**id_label, &mut ui_cell);
+
+                        Text::new(value_text)
+                            .mid_right_of(**id_canvas)
+                            .left_justify()
+                            .set(**id_value_label, &mut ui_cell);
+                    }
+                }
+                GameModeScore::SprayControl(score) => {
+                    let mut prev_canvas_id = self.ids.title_label;
+
+                    let l = [
+                        (
+                            &self.ids.hit_canvas,
+                            &self.ids.hit_label,
+                            "Hit",
+                            &self.ids.hit_value_label,
+                            &format!("{}", score.hit),
+                        ),
+                        (
+                            &self.ids.avg_compensation_error_canvas,
+                            &self.ids.avg_compensation_error_label,
+                            "Avg compensation error",
+                            &self.ids.avg_compensation_error_value_label,
+                            &format!("{:.2}°", score.avg_compensation_error),
+                        ),
+                        (
+                            &self.ids.miss_canvas,
+                            &self.ids.miss_label,
+                            "Miss",
+                            &self.ids.miss_value_label,
+                            &format!("{}", score.miss),
+                        ),
+                        (
+                            &self.ids.accuracy_canvas,
+                            &self.ids.accuracy_label,
+                            "Accuracy",
+                            &self.ids.accuracy_value_label,
+                            &format!("{:.2}%", score.accuracy),
+                        ),
+                    ];
+
+                    for (i, (id_canvas, _id_label, _label_text, _id_value_label, _value_text)) in
+                        l.iter().enumerate()
+                    {
+                        Canvas::new()
+                            .down_from(prev_canvas_id, if i == 0 { 50.0 } else { GAP_BETWEEN_ITEM })
+                            .align_middle_x()
+                            .w(ITEM_WIDTH)
+                            .set(**id_canvas, &mut ui_cell);
+
+                        prev_canvas_id = **id_canvas;
+                    }
+                    for (id_canvas, id_label, label_text, id_value_label, value_text) in l.iter() {
+                        Text::new(label_text)
+                            .mid_left_of(**id_canvas)
+                            .left_justify()
+                            .set(**id_label, &mut ui_cell);
+
+                        Text::new(value_text)
+                            .mid_right_of(**id_canvas)
+                            .left_justify()
+                            .set(**id_value_label, &mut ui_cell);
+                    }
+
+                    // Last spray: the pull-down that cancels the recoil against the one the
+                    // player did, both starting from the center, with the impacts around the
+                    // target.
+                    const SPRAY_CANVAS_SIZE: f64 = 320.0;
+                    const PIXELS_PER_DEGREE: f64 = 15.0;
+
+                    Canvas::new()
+                        .color(Color::Rgba(0.0, 0.0, 0.0, 0.3))
+                        .mid_right_with_margin_on(self.ids.canvas, MARGIN)
+                        .wh(conrod_core::Dimensions::new(
+                            SPRAY_CANVAS_SIZE,
+                            SPRAY_CANVAS_SIZE,
+                        ))
+                        .set(self.ids.spray_canvas, &mut ui_cell);
+
+                    Text::new("Last spray")
+                        .font_size(14)
+                        .mid_top_with_margin_on(self.ids.spray_canvas, GAP_BETWEEN_ITEM)
+                        .set(self.ids.spray_title_label, &mut ui_cell);
+
+                    let [center_x, center_y] = ui_cell.xy_of(self.ids.spray_canvas).unwrap();
+                    let to_point = |v: &Vector2<f32>| {
+                        [
+                            center_x + v.x as f64 * PIXELS_PER_DEGREE,
+                            center_y + v.y as f64 * PIXELS_PER_DEGREE,
+                        ]
+                    };
+
+                    Circle::outline(score.target_radius as f64 * PIXELS_PER_DEGREE)
+                        .x_y(center_x, center_y)
+                        .color(color::YELLOW)
+                        .set(self.ids.spray_target, &mut ui_cell);
+
+                    let ideal_points = std::iter::once([center_x, center_y])
+                        .chain(score.last_spray.iter().map(|shot| to_point(&shot.ideal)))
+                        .collect::<Vec<_>>();
+                    PointPath::abs(ideal_points)
+                        .color(color::GREEN)
+                        .thickness(2.0)
+                        .set(self.ids.spray_ideal_path, &mut ui_cell);
+
+                    let actual_points = std::iter::once([center_x, center_y])
+                        .chain(score.last_spray.iter().map(|shot| to_point(&shot.actual)))
+                        .collect::<Vec<_>>();
+                    PointPath::abs(actual_points)
+                        .color(color::RED)
+                        .thickness(2.0)
+                        .set(self.ids.spray_actual_path, &mut ui_cell);
+
+                    self.ids
+                        .spray_impacts
+                        .resize(score.last_spray.len(), &mut ui_cell.widget_id_generator());
+                    for (id, shot) in self.ids.spray_impacts.iter().zip(score.last_spray.iter()) {
+                        if let Some(ref impact) = shot.impact {
+                            let [x, y] = to_point(impact);
+                            Circle::fill(3.0)
+                                .x_y(x, y)
+                                .color(color::WHITE)
+                                .set(*id, &mut ui_cell);
+                        }
+                    }
+                }
+                GameModeScore::Lead(score) => {
+                    let mut prev_canvas_id = self.ids.title_label;
+
+                    let l = [
+                        (
+                            &self.ids.hit_canvas,
+                            &self.ids.hit_label,
+                            "Hit",
+                            &self.ids.hit_value_label,
+                            &format!("{}", score.hit),
+                        ),
+                        (
+                            &self.ids.score_canvas,
+                            &self.ids.score_label,
+                            "Score",
+                            &self.ids.score_value_label,
+                            &format!("{}", score.score),
+                        ),
+                        (
+                            &self.ids.avg_hit_distance_canvas,
+                            &self.ids.avg_hit_distance_label,
+                            "Avg hit distance",
+                            &self.ids.avg_hit_distance_value_label,
+                            &format!("{:.1}m", score.avg_hit_distance),
+                        ),
+                        (
+                            &self.ids.miss_canvas,
+                            &self.ids.miss_label,
+                            "Miss",
+                            &self.ids.miss_value_label,
+                            &format!("{}", score.miss),
+                        ),
+                        (
+                            &self.ids.accuracy_canvas,
+                            &self.ids.accuracy_label,
+                            "Accuracy",
+                            &self.ids.accuracy_value_label,
+                            &format!("{:.2}%", score.accuracy),
+                        ),
                     ];
 
                     for (i, (id_canvas, _id_label, _label_text, _id_value_label, _value_text)) in
@@ -520,6 +1231,11 @@ impl Scene for GameScoreScene {
                             GameModeScore::Classic(_) => 0,
                             GameModeScore::Elimination(_) => 1,
                             GameModeScore::HitAndDodge(_) => 2,
+                            GameModeScore::Gridshot(_) => 3,
+                            GameModeScore::Shrinking(_) => 4,
+                            GameModeScore::Survival(_) => 5,
+                            GameModeScore::SprayControl(_) => 6,
+                            GameModeScore::Lead(_) => 7,
                         } as i64),
                     );
                     m.insert("difficulty", Value::I64(self.difficulty as i64));
@@ -1,6 +1,6 @@
 use conrod_core::widget::envelope_editor::EnvelopePoint;
 
-use conrod_core::widget::{Button, Canvas, Text};
+use conrod_core::widget::{Button, Canvas, DropDownList, Text};
 use conrod_core::{Colorable, Labelable, Positionable, Sizeable, Widget};
 use std::collections::HashMap;
 
@@ -10,14 +10,21 @@ use crate::audio::AudioContext;
 use crate::database::Database;
 use crate::gui::ConrodHandle;
 use crate::input_manager::InputManager;
+use crate::renderer::rendering_info::{ENVIRONMENTS, ENVIRONMENT_NAMES};
 use crate::renderer::Renderer;
 use crate::scene::classic_game_scene::ClassicGameScene;
 use crate::scene::elimination_game_scene::EliminationGameScene;
+use crate::scene::gridshot_game_scene::GridshotGameScene;
 use crate::scene::hit_and_dodge_scene::HitAndDodgeGameScene;
+use crate::scene::lead_game_scene::LeadGameScene;
+use crate::scene::shrinking_game_scene::ShrinkingGameScene;
+use crate::scene::spray_control_game_scene::SprayControlGameScene;
+use crate::scene::survival_game_scene::SurvivalGameScene;
 
 use crate::scene::{
     GameDifficulty, MaybeMessage, Scene, SceneOp, Value, BUTTON_HEIGHT, BUTTON_WIDTH, MARGIN,
 };
+use crate::weapon::{WeaponType, WEAPON_NAMES};
 use crate::window::Window;
 use conrod_core::widget_ids;
 
@@ -55,6 +62,8 @@ widget_ids! {
         play_button_2,
         play_button_3_canvas,
         play_button_3,
+        weapon_selection,
+        environment_selection,
         score_button
     }
 }
@@ -64,9 +73,26 @@ struct GameModeDetails {
     description: &'static str,
 }
 
+// Weapon picked for each game mode until the player chooses another one.
+const DEFAULT_WEAPONS: [usize; 8] = [
+    WeaponType::Pistol as usize,
+    WeaponType::Pistol as usize,
+    WeaponType::Pistol as usize,
+    WeaponType::Pistol as usize,
+    WeaponType::Pistol as usize,
+    WeaponType::Rifle as usize,
+    WeaponType::Rifle as usize,
+    WeaponType::Pistol as usize,
+];
+
+// Index in `ENVIRONMENTS` picked for each game mode until the player chooses another one.
+const DEFAULT_ENVIRONMENTS: [usize; 8] = [0, 1, 0, 0, 0, 0, 0, 0];
+
 pub struct GameSelectionScene {
     ids: GameSelectionSceneIds,
     selected_game_mode_idx: usize,
+    weapon_selections: [usize; 8],
+    environment_selections: [usize; 8],
 }
 
 impl GameSelectionScene {
@@ -74,6 +100,8 @@ impl GameSelectionScene {
         Self {
             ids: GameSelectionSceneIds::new(conrod_handle.get_ui_mut().widget_id_generator()),
             selected_game_mode_idx: 0,
+            weapon_selections: DEFAULT_WEAPONS,
+            environment_selections: DEFAULT_ENVIRONMENTS,
         }
     }
 }
@@ -96,7 +124,7 @@ impl Scene for GameSelectionScene {
         &mut self,
         _window: &mut Window,
         renderer: &mut Renderer,
-        input_manager: &InputManager,
+        input_manager: &mut InputManager,
         _delta_time: f32,
         conrod_handle: &mut ConrodHandle,
         _audio_context: &mut AudioContext,
@@ -123,6 +151,26 @@ impl Scene for GameSelectionScene {
             GameModeDetails {
                 title: "Hit and Dodge",
                 description: "You have to shoot the gunman while avoiding the bullet and swordman attack.",
+            },
+            GameModeDetails {
+                title: "Gridshot",
+                description: "Several targets are live at once in a grid in front of you. Every destroyed target reappears in a slot away from the last one.",
+            },
+            GameModeDetails {
+                title: "Shrinking Target",
+                description: "Every target shrinks until it disappears. The smaller the target when you hit it, the higher the score.",
+            },
+            GameModeDetails {
+                title: "Survival",
+                description: "Waves of gunmen and swordmen come from the edges of the arena, each wave bigger and faster than the last. Survive as long as you can.",
+            },
+            GameModeDetails {
+                title: "Spray Control",
+                description: "Hold fire at a target on the wall and pull against the recoil. The score screen compares your pull-down to the ideal one for the last spray.",
+            },
+            GameModeDetails {
+                title: "Lead the Target",
+                description: "Your shots take time to fly. Aim ahead of the fast moving targets so they run into them, and mind the drop on Hard.",
             }];
 
         {
@@ -233,6 +281,36 @@ impl Scene for GameSelectionScene {
                     BUTTON_HEIGHT,
                 ))
                 .set(self.ids.play_button_3, &mut ui_cell);
+
+            if let Some(new_idx) = DropDownList::new(
+                WEAPON_NAMES,
+                Some(self.weapon_selections[self.selected_game_mode_idx]),
+            )
+            .down_from(self.ids.play_button_3, GAP_BETWEEN_ITEM)
+            .wh(conrod_core::Dimensions::new(
+                BUTTON_WIDTH - MARGIN,
+                BUTTON_HEIGHT,
+            ))
+            .scrollbar_next_to()
+            .set(self.ids.weapon_selection, &mut ui_cell)
+            {
+                self.weapon_selections[self.selected_game_mode_idx] = new_idx;
+            }
+
+            if let Some(new_idx) = DropDownList::new(
+                ENVIRONMENT_NAMES,
+                Some(self.environment_selections[self.selected_game_mode_idx]),
+            )
+            .down_from(self.ids.weapon_selection, GAP_BETWEEN_ITEM)
+            .wh(conrod_core::Dimensions::new(
+                BUTTON_WIDTH - MARGIN,
+                BUTTON_HEIGHT,
+            ))
+            .scrollbar_next_to()
+            .set(self.ids.environment_selection, &mut ui_cell)
+            {
+                self.environment_selections[self.selected_game_mode_idx] = new_idx;
+            }
         }
 
         if play_1_button.was_clicked() {
@@ -253,9 +331,48 @@ impl Scene for GameSelectionScene {
                         conrod_handle,
                         GameDifficulty::Easy,
                     )),
+                    3 => Box::new(GridshotGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Easy,
+                    )),
+                    4 => Box::new(ShrinkingGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Easy,
+                    )),
+                    5 => Box::new(SurvivalGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Easy,
+                    )),
+                    6 => Box::new(SprayControlGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Easy,
+                    )),
+                    7 => Box::new(LeadGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Easy,
+                    )),
                     _ => unreachable!(),
                 },
-                None,
+                Some({
+                    let mut m = HashMap::new();
+                    m.insert(
+                        "weapon_type",
+                        Value::I64(self.weapon_selections[self.selected_game_mode_idx] as i64),
+                    );
+                    m.insert(
+                        "background_type",
+                        Value::I64(
+                            ENVIRONMENTS[self.environment_selections[self.selected_game_mode_idx]]
+                                as i64,
+                        ),
+                    );
+                    m
+                }),
             );
         }
 
@@ -277,9 +394,48 @@ impl Scene for GameSelectionScene {
                         conrod_handle,
                         GameDifficulty::Medium,
                     )),
+                    3 => Box::new(GridshotGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Medium,
+                    )),
+                    4 => Box::new(ShrinkingGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Medium,
+                    )),
+                    5 => Box::new(SurvivalGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Medium,
+                    )),
+                    6 => Box::new(SprayControlGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Medium,
+                    )),
+                    7 => Box::new(LeadGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Medium,
+                    )),
                     _ => unreachable!(),
                 },
-                None,
+                Some({
+                    let mut m = HashMap::new();
+                    m.insert(
+                        "weapon_type",
+                        Value::I64(self.weapon_selections[self.selected_game_mode_idx] as i64),
+                    );
+                    m.insert(
+                        "background_type",
+                        Value::I64(
+                            ENVIRONMENTS[self.environment_selections[self.selected_game_mode_idx]]
+                                as i64,
+                        ),
+                    );
+                    m
+                }),
             );
         }
 
@@ -301,9 +457,48 @@ impl Scene for GameSelectionScene {
                         conrod_handle,
                         GameDifficulty::Hard,
                     )),
+                    3 => Box::new(GridshotGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Hard,
+                    )),
+                    4 => Box::new(ShrinkingGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Hard,
+                    )),
+                    5 => Box::new(SurvivalGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Hard,
+                    )),
+                    6 => Box::new(SprayControlGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Hard,
+                    )),
+                    7 => Box::new(LeadGameScene::new(
+                        renderer,
+                        conrod_handle,
+                        GameDifficulty::Hard,
+                    )),
                     _ => unreachable!(),
                 },
-                None,
+                Some({
+                    let mut m = HashMap::new();
+                    m.insert(
+                        "weapon_type",
+                        Value::I64(self.weapon_selections[self.selected_game_mode_idx] as i64),
+                    );
+                    m.insert(
+                        "background_type",
+                        Value::I64(
+                            ENVIRONMENTS[self.environment_selections[self.selected_game_mode_idx]]
+                                as i64,
+                        ),
+                    );
+                    m
+                }),
             );
         }
 
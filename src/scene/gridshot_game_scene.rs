@@ -0,0 +1,609 @@
+use conrod_core::{color, Color, Colorable, Positionable, Sizeable, Widget};
+
+use chrono::Utc;
+use std::io::{BufReader, Cursor};
+
+use hecs::{Entity, World};
+
+use rapier3d::prelude::*;
+use winit::event_loop::ControlFlow;
+
+use crate::animation::InOutAnimation;
+use crate::audio::Sink;
+use crate::audio::{AudioContext, AUDIO_FILE_SHOOT};
+use crate::database::Database;
+use crate::entity::target::{Patrol, SphereTarget};
+use crate::entity::target_grid::{GridSlot, TargetGrid};
+
+use crate::gui::ConrodHandle;
+use crate::input_binding::Action;
+use crate::input_manager::InputManager;
+use crate::physics::GamePhysics;
+
+use crate::renderer::render_objects::{MaterialType, ShapeType};
+use crate::renderer::rendering_info::BackgroundType;
+use crate::renderer::Renderer;
+use crate::scene::game_score_scene::{GameModeScore, GameScoreScene, GridshotGameScoreDisplay};
+use crate::scene::pause_scene::PauseScene;
+use crate::scene::{
+    GameDifficulty, GameState, MaybeMessage, Scene, SceneOp, Value, FINISHING_DURATION,
+    IN_SHOOT_ANIM_DURATION, MARGIN, OUT_SHOOT_ANIM_DURATION, PREPARE_DURATION,
+};
+use crate::systems::player::{init_player, setup_player_collider};
+use crate::systems::target::{enqueue_target, update_target};
+use crate::systems::update_player_movement::update_player_position;
+use crate::systems::wall::{enqueue_wall, spawn_wall};
+use crate::timer::{Stopwatch, Timer};
+
+use crate::weapon::{Weapon, WeaponType};
+use crate::window::Window;
+use conrod_core::widget::envelope_editor::EnvelopePoint;
+use conrod_core::widget::{Canvas, Text};
+use conrod_core::widget_ids;
+
+use crate::camera::Camera;
+use crate::character_controller::CharacterController;
+use crate::systems::shoot_ray::shoot_ray;
+use crate::systems::shootanim::shootanim;
+use nalgebra::Vector3;
+use rand::prelude::SmallRng;
+use rand::SeedableRng;
+
+widget_ids! {
+    pub struct GridshotGameSceneIds {
+        // The main canvas
+        canvas,
+        start_duration_label,
+        ammo_label,
+
+        indicator_canvas,
+
+        duration_canvas,
+        duration_label,
+        score_canvas,
+        score_label,
+        accuracy_canvas,
+        accuracy_label,
+    }
+}
+
+pub struct Score {
+    pub hit: u16,
+    pub miss: u16,
+    pub score: i32,
+    pub total_inter_target_angle: f32,
+    pub inter_target_count: u16,
+}
+
+impl Score {
+    pub fn new() -> Self {
+        Self {
+            hit: 0,
+            miss: 0,
+            score: 0,
+            total_inter_target_angle: 0.0,
+            inter_target_count: 0,
+        }
+    }
+}
+
+pub const GAME_DURATION: f32 = 60.0;
+
+const GRID_COLUMNS: usize = 5;
+const GRID_ROWS: usize = 4;
+const GRID_SPACING: f32 = 1.6;
+
+pub struct GridshotGameScene {
+    ids: GridshotGameSceneIds,
+    world: World,
+    physics: GamePhysics,
+    player_rigid_body_handle: RigidBodyHandle,
+    player_controller: CharacterController,
+    game_state: GameState,
+    delta_shoot_time: Stopwatch,
+    weapon: Weapon,
+    background_type: BackgroundType,
+    score: Score,
+    rng: SmallRng,
+    round_timer: Timer,
+    shoot_animation: InOutAnimation,
+    target_grid: TargetGrid,
+    target_count: usize,
+    last_hit_direction: Option<Vector3<f32>>,
+    entity_to_remove: Vec<Entity>,
+    freeze: bool,
+    difficulty: GameDifficulty,
+}
+
+impl GridshotGameScene {
+    pub fn new(
+        _renderer: &mut Renderer,
+        conrod_handle: &mut ConrodHandle,
+        difficulty: GameDifficulty,
+    ) -> Self {
+        let mut world = World::new();
+        let mut physics = GamePhysics::new();
+
+        // Ground
+        physics
+            .collider_set
+            .insert(ColliderBuilder::new(SharedShape::cuboid(10.0, 1.0, 10.0)).build());
+
+        let player_rigid_body_handle =
+            setup_player_collider(&mut physics, Vector3::new(0.0, 2.6, 0.0));
+
+        spawn_wall(
+            &mut world,
+            &mut physics,
+            Vector3::new(0.0, 1.4, 9.5),
+            Vector3::new(10.0, 0.398, 0.5),
+        );
+        spawn_wall(
+            &mut world,
+            &mut physics,
+            Vector3::new(9.5, 1.4, 0.0),
+            Vector3::new(0.5, 0.398, 9.99),
+        );
+        spawn_wall(
+            &mut world,
+            &mut physics,
+            Vector3::new(-9.5, 1.4, 0.0),
+            Vector3::new(0.5, 0.398, 9.99),
+        );
+
+        let target_count = match difficulty {
+            GameDifficulty::Easy => 3,
+            GameDifficulty::Medium => 4,
+            GameDifficulty::Hard => 5,
+        };
+
+        let mut scene = Self {
+            world,
+            physics,
+            player_rigid_body_handle,
+            player_controller: CharacterController::new(),
+            ids: GridshotGameSceneIds::new(conrod_handle.get_ui_mut().widget_id_generator()),
+            score: Score::new(),
+            delta_shoot_time: Stopwatch::new(),
+            game_state: GameState::Preround,
+            weapon: Weapon::new(WeaponType::Pistol),
+            background_type: BackgroundType::Forest,
+            rng: SmallRng::from_entropy(),
+            shoot_animation: InOutAnimation::new(IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION),
+            target_grid: TargetGrid::new(
+                Vector3::new(0.0, 3.5, -12.0),
+                GRID_COLUMNS,
+                GRID_ROWS,
+                GRID_SPACING,
+            ),
+            target_count,
+            last_hit_direction: None,
+            entity_to_remove: Vec::new(),
+            round_timer: Timer::new(GAME_DURATION),
+            freeze: false,
+            difficulty,
+        };
+
+        for _ in 0..scene.target_count {
+            scene.spawn_in_free_slot(None);
+        }
+
+        scene
+    }
+}
+
+impl Scene for GridshotGameScene {
+    fn init(
+        &mut self,
+        message: MaybeMessage,
+        window: &mut Window,
+        renderer: &mut Renderer,
+        _conrod_handle: &mut ConrodHandle,
+        audio_context: &mut AudioContext,
+        _database: &mut Database,
+    ) {
+        renderer.is_render_gui = true;
+        renderer.is_render_game = true;
+
+        init_player(&mut self.physics, renderer, self.player_rigid_body_handle);
+
+        // Ground
+        let (objects, ref mut bound) = renderer.render_objects.next_static();
+        objects.position = nalgebra::Vector3::new(0.0, 0.0, 0.0);
+        objects.shape_type_material_ids.0 = ShapeType::Box;
+        objects.shape_type_material_ids.1 = MaterialType::CobblestonePaving;
+        objects.shape_data1 = nalgebra::Vector4::new(10.0, 1.0, 10.0, 0.0);
+        *bound = objects.get_bounding_sphere_radius();
+
+        // Back Wall
+        let (objects, ref mut bound) = renderer.render_objects.next_static();
+        objects.position = nalgebra::Vector3::new(0.0, 0.0, -14.0);
+        objects.shape_type_material_ids.0 = ShapeType::Box;
+        objects.shape_type_material_ids.1 = MaterialType::StoneWall;
+        objects.shape_data1 = nalgebra::Vector4::new(10.0, 8.0, 1.0, 0.0);
+        *bound = objects.get_bounding_sphere_radius();
+
+        window.set_is_cursor_grabbed(true);
+        audio_context.global_sinks_map.remove("bgm");
+
+        if let Some(m) = message {
+            if let Some(Value::I64(background_type)) = m.get("background_type") {
+                self.background_type = BackgroundType::from(*background_type as usize);
+            }
+            if let Some(Value::I64(weapon_type)) = m.get("weapon_type") {
+                self.weapon = Weapon::new(WeaponType::from(*weapon_type as usize));
+            }
+            if m.contains_key("from_pause") {
+                self.freeze = true;
+                renderer.game_renderer.render_crosshair = false;
+                self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION))
+            }
+        }
+
+        renderer.rendering_info.background_type = self.background_type;
+        renderer.rendering_info.weapon_type = self.weapon.get_type() as u32;
+    }
+
+    fn update(
+        &mut self,
+        _window: &mut Window,
+        renderer: &mut Renderer,
+        input_manager: &mut InputManager,
+        delta_time: f32,
+        conrod_handle: &mut ConrodHandle,
+        audio_context: &mut AudioContext,
+        _control_flow: &mut ControlFlow,
+        _database: &mut Database,
+    ) -> SceneOp {
+        let mut shoot_trigger = false;
+
+        let round_timer_sec = self.round_timer.get_duration();
+
+        let mut ui_cell = conrod_handle.get_ui_mut().set_widgets();
+        {
+            Canvas::new()
+                .color(Color::Rgba(0.0, 0.0, 0.0, 0.0))
+                .set(self.ids.canvas, &mut ui_cell);
+
+            Canvas::new()
+                .color(Color::Rgba(1.0, 1.0, 1.0, 0.3))
+                .mid_top_of(self.ids.canvas)
+                .flow_right(&[
+                    (
+                        self.ids.score_canvas,
+                        Canvas::new()
+                            .length_weight(0.3)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.2)),
+                    ),
+                    (
+                        self.ids.duration_canvas,
+                        Canvas::new()
+                            .length_weight(0.4)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.4)),
+                    ),
+                    (
+                        self.ids.accuracy_canvas,
+                        Canvas::new()
+                            .length_weight(0.3)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.2)),
+                    ),
+                ])
+                .wh(conrod_core::Dimensions::new(200.0, 30.0))
+                .set(self.ids.indicator_canvas, &mut ui_cell);
+
+            Text::new(&format!(
+                "{:02}:{:02}",
+                (round_timer_sec / 60.0) as i32,
+                (round_timer_sec % 60.0) as i32
+            ))
+            .color(color::BLACK)
+            .middle_of(self.ids.duration_canvas)
+            .set(self.ids.duration_label, &mut ui_cell);
+
+            Text::new(&format!("{}", self.score.score))
+                .font_size(12)
+                .color(color::BLACK)
+                .middle_of(self.ids.score_canvas)
+                .set(self.ids.score_label, &mut ui_cell);
+            Text::new(&format!(
+                "{:.2}%",
+                (self.score.hit) as f32 / (self.score.hit + self.score.miss).max(1) as f32 * 100.0
+            ))
+            .font_size(12)
+            .color(color::BLACK)
+            .middle_of(self.ids.accuracy_canvas)
+            .set(self.ids.accuracy_label, &mut ui_cell);
+        }
+
+        Text::new(&self.weapon.get_ammo_text())
+            .font_size(14)
+            .color(color::WHITE)
+            .bottom_right_with_margin_on(self.ids.canvas, MARGIN)
+            .set(self.ids.ammo_label, &mut ui_cell);
+
+        let mut game_finished = false;
+
+        let mut scene_op = SceneOp::None;
+
+        if !self.freeze {
+            renderer.camera.move_direction(input_manager.mouse_movement);
+
+            let _player_position = update_player_position(
+                delta_time,
+                input_manager,
+                &mut renderer.camera,
+                &mut self.physics,
+                self.player_rigid_body_handle,
+                &mut self.player_controller,
+            );
+        }
+
+        match self.game_state {
+            GameState::Preround => {
+                Text::new("Press any mouse key to start")
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if input_manager.is_any_mouse_press() {
+                    self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION));
+                }
+            }
+            GameState::Prepare(ref mut timer) => {
+                timer.update(delta_time);
+
+                Text::new(&format!("{:.1}", timer.get_duration()))
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if timer.is_finished() {
+                    self.freeze = false;
+                    self.game_state = GameState::Round;
+                    renderer.game_renderer.render_crosshair = true;
+                }
+            }
+            GameState::Round => {
+                self.round_timer.update(delta_time);
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
+                self.delta_shoot_time.update(delta_time);
+
+                update_target(
+                    &mut self.world,
+                    &mut self.physics,
+                    delta_time,
+                    &mut self.rng,
+                );
+
+                self.target_disposal();
+
+                shootanim(
+                    &mut self.shoot_animation,
+                    &mut renderer.rendering_info,
+                    delta_time,
+                );
+
+                shoot_trigger = true;
+
+                if self.round_timer.is_finished() {
+                    self.game_state = GameState::Finishing(Timer::new(FINISHING_DURATION));
+                }
+            }
+            GameState::Finishing(ref mut timer) => {
+                renderer.game_renderer.render_crosshair = false;
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
+                timer.update(delta_time);
+
+                Text::new("Time out!")
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if timer.is_finished() {
+                    game_finished = true;
+                }
+            }
+        };
+
+        if !self.freeze {
+            self.physics.physics_pipeline.step(
+                &self.physics.gravity,
+                &self.physics.integration_parameters,
+                &mut self.physics.island_manager,
+                &mut self.physics.broad_phase,
+                &mut self.physics.narrow_phase,
+                &mut self.physics.rigid_body_set,
+                &mut self.physics.collider_set,
+                &mut self.physics.joint_set,
+                &mut self.physics.ccd_solver,
+                &(),
+                &(),
+            );
+            self.physics.query_pipeline.update(
+                &self.physics.island_manager,
+                &self.physics.rigid_body_set,
+                &self.physics.collider_set,
+            );
+
+            if shoot_trigger {
+                self.shoot(
+                    input_manager,
+                    audio_context,
+                    &mut renderer.camera,
+                    delta_time,
+                );
+            }
+        }
+
+        drop(ui_cell);
+
+        if game_finished {
+            scene_op = SceneOp::Replace(
+                Box::new(GameScoreScene::new(
+                    conrod_handle,
+                    GameModeScore::Gridshot(GridshotGameScoreDisplay {
+                        accuracy: (self.score.hit) as f32
+                            / (self.score.hit + self.score.miss).max(1) as f32
+                            * 100.0,
+                        hit: self.score.hit,
+                        miss: self.score.miss,
+                        score: self.score.score,
+                        targets_per_second: self.score.hit as f32 / GAME_DURATION,
+                        avg_inter_target_angle: self.score.total_inter_target_angle
+                            / self.score.inter_target_count.max(1) as f32,
+                        created_at: Utc::now().naive_utc(),
+                        background_type: self.background_type,
+                    }),
+                    self.difficulty,
+                )),
+                None,
+            );
+        }
+
+        if input_manager.is_action_press(Action::Pause) {
+            scene_op = SceneOp::Push(Box::new(PauseScene::new(renderer, conrod_handle)), None);
+        }
+
+        scene_op
+    }
+
+    fn prerender(
+        &mut self,
+        renderer: &mut Renderer,
+        _input_manager: &InputManager,
+        _delta_time: f32,
+        _conrod_handle: &mut ConrodHandle,
+        _audio_context: &mut AudioContext,
+    ) {
+        enqueue_target(&mut self.world, &mut self.physics, renderer);
+        enqueue_wall(
+            &mut self.world,
+            &mut self.physics,
+            renderer,
+            MaterialType::StoneWall,
+        );
+    }
+
+    fn deinit(
+        &mut self,
+        window: &mut Window,
+        renderer: &mut Renderer,
+        _conrod_handle: &mut ConrodHandle,
+        _audio_context: &mut AudioContext,
+        _database: &mut Database,
+    ) {
+        renderer.rendering_info.fov_shootanim.y = 0.0;
+        renderer.camera.reset_zoom();
+        renderer.camera.effects.clear();
+        renderer.render_objects.clear();
+        renderer.game_renderer.render_crosshair = false;
+        window.set_is_cursor_grabbed(false);
+    }
+}
+
+impl GridshotGameScene {
+    fn shoot(
+        &mut self,
+        input_manager: &InputManager,
+        audio_context: &mut AudioContext,
+        camera: &mut Camera,
+        _delta_time: f32,
+    ) {
+        if let Some(direction) = self.weapon.fire(input_manager, camera) {
+            self.shoot_animation.trigger();
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let sink = rodio::Sink::try_new(&audio_context.output_stream_handle).unwrap();
+                sink.append(
+                    rodio::Decoder::new(BufReader::new(Cursor::new(AUDIO_FILE_SHOOT.to_vec())))
+                        .unwrap(),
+                );
+                audio_context.push(Sink::Regular(sink));
+            }
+
+            if let Some((handle, _distance)) = shoot_ray(&self.physics, camera, direction) {
+                let collider = self.physics.collider_set.get(handle).unwrap();
+                let hit_direction = (collider.translation() - camera.position).normalize();
+
+                // The floor has no entity, hitting it is a miss.
+                let mut previous_slot = None;
+                if let Some((target, slot)) =
+                    Entity::from_bits(collider.user_data as u64).and_then(|entity| {
+                        self.world
+                            .query_one_mut::<(&mut SphereTarget, &GridSlot)>(entity)
+                            .ok()
+                    })
+                {
+                    // A dimmed target is already counted, so hitting it again is a miss.
+                    if !target.is_shooted() && target.try_shoot(audio_context) {
+                        previous_slot = Some(slot.0);
+                    }
+                }
+
+                if let Some(previous_slot) = previous_slot {
+                    let shoot_time =
+                        self.delta_shoot_time.get_duration() - self.weapon.get_last_shot_delay();
+                    self.delta_shoot_time.reset();
+
+                    if let Some(last_hit_direction) = self.last_hit_direction {
+                        self.score.total_inter_target_angle +=
+                            last_hit_direction.angle(&hit_direction).to_degrees();
+                        self.score.inter_target_count += 1;
+                    }
+                    self.last_hit_direction = Some(hit_direction);
+
+                    self.score.hit += 1;
+                    self.score.score += ((100.0 * (2.0 - shoot_time)) as i32).max(10);
+
+                    self.spawn_in_free_slot(Some(previous_slot));
+                } else {
+                    // Hit other than live target
+                    self.score.miss += 1;
+                }
+            } else {
+                self.score.miss += 1;
+            }
+        }
+    }
+
+    fn target_disposal(&mut self) {
+        for (id, (target, collider_handle, slot)) in
+            self.world
+                .query_mut::<(&SphereTarget, &ColliderHandle, &GridSlot)>()
+        {
+            if target.is_need_to_be_deleted() {
+                self.entity_to_remove.push(id);
+                self.physics.collider_set.remove(
+                    *collider_handle,
+                    &mut self.physics.island_manager,
+                    &mut self.physics.rigid_body_set,
+                    false,
+                );
+                self.target_grid.free(slot.0);
+            }
+        }
+        for entity in self.entity_to_remove.iter() {
+            self.world.despawn(*entity).unwrap();
+        }
+        self.entity_to_remove.clear();
+    }
+
+    fn spawn_in_free_slot(&mut self, previous_slot: Option<usize>) {
+        if let Some(slot) = self.target_grid.allocate(&mut self.rng, previous_slot) {
+            // Like `spawn_target`, with the slot the target holds
+            let target = SphereTarget::new(None, Patrol::None);
+            let entity = self.world.reserve_entity();
+            let collider_handle = self.physics.collider_set.insert(
+                ColliderBuilder::new(SharedShape::ball(target.get_radius()))
+                    .user_data(entity.to_bits().get() as u128)
+                    .translation(self.target_grid.slot_position(slot))
+                    .build(),
+            );
+            self.world
+                .spawn_at(entity, (collider_handle, target, GridSlot(slot)));
+        }
+    }
+}
@@ -53,7 +53,7 @@ impl Scene for GuideScene {
         &mut self,
         _window: &mut Window,
         _renderer: &mut Renderer,
-        input_manager: &InputManager,
+        input_manager: &mut InputManager,
         _delta_time: f32,
         conrod_handle: &mut ConrodHandle,
         _audio_context: &mut AudioContext,
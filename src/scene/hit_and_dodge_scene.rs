@@ -8,18 +8,20 @@ use std::io::{BufReader, Cursor};
 use hecs::{Entity, World};
 
 use rapier3d::prelude::*;
-use winit::event::{MouseButton, VirtualKeyCode};
 use winit::event_loop::ControlFlow;
 
 use crate::animation::InOutAnimation;
 use crate::audio::Sink;
 use crate::audio::{AudioContext, AUDIO_FILE_SHOOT};
 use crate::database::Database;
-use crate::entity::enemy::gunman::{Bullet, Gunman};
+use crate::entity::enemy::gunman::{Bullet, Gunman, GunmanAccuracy};
 use crate::entity::enemy::swordman::Swordman;
+use crate::entity::enemy::{HitZone, SHOT_DAMAGE};
 
 use crate::gui::ConrodHandle;
+use crate::input_binding::Action;
 use crate::input_manager::InputManager;
+use crate::navigation::NavGrid;
 use crate::physics::GamePhysics;
 use crate::renderer::render_objects::MaterialType;
 use crate::renderer::render_objects::ShapeType;
@@ -28,33 +30,50 @@ use crate::renderer::Renderer;
 use crate::scene::game_score_scene::{GameModeScore, GameScoreScene, HitAndDodgeGameScoreDisplay};
 use crate::scene::pause_scene::PauseScene;
 use crate::scene::{
-    GameDifficulty, GameState, MaybeMessage, Scene, SceneOp, FINISHING_DURATION,
-    IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION, PREPARE_DURATION,
+    GameDifficulty, GameState, MaybeMessage, Scene, SceneOp, Value, FINISHING_DURATION,
+    IN_SHOOT_ANIM_DURATION, MARGIN, OUT_SHOOT_ANIM_DURATION, PREPARE_DURATION,
+};
+use crate::systems::gunman::{
+    despawn_dead_gunmans, enqueue_bullet, enqueue_gunman, spawn_gunman, update_bullet_near_misses,
+    update_gunmans,
 };
-use crate::systems::gunman::{enqueue_bullet, enqueue_gunman, spawn_gunman, update_gunmans};
 use crate::systems::player::{init_player, setup_player_collider};
-use crate::systems::swordman::{enqueue_swordman, spawn_swordman, update_swordmans};
+use crate::systems::swordman::{
+    despawn_dead_swordmans, enqueue_swordman, spawn_swordman, update_swordmans,
+};
 use crate::systems::target::enqueue_target;
-use crate::systems::update_player_movement::update_player_position;
+use crate::systems::update_player_movement::{update_player_dash, update_player_position};
 use crate::systems::wall::{enqueue_wall, spawn_wall};
 use crate::timer::{Stopwatch, Timer};
 
+use crate::weapon::{Weapon, WeaponType};
 use crate::window::Window;
 use conrod_core::widget_ids;
 
-use nalgebra::Vector3;
+use nalgebra::{Vector2, Vector3};
 
 use crate::camera::Camera;
+use crate::character_controller::CharacterController;
 use crate::systems::shoot_ray::shoot_ray;
 use crate::systems::shootanim::shootanim;
 use rand::prelude::SmallRng;
-use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
+
+fn new_gunman(difficulty: GameDifficulty) -> Gunman {
+    match difficulty {
+        GameDifficulty::Easy => Gunman::new(0.3, 3.0, GunmanAccuracy::new(difficulty)),
+        GameDifficulty::Medium => Gunman::new(0.0, 3.0, GunmanAccuracy::new(difficulty)),
+        GameDifficulty::Hard => Gunman::new(0.0, 5.0, GunmanAccuracy::new(difficulty)),
+    }
+}
 
 widget_ids! {
     pub struct HitAndDodgeGameSceneIds {
         // The main canvas
         canvas,
         start_duration_label,
+        ammo_label,
+        dash_label,
 
         indicator_canvas,
 
@@ -72,6 +91,8 @@ pub struct Score {
     pub miss: u16,
     pub score: i32,
     pub hit_taken: u16,
+    pub headshot: u16,
+    pub near_miss: u16,
     pub total_shoot_time: f32,
 }
 
@@ -82,20 +103,29 @@ impl Score {
             miss: 0,
             score: 0,
             hit_taken: 0,
+            headshot: 0,
+            near_miss: 0,
             total_shoot_time: 0.0,
         }
     }
 }
 
 pub const GAME_DURATION: f32 = 90.0;
+pub const HEADSHOT_BONUS: i32 = 50;
+pub const KILL_BONUS: i32 = 150;
+pub const NEAR_MISS_BONUS: i32 = 50;
 
 pub struct HitAndDodgeGameScene {
     ids: HitAndDodgeGameSceneIds,
     world: World,
     physics: GamePhysics,
     player_rigid_body_handle: RigidBodyHandle,
+    player_controller: CharacterController,
+    nav_grid: NavGrid,
+    dash_cooldown: Timer,
     delta_shoot_time: Stopwatch,
-    shoot_timer: Timer,
+    weapon: Weapon,
+    background_type: BackgroundType,
     score: Score,
     rng: SmallRng,
     shoot_animation: InOutAnimation,
@@ -121,39 +151,41 @@ impl HitAndDodgeGameScene {
             .insert(ColliderBuilder::new(SharedShape::cuboid(1000.0, 1.0, 1000.0)).build());
 
         let player_rigid_body_handle =
-            setup_player_collider(&mut physics, Vector3::new(0.0, 1.0, 0.0));
-
-        let mut rng = SmallRng::from_entropy();
+            setup_player_collider(&mut physics, Vector3::new(0.0, 2.6, 0.0));
 
         match difficulty {
-            GameDifficulty::Medium => {
+            GameDifficulty::Hard => {
                 spawn_swordman(
                     &mut world,
                     &mut physics,
                     Vector3::<f32>::new(-2.0, 2.5, -2.0),
-                    Swordman::new(),
+                    Swordman::new(3.0),
+                );
+                spawn_swordman(
+                    &mut world,
+                    &mut physics,
+                    Vector3::<f32>::new(-2.0, 2.5, 2.0),
+                    Swordman::new(3.0),
                 );
-
                 spawn_gunman(
                     &mut world,
                     &mut physics,
                     Vector3::<f32>::new(2.0, 2.5, -2.0),
-                    Gunman::new(&mut rng, 0.0, 3.0),
+                    new_gunman(difficulty),
                 );
             }
-            GameDifficulty::Hard => {
+            GameDifficulty::Medium => {
                 spawn_swordman(
                     &mut world,
                     &mut physics,
                     Vector3::<f32>::new(-2.0, 2.5, -2.0),
-                    Swordman::new(),
+                    Swordman::new(3.0),
                 );
-
                 spawn_gunman(
                     &mut world,
                     &mut physics,
                     Vector3::<f32>::new(2.0, 2.5, -2.0),
-                    Gunman::new(&mut rng, 0.0, 5.0),
+                    new_gunman(difficulty),
                 );
             }
             GameDifficulty::Easy => {
@@ -161,7 +193,7 @@ impl HitAndDodgeGameScene {
                     &mut world,
                     &mut physics,
                     Vector3::<f32>::new(2.0, 2.5, -2.0),
-                    Gunman::new(&mut rng, 0.3, 3.0),
+                    new_gunman(difficulty),
                 );
             }
         };
@@ -191,15 +223,21 @@ impl HitAndDodgeGameScene {
             Vector3::new(0.5, 0.398, 9.99),
         );
 
+        let nav_grid = NavGrid::new(&physics, Vector2::new(0.0, 0.0), 10.0);
+
         Self {
             world,
             physics,
             player_rigid_body_handle,
+            player_controller: CharacterController::new(),
+            nav_grid,
+            dash_cooldown: Timer::new_finished(),
             ids: HitAndDodgeGameSceneIds::new(conrod_handle.get_ui_mut().widget_id_generator()),
             score: Score::new(),
             delta_shoot_time: Stopwatch::new(),
-            shoot_timer: Timer::new_finished(),
-            rng,
+            weapon: Weapon::new(WeaponType::Pistol),
+            background_type: BackgroundType::Forest,
+            rng: SmallRng::from_entropy(),
             shoot_animation: InOutAnimation::new(IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION),
             entity_to_remove: Vec::new(),
             round_timer: Timer::new(GAME_DURATION),
@@ -223,8 +261,6 @@ impl Scene for HitAndDodgeGameScene {
         renderer.is_render_gui = true;
         renderer.is_render_game = true;
 
-        renderer.rendering_info.background_type = BackgroundType::Forest;
-
         init_player(&mut self.physics, renderer, self.player_rigid_body_handle);
 
         // Ground
@@ -239,19 +275,28 @@ impl Scene for HitAndDodgeGameScene {
         audio_context.global_sinks_map.remove("bgm");
 
         if let Some(m) = message {
+            if let Some(Value::I64(background_type)) = m.get("background_type") {
+                self.background_type = BackgroundType::from(*background_type as usize);
+            }
+            if let Some(Value::I64(weapon_type)) = m.get("weapon_type") {
+                self.weapon = Weapon::new(WeaponType::from(*weapon_type as usize));
+            }
             if m.contains_key("from_pause") {
                 self.freeze = true;
                 renderer.game_renderer.render_crosshair = false;
                 self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION))
             }
         }
+
+        renderer.rendering_info.background_type = self.background_type;
+        renderer.rendering_info.weapon_type = self.weapon.get_type() as u32;
     }
 
     fn update(
         &mut self,
         _window: &mut Window,
         renderer: &mut Renderer,
-        input_manager: &InputManager,
+        input_manager: &mut InputManager,
         delta_time: f32,
         conrod_handle: &mut ConrodHandle,
         audio_context: &mut AudioContext,
@@ -320,14 +365,28 @@ impl Scene for HitAndDodgeGameScene {
             .set(self.ids.accuracy_label, &mut ui_cell);
         }
 
+        Text::new(&self.weapon.get_ammo_text())
+            .font_size(14)
+            .color(color::WHITE)
+            .bottom_right_with_margin_on(self.ids.canvas, MARGIN)
+            .set(self.ids.ammo_label, &mut ui_cell);
+
+        Text::new(&if self.dash_cooldown.is_finished() {
+            "Dash ready".to_string()
+        } else {
+            format!("Dash {:.1}s", self.dash_cooldown.get_duration())
+        })
+        .font_size(14)
+        .color(color::WHITE)
+        .bottom_left_with_margin_on(self.ids.canvas, MARGIN)
+        .set(self.ids.dash_label, &mut ui_cell);
+
         let mut game_finished = false;
 
         let mut scene_op = SceneOp::None;
 
         if !self.freeze {
-            renderer
-                .camera
-                .move_direction(input_manager.mouse_movement * delta_time);
+            renderer.camera.move_direction(input_manager.mouse_movement);
 
             let _player_position = update_player_position(
                 delta_time,
@@ -335,6 +394,7 @@ impl Scene for HitAndDodgeGameScene {
                 &mut renderer.camera,
                 &mut self.physics,
                 self.player_rigid_body_handle,
+                &mut self.player_controller,
             );
         }
 
@@ -365,23 +425,40 @@ impl Scene for HitAndDodgeGameScene {
             }
             GameState::Round => {
                 self.round_timer.update(delta_time);
-                self.shoot_timer.update(delta_time);
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
                 self.delta_shoot_time.update(delta_time);
 
+                update_player_dash(
+                    delta_time,
+                    input_manager,
+                    &renderer.camera,
+                    &mut self.player_controller,
+                    &mut self.dash_cooldown,
+                );
+
                 update_gunmans(
                     &mut self.world,
                     &mut self.physics,
                     delta_time,
                     &renderer.camera.position,
+                    &self.player_controller.get_velocity(),
+                    &self.nav_grid,
                     &mut self.rng,
                 );
+                let prev_hit_taken = self.score.hit_taken;
                 update_swordmans(
                     &mut self.world,
                     &mut self.physics,
-                    &mut self.score,
+                    &mut self.score.hit_taken,
                     delta_time,
                     &renderer.camera.position,
                 );
+                if self.score.hit_taken > prev_hit_taken {
+                    renderer.camera.effects.add_flinch();
+                }
+
+                self.respawn_dead_enemies();
 
                 shootanim(
                     &mut self.shoot_animation,
@@ -397,7 +474,8 @@ impl Scene for HitAndDodgeGameScene {
             }
             GameState::Finishing(ref mut timer) => {
                 renderer.game_renderer.render_crosshair = false;
-                self.shoot_timer.update(delta_time);
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
                 timer.update(delta_time);
 
                 Text::new("Time out!")
@@ -430,13 +508,23 @@ impl Scene for HitAndDodgeGameScene {
                 &self.physics.rigid_body_set,
                 &self.physics.collider_set,
             );
+            update_bullet_near_misses(
+                &mut self.world,
+                &self.physics,
+                self.player_rigid_body_handle,
+            );
 
             if shoot_trigger {
-                self.shoot(input_manager, audio_context, &renderer.camera, delta_time);
+                self.shoot(
+                    input_manager,
+                    audio_context,
+                    &mut renderer.camera,
+                    delta_time,
+                );
             }
         }
 
-        self.bullet_disposal();
+        self.bullet_disposal(&mut renderer.camera);
 
         drop(ui_cell);
 
@@ -454,6 +542,10 @@ impl Scene for HitAndDodgeGameScene {
                         score: self.score.score,
                         avg_hit_time: GAME_DURATION / self.score.hit.max(1) as f32,
                         created_at: Utc::now().naive_utc(),
+                        background_type: self.background_type,
+                        headshot_rate: self.score.headshot as f32 / self.score.hit.max(1) as f32
+                            * 100.0,
+                        near_miss: self.score.near_miss,
                     }),
                     self.difficulty,
                 )),
@@ -461,7 +553,7 @@ impl Scene for HitAndDodgeGameScene {
             );
         }
 
-        if input_manager.is_keyboard_press(&VirtualKeyCode::Escape) {
+        if input_manager.is_action_press(Action::Pause) {
             scene_op = SceneOp::Push(Box::new(PauseScene::new(renderer, conrod_handle)), None);
         }
 
@@ -497,6 +589,8 @@ impl Scene for HitAndDodgeGameScene {
         _database: &mut Database,
     ) {
         renderer.rendering_info.fov_shootanim.y = 0.0;
+        renderer.camera.reset_zoom();
+        renderer.camera.effects.clear();
         renderer.render_objects.clear();
         window.set_is_cursor_grabbed(false);
     }
@@ -507,12 +601,11 @@ impl HitAndDodgeGameScene {
         &mut self,
         input_manager: &InputManager,
         audio_context: &mut AudioContext,
-        camera: &Camera,
+        camera: &mut Camera,
         _delta_time: f32,
     ) {
-        if input_manager.is_mouse_press(&MouseButton::Left) && self.shoot_timer.is_finished() {
+        if let Some(direction) = self.weapon.fire(input_manager, camera) {
             self.shoot_animation.trigger();
-            self.shoot_timer.reset(0.4);
             #[cfg(not(target_arch = "wasm32"))]
             {
                 let sink = rodio::Sink::try_new(&audio_context.output_stream_handle).unwrap();
@@ -522,25 +615,44 @@ impl HitAndDodgeGameScene {
                 );
                 audio_context.push(Sink::Regular(sink));
             }
-            if let Some((handle, _distance)) = shoot_ray(&self.physics, camera) {
+            if let Some((handle, _distance)) = shoot_ray(&self.physics, camera, direction) {
                 let collider = self.physics.collider_set.get(handle).unwrap();
-                if let Some(entity) = Entity::from_bits(collider.user_data as u64) {
-                    if let Ok(mut gunman) = self.world.get_mut::<Gunman>(entity) {
-                        gunman.hit();
-                        let shoot_time = self.delta_shoot_time.get_duration();
-                        self.delta_shoot_time.reset();
-                        self.score.hit += 1;
-                        self.score.score += ((100.0 * (7.0 - shoot_time)) as i32).max(100);
-                    } else {
-                        self.score.miss += 1;
+                let zone = HitZone::from_user_data(collider.user_data);
+                let entity = Entity::from_bits(collider.user_data as u64);
+                let result = match (entity, zone) {
+                    (Some(entity), Some(zone)) => {
+                        if let Ok(mut gunman) = self.world.get_mut::<Gunman>(entity) {
+                            if gunman.is_dead() {
+                                None
+                            } else {
+                                Some((zone, gunman.hit(SHOT_DAMAGE, zone)))
+                            }
+                        } else if let Ok(mut swordman) = self.world.get_mut::<Swordman>(entity) {
+                            if swordman.is_dead() {
+                                None
+                            } else {
+                                Some((zone, swordman.hit(SHOT_DAMAGE, zone)))
+                            }
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
+                };
+
+                if let Some((zone, killed)) = result {
+                    let shoot_time =
+                        self.delta_shoot_time.get_duration() - self.weapon.get_last_shot_delay();
+                    self.delta_shoot_time.reset();
+                    self.score.hit += 1;
+                    self.score.score += ((100.0 * (7.0 - shoot_time)) as i32).max(100);
+                    if zone == HitZone::Head {
+                        self.score.headshot += 1;
+                        self.score.score += HEADSHOT_BONUS;
+                    }
+                    if killed {
+                        self.score.score += KILL_BONUS;
                     }
-                    // else if let Ok(mut swordman) = self.world.get_mut::<Swordman>(entity) {
-                    //     swordman.hit();
-                    //
-                    //     let shoot_time = self.delta_shoot_time.get_duration();
-                    //     self.delta_shoot_time.reset();
-                    //
-                    //     self.score.score += ((100.0 * (5.0 - shoot_time)) as i32).max(100);
                 } else {
                     self.score.miss += 1;
                 }
@@ -551,7 +663,37 @@ impl HitAndDodgeGameScene {
         }
     }
 
-    fn bullet_disposal(&mut self) {
+    // Replaces every enemy whose death animation has finished with a fresh one of the same
+    // kind somewhere inside the arena.
+    fn respawn_dead_enemies(&mut self) {
+        let gunman_count = despawn_dead_gunmans(&mut self.world, &mut self.physics);
+        let swordman_count = despawn_dead_swordmans(&mut self.world, &mut self.physics);
+
+        for _ in 0..gunman_count {
+            let position = self.random_spawn_position();
+            let gunman = new_gunman(self.difficulty);
+            spawn_gunman(&mut self.world, &mut self.physics, position, gunman);
+        }
+        for _ in 0..swordman_count {
+            let position = self.random_spawn_position();
+            spawn_swordman(
+                &mut self.world,
+                &mut self.physics,
+                position,
+                Swordman::new(3.0),
+            );
+        }
+    }
+
+    fn random_spawn_position(&mut self) -> Vector3<f32> {
+        Vector3::new(
+            self.rng.gen_range(-8.0..8.0),
+            2.5,
+            self.rng.gen_range(-8.0..8.0),
+        )
+    }
+
+    fn bullet_disposal(&mut self, camera: &mut Camera) {
         while let Ok(contact_event) = self.physics.contact_recv.try_recv() {
             match contact_event {
                 ContactEvent::Started(a_collider, b_collider) => {
@@ -587,6 +729,10 @@ impl HitAndDodgeGameScene {
 
                         if has_player {
                             self.score.hit_taken += 1;
+                            camera.effects.add_flinch();
+                        } else if self.world.get::<Bullet>(e).unwrap().is_near_miss() {
+                            self.score.near_miss += 1;
+                            self.score.score += NEAR_MISS_BONUS;
                         }
                     }
                 }
@@ -0,0 +1,593 @@
+use conrod_core::{color, Color, Colorable, Positionable, Sizeable, Widget};
+
+use chrono::Utc;
+use std::io::{BufReader, Cursor};
+
+use hecs::{Entity, World};
+
+use rapier3d::prelude::*;
+use winit::event_loop::ControlFlow;
+
+use crate::animation::InOutAnimation;
+use crate::audio::Sink;
+use crate::audio::{AudioContext, AUDIO_FILE_SHOOT};
+use crate::database::Database;
+use crate::entity::target::{Patrol, SphereTarget};
+
+use crate::gui::ConrodHandle;
+use crate::input_binding::Action;
+use crate::input_manager::InputManager;
+use crate::physics::GamePhysics;
+
+use crate::renderer::render_objects::{MaterialType, ShapeType};
+use crate::renderer::rendering_info::BackgroundType;
+use crate::renderer::Renderer;
+use crate::scene::game_score_scene::{GameModeScore, GameScoreScene, LeadGameScoreDisplay};
+use crate::scene::pause_scene::PauseScene;
+use crate::scene::{
+    GameDifficulty, GameState, MaybeMessage, Scene, SceneOp, Value, FINISHING_DURATION,
+    IN_SHOOT_ANIM_DURATION, MARGIN, OUT_SHOOT_ANIM_DURATION, PREPARE_DURATION,
+};
+use crate::systems::player::{init_player, setup_player_collider};
+use crate::systems::projectile::{
+    enqueue_projectile, resolve_projectile_contacts, spawn_projectile, update_projectiles,
+};
+use crate::systems::target::{enqueue_target, spawn_target, update_target};
+use crate::systems::update_player_movement::update_player_position;
+use crate::timer::Timer;
+
+use crate::weapon::{Ballistics, Weapon, WeaponType};
+use crate::window::Window;
+use conrod_core::widget::envelope_editor::EnvelopePoint;
+use conrod_core::widget::{Canvas, Text};
+use conrod_core::widget_ids;
+
+use crate::camera::Camera;
+use crate::character_controller::CharacterController;
+use crate::systems::shootanim::shootanim;
+use nalgebra::Vector3;
+use rand::distributions::Uniform;
+use rand::prelude::SmallRng;
+use rand::{Rng, SeedableRng};
+
+widget_ids! {
+    pub struct LeadGameSceneIds {
+        // The main canvas
+        canvas,
+        start_duration_label,
+        ammo_label,
+
+        indicator_canvas,
+
+        duration_canvas,
+        duration_label,
+        score_canvas,
+        score_label,
+        accuracy_canvas,
+        accuracy_label,
+    }
+}
+
+pub struct Score {
+    pub hit: u16,
+    pub miss: u16,
+    pub score: i32,
+    pub total_hit_distance: f32,
+}
+
+impl Score {
+    pub fn new() -> Self {
+        Self {
+            hit: 0,
+            miss: 0,
+            score: 0,
+            total_hit_distance: 0.0,
+        }
+    }
+}
+
+pub const GAME_DURATION: f32 = 60.0;
+
+const TARGET_COUNT: usize = 3;
+// Targets cross the whole field of view from one side to the other.
+const PATROL_HALF_WIDTH: f32 = 12.0;
+
+pub struct LeadGameScene {
+    ids: LeadGameSceneIds,
+    world: World,
+    physics: GamePhysics,
+    player_rigid_body_handle: RigidBodyHandle,
+    player_controller: CharacterController,
+    game_state: GameState,
+    weapon: Weapon,
+    background_type: BackgroundType,
+    ballistics: Ballistics,
+    target_speed: f32,
+    score: Score,
+    rng: SmallRng,
+    round_timer: Timer,
+    shoot_animation: InOutAnimation,
+    entity_to_remove: Vec<Entity>,
+    freeze: bool,
+    difficulty: GameDifficulty,
+}
+
+impl LeadGameScene {
+    pub fn new(
+        _renderer: &mut Renderer,
+        conrod_handle: &mut ConrodHandle,
+        difficulty: GameDifficulty,
+    ) -> Self {
+        let world = World::new();
+        let mut physics = GamePhysics::new();
+
+        // Ground
+        physics
+            .collider_set
+            .insert(ColliderBuilder::new(SharedShape::cuboid(10.0, 1.0, 10.0)).build());
+
+        let player_rigid_body_handle =
+            setup_player_collider(&mut physics, Vector3::new(0.0, 2.6, 0.0));
+
+        // Slower projectiles and faster targets need more lead, and Hard adds the drop.
+        let (ballistics, target_speed) = match difficulty {
+            GameDifficulty::Easy => (
+                Ballistics {
+                    speed: 40.0,
+                    gravity_scale: 0.0,
+                },
+                8.0,
+            ),
+            GameDifficulty::Medium => (
+                Ballistics {
+                    speed: 30.0,
+                    gravity_scale: 0.0,
+                },
+                10.0,
+            ),
+            GameDifficulty::Hard => (
+                Ballistics {
+                    speed: 30.0,
+                    gravity_scale: 1.0,
+                },
+                12.0,
+            ),
+        };
+
+        let mut scene = Self {
+            world,
+            physics,
+            player_rigid_body_handle,
+            player_controller: CharacterController::new(),
+            ids: LeadGameSceneIds::new(conrod_handle.get_ui_mut().widget_id_generator()),
+            score: Score::new(),
+            game_state: GameState::Preround,
+            weapon: Weapon::new(WeaponType::Pistol).with_projectile(ballistics),
+            background_type: BackgroundType::Forest,
+            ballistics,
+            target_speed,
+            rng: SmallRng::from_entropy(),
+            shoot_animation: InOutAnimation::new(IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION),
+            entity_to_remove: Vec::new(),
+            round_timer: Timer::new(GAME_DURATION),
+            freeze: false,
+            difficulty,
+        };
+
+        for _ in 0..TARGET_COUNT {
+            scene.spawn_target();
+        }
+
+        scene
+    }
+}
+
+impl Scene for LeadGameScene {
+    fn init(
+        &mut self,
+        message: MaybeMessage,
+        window: &mut Window,
+        renderer: &mut Renderer,
+        _conrod_handle: &mut ConrodHandle,
+        audio_context: &mut AudioContext,
+        _database: &mut Database,
+    ) {
+        renderer.is_render_gui = true;
+        renderer.is_render_game = true;
+
+        init_player(&mut self.physics, renderer, self.player_rigid_body_handle);
+
+        // Ground
+        let (objects, ref mut bound) = renderer.render_objects.next_static();
+        objects.position = nalgebra::Vector3::new(0.0, 0.0, 0.0);
+        objects.shape_type_material_ids.0 = ShapeType::Box;
+        objects.shape_type_material_ids.1 = MaterialType::CobblestonePaving;
+        objects.shape_data1 = nalgebra::Vector4::new(10.0, 1.0, 10.0, 0.0);
+        *bound = objects.get_bounding_sphere_radius();
+
+        window.set_is_cursor_grabbed(true);
+        audio_context.global_sinks_map.remove("bgm");
+
+        if let Some(m) = message {
+            if let Some(Value::I64(background_type)) = m.get("background_type") {
+                self.background_type = BackgroundType::from(*background_type as usize);
+            }
+            if let Some(Value::I64(weapon_type)) = m.get("weapon_type") {
+                self.weapon = Weapon::new(WeaponType::from(*weapon_type as usize))
+                    .with_projectile(self.ballistics);
+            }
+            if m.contains_key("from_pause") {
+                self.freeze = true;
+                renderer.game_renderer.render_crosshair = false;
+                self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION))
+            }
+        }
+
+        renderer.rendering_info.background_type = self.background_type;
+        renderer.rendering_info.weapon_type = self.weapon.get_type() as u32;
+    }
+
+    fn update(
+        &mut self,
+        _window: &mut Window,
+        renderer: &mut Renderer,
+        input_manager: &mut InputManager,
+        delta_time: f32,
+        conrod_handle: &mut ConrodHandle,
+        audio_context: &mut AudioContext,
+        _control_flow: &mut ControlFlow,
+        _database: &mut Database,
+    ) -> SceneOp {
+        let mut shoot_trigger = false;
+
+        let round_timer_sec = self.round_timer.get_duration();
+
+        let mut ui_cell = conrod_handle.get_ui_mut().set_widgets();
+        {
+            Canvas::new()
+                .color(Color::Rgba(0.0, 0.0, 0.0, 0.0))
+                .set(self.ids.canvas, &mut ui_cell);
+
+            Canvas::new()
+                .color(Color::Rgba(1.0, 1.0, 1.0, 0.3))
+                .mid_top_of(self.ids.canvas)
+                .flow_right(&[
+                    (
+                        self.ids.score_canvas,
+                        Canvas::new()
+                            .length_weight(0.3)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.2)),
+                    ),
+                    (
+                        self.ids.duration_canvas,
+                        Canvas::new()
+                            .length_weight(0.4)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.4)),
+                    ),
+                    (
+                        self.ids.accuracy_canvas,
+                        Canvas::new()
+                            .length_weight(0.3)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.2)),
+                    ),
+                ])
+                .wh(conrod_core::Dimensions::new(200.0, 30.0))
+                .set(self.ids.indicator_canvas, &mut ui_cell);
+
+            Text::new(&format!(
+                "{:02}:{:02}",
+                (round_timer_sec / 60.0) as i32,
+                (round_timer_sec % 60.0) as i32
+            ))
+            .color(color::BLACK)
+            .middle_of(self.ids.duration_canvas)
+            .set(self.ids.duration_label, &mut ui_cell);
+
+            Text::new(&format!("{}", self.score.score))
+                .font_size(12)
+                .color(color::BLACK)
+                .middle_of(self.ids.score_canvas)
+                .set(self.ids.score_label, &mut ui_cell);
+            Text::new(&format!(
+                "{:.2}%",
+                (self.score.hit) as f32 / (self.score.hit + self.score.miss).max(1) as f32 * 100.0
+            ))
+            .font_size(12)
+            .color(color::BLACK)
+            .middle_of(self.ids.accuracy_canvas)
+            .set(self.ids.accuracy_label, &mut ui_cell);
+        }
+
+        Text::new(&self.weapon.get_ammo_text())
+            .font_size(14)
+            .color(color::WHITE)
+            .bottom_right_with_margin_on(self.ids.canvas, MARGIN)
+            .set(self.ids.ammo_label, &mut ui_cell);
+
+        let mut game_finished = false;
+
+        let mut scene_op = SceneOp::None;
+
+        if !self.freeze {
+            renderer.camera.move_direction(input_manager.mouse_movement);
+
+            let _player_position = update_player_position(
+                delta_time,
+                input_manager,
+                &mut renderer.camera,
+                &mut self.physics,
+                self.player_rigid_body_handle,
+                &mut self.player_controller,
+            );
+        }
+
+        match self.game_state {
+            GameState::Preround => {
+                Text::new("Press any mouse key to start")
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if input_manager.is_any_mouse_press() {
+                    self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION));
+                }
+            }
+            GameState::Prepare(ref mut timer) => {
+                timer.update(delta_time);
+
+                Text::new(&format!("{:.1}", timer.get_duration()))
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if timer.is_finished() {
+                    self.freeze = false;
+                    self.game_state = GameState::Round;
+                    renderer.game_renderer.render_crosshair = true;
+                }
+            }
+            GameState::Round => {
+                self.round_timer.update(delta_time);
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
+
+                update_target(
+                    &mut self.world,
+                    &mut self.physics,
+                    delta_time,
+                    &mut self.rng,
+                );
+
+                self.target_disposal();
+
+                shootanim(
+                    &mut self.shoot_animation,
+                    &mut renderer.rendering_info,
+                    delta_time,
+                );
+
+                shoot_trigger = true;
+
+                if self.round_timer.is_finished() {
+                    self.game_state = GameState::Finishing(Timer::new(FINISHING_DURATION));
+                }
+            }
+            GameState::Finishing(ref mut timer) => {
+                renderer.game_renderer.render_crosshair = false;
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
+                timer.update(delta_time);
+
+                Text::new("Time out!")
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if timer.is_finished() {
+                    game_finished = true;
+                }
+            }
+        };
+
+        if !self.freeze {
+            self.physics.physics_pipeline.step(
+                &self.physics.gravity,
+                &self.physics.integration_parameters,
+                &mut self.physics.island_manager,
+                &mut self.physics.broad_phase,
+                &mut self.physics.narrow_phase,
+                &mut self.physics.rigid_body_set,
+                &mut self.physics.collider_set,
+                &mut self.physics.joint_set,
+                &mut self.physics.ccd_solver,
+                &(),
+                &self.physics.event_handler,
+            );
+            self.physics.query_pipeline.update(
+                &self.physics.island_manager,
+                &self.physics.rigid_body_set,
+                &self.physics.collider_set,
+            );
+
+            // Projectiles still in flight when the round ends are resolved all the same.
+            self.projectile_disposal(audio_context, delta_time);
+
+            if shoot_trigger {
+                self.shoot(
+                    input_manager,
+                    audio_context,
+                    &mut renderer.camera,
+                    delta_time,
+                );
+            }
+        }
+
+        drop(ui_cell);
+
+        if game_finished {
+            scene_op = SceneOp::Replace(
+                Box::new(GameScoreScene::new(
+                    conrod_handle,
+                    GameModeScore::Lead(LeadGameScoreDisplay {
+                        accuracy: (self.score.hit) as f32
+                            / (self.score.hit + self.score.miss).max(1) as f32
+                            * 100.0,
+                        hit: self.score.hit,
+                        miss: self.score.miss,
+                        score: self.score.score,
+                        avg_hit_distance: self.score.total_hit_distance
+                            / self.score.hit.max(1) as f32,
+                        created_at: Utc::now().naive_utc(),
+                        background_type: self.background_type,
+                    }),
+                    self.difficulty,
+                )),
+                None,
+            );
+        }
+
+        if input_manager.is_action_press(Action::Pause) {
+            scene_op = SceneOp::Push(Box::new(PauseScene::new(renderer, conrod_handle)), None);
+        }
+
+        scene_op
+    }
+
+    fn prerender(
+        &mut self,
+        renderer: &mut Renderer,
+        _input_manager: &InputManager,
+        _delta_time: f32,
+        _conrod_handle: &mut ConrodHandle,
+        _audio_context: &mut AudioContext,
+    ) {
+        enqueue_target(&mut self.world, &mut self.physics, renderer);
+        enqueue_projectile(&mut self.world, &mut self.physics, renderer);
+    }
+
+    fn deinit(
+        &mut self,
+        window: &mut Window,
+        renderer: &mut Renderer,
+        _conrod_handle: &mut ConrodHandle,
+        _audio_context: &mut AudioContext,
+        _database: &mut Database,
+    ) {
+        renderer.rendering_info.fov_shootanim.y = 0.0;
+        renderer.camera.reset_zoom();
+        renderer.camera.effects.clear();
+        renderer.render_objects.clear();
+        renderer.game_renderer.render_crosshair = false;
+        window.set_is_cursor_grabbed(false);
+    }
+}
+
+impl LeadGameScene {
+    fn shoot(
+        &mut self,
+        input_manager: &InputManager,
+        audio_context: &mut AudioContext,
+        camera: &mut Camera,
+        _delta_time: f32,
+    ) {
+        let muzzle_position = camera.position;
+        if let Some(direction) = self.weapon.fire(input_manager, camera) {
+            self.shoot_animation.trigger();
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let sink = rodio::Sink::try_new(&audio_context.output_stream_handle).unwrap();
+                sink.append(
+                    rodio::Decoder::new(BufReader::new(Cursor::new(AUDIO_FILE_SHOOT.to_vec())))
+                        .unwrap(),
+                );
+                audio_context.push(Sink::Regular(sink));
+            }
+
+            // Hits are resolved once the projectile touches something, see `projectile_disposal`.
+            if let Some(ballistics) = self.weapon.get_projectile() {
+                spawn_projectile(
+                    &mut self.world,
+                    &mut self.physics,
+                    muzzle_position + direction.into_inner() * 1.0,
+                    direction,
+                    ballistics,
+                );
+            }
+        }
+    }
+
+    fn projectile_disposal(&mut self, audio_context: &mut AudioContext, delta_time: f32) {
+        self.score.miss +=
+            update_projectiles(&mut self.world, &mut self.physics, delta_time) as u16;
+
+        for hit in resolve_projectile_contacts(&mut self.world, &mut self.physics) {
+            let mut is_hit = false;
+            if let Some(entity) = hit.entity {
+                if let Ok(target) = self.world.query_one_mut::<&mut SphereTarget>(entity) {
+                    // A dimmed target is already counted, so hitting it again is a miss.
+                    is_hit = !target.is_shooted() && target.try_shoot(audio_context);
+                }
+            }
+
+            if is_hit {
+                self.score.hit += 1;
+                self.score.total_hit_distance += hit.distance;
+                // Farther hits needed more lead.
+                self.score.score += 100 + (hit.distance * 5.0) as i32;
+            } else {
+                self.score.miss += 1;
+            }
+        }
+    }
+
+    fn target_disposal(&mut self) {
+        for (id, (target, collider_handle)) in
+            self.world.query_mut::<(&SphereTarget, &ColliderHandle)>()
+        {
+            if target.is_need_to_be_deleted() {
+                self.entity_to_remove.push(id);
+                self.physics.collider_set.remove(
+                    *collider_handle,
+                    &mut self.physics.island_manager,
+                    &mut self.physics.rigid_body_set,
+                    false,
+                );
+            }
+        }
+        for _ in 0..self.entity_to_remove.len() {
+            self.spawn_target();
+        }
+        for entity in self.entity_to_remove.iter() {
+            self.world.despawn(*entity).unwrap();
+        }
+        self.entity_to_remove.clear();
+    }
+
+    fn spawn_target(&mut self) {
+        let y = self.rng.sample(Uniform::new(2.0, 5.0));
+        let z = self.rng.sample(Uniform::new(-30.0, -15.0));
+        let (a_x, b_x) = if self.rng.gen_bool(0.5) {
+            (-PATROL_HALF_WIDTH, PATROL_HALF_WIDTH)
+        } else {
+            (PATROL_HALF_WIDTH, -PATROL_HALF_WIDTH)
+        };
+        // Start anywhere along the path so the targets don't move in lockstep.
+        let pos = Vector3::new(
+            self.rng.sample(Uniform::new(a_x.min(b_x), a_x.max(b_x))),
+            y,
+            z,
+        );
+        let patrol = Patrol::Linear {
+            a: Vector3::new(a_x, y, z),
+            b: Vector3::new(b_x, y, z),
+        };
+
+        spawn_target(
+            &mut self.world,
+            &mut self.physics,
+            pos,
+            SphereTarget::new(None, patrol).with_linear_speed(self.target_speed),
+        );
+    }
+}
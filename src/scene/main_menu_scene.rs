@@ -93,7 +93,7 @@ impl Scene for MainMenuScene {
         &mut self,
         _window: &mut Window,
         renderer: &mut Renderer,
-        input_manager: &InputManager,
+        input_manager: &mut InputManager,
         _delta_time: f32,
         conrod_handle: &mut ConrodHandle,
         _audio_context: &mut AudioContext,
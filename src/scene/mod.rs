@@ -17,12 +17,17 @@ pub mod elimination_game_scene;
 pub mod exit_confirm_scene;
 pub mod game_score_scene;
 pub mod game_selection_scene;
+pub mod gridshot_game_scene;
 pub mod guide_scene;
 pub mod hit_and_dodge_scene;
+pub mod lead_game_scene;
 pub mod main_menu_scene;
 pub mod pause_scene;
 pub mod score_history_scene;
 pub mod settings_scene;
+pub mod shrinking_game_scene;
+pub mod spray_control_game_scene;
+pub mod survival_game_scene;
 
 const BUTTON_WIDTH: f64 = 160.0;
 const BUTTON_HEIGHT: f64 = 40.0;
@@ -58,6 +63,11 @@ pub enum GameMode {
     Classic = 0,
     Elimination = 1,
     HitAndDodge = 2,
+    Gridshot = 3,
+    Shrinking = 4,
+    Survival = 5,
+    SprayControl = 6,
+    Lead = 7,
 }
 
 impl From<usize> for GameMode {
@@ -66,6 +76,11 @@ impl From<usize> for GameMode {
             0 => GameMode::Classic,
             1 => GameMode::Elimination,
             2 => GameMode::HitAndDodge,
+            3 => GameMode::Gridshot,
+            4 => GameMode::Shrinking,
+            5 => GameMode::Survival,
+            6 => GameMode::SprayControl,
+            7 => GameMode::Lead,
             _ => unreachable!(),
         }
     }
@@ -120,7 +135,7 @@ pub trait Scene {
         &mut self,
         window: &mut Window,
         renderer: &mut Renderer,
-        input_manager: &InputManager,
+        input_manager: &mut InputManager,
         delta_time: f32,
         conrod_handle: &mut ConrodHandle,
         audio_context: &mut AudioContext,
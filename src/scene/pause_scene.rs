@@ -54,7 +54,7 @@ impl Scene for PauseScene {
         &mut self,
         _window: &mut Window,
         renderer: &mut Renderer,
-        _input_manager: &InputManager,
+        _input_manager: &mut InputManager,
         _delta_time: f32,
         conrod_handle: &mut ConrodHandle,
         _audio_context: &mut AudioContext,
@@ -97,7 +97,7 @@ impl Scene for ScoreHistoryScene {
         &mut self,
         _window: &mut Window,
         _renderer: &mut Renderer,
-        input_manager: &InputManager,
+        input_manager: &mut InputManager,
         _delta_time: f32,
         conrod_handle: &mut ConrodHandle,
         _audio_context: &mut AudioContext,
@@ -145,8 +145,8 @@ impl Scene for ScoreHistoryScene {
                     while let Some(item) = score_list_event.next(&ui_cell) {
                         let y = &x[item.i];
                         let s = format!(
-                            "Score: {}\nAccuracy: {:.2}%\nHit: {}\nMiss: {}\nAverage hit time{:.2}s\n{}\n\n",
-                            y.score, y.accuracy, y.hit, y.miss, y.avg_hit_time, y.created_at
+                            "Score: {}\nAccuracy: {:.2}%\nHit: {}\nMiss: {}\nAverage hit time{:.2}s\nEnvironment: {}\n{}\n\n",
+                            y.score, y.accuracy, y.hit, y.miss, y.avg_hit_time, y.background_type.get_name(), y.created_at
                         );
                         let text = Text::new(&s);
                         item.set(text, &mut ui_cell);
@@ -157,8 +157,8 @@ impl Scene for ScoreHistoryScene {
                     while let Some(item) = score_list_event.next(&ui_cell) {
                         let y = &x[item.i];
                         let s = format!(
-                            "Score: {}\nAccuracy: {:.2}%\nHit: {}\nMiss: {}\nAverage hit time: {:.2}s\nFake target hit: {}\nRunning time: {:02}:{:02}\n{}\n\n",
-                            y.score, y.accuracy, y.hit, y.miss, y.avg_hit_time, y.hit_fake_target, (y.running_time / 60.0) as i32, (y.running_time % 60.0) as i32, y.created_at
+                            "Score: {}\nAccuracy: {:.2}%\nHit: {}\nMiss: {}\nAverage hit time: {:.2}s\nFake target hit: {}\nRunning time: {:02}:{:02}\nEnvironment: {}\n{}\n\n",
+                            y.score, y.accuracy, y.hit, y.miss, y.avg_hit_time, y.hit_fake_target, (y.running_time / 60.0) as i32, (y.running_time % 60.0) as i32, y.background_type.get_name(), y.created_at
                         );
                         let text = Text::new(&s);
                         item.set(text, &mut ui_cell);
@@ -169,8 +169,68 @@ impl Scene for ScoreHistoryScene {
                     while let Some(item) = score_list_event.next(&ui_cell) {
                         let y = &x[item.i];
                         let s = format!(
-                            "Score: {}\nAccuracy: {:.2}%\nHit: {}\nMiss: {}\nAverage hit time: {:.2}\nHit taken: {}\n{}\n\n",
-                            y.score, y.accuracy, y.hit, y.miss, y.avg_hit_time, y.hit_taken, y.created_at
+                            "Score: {}\nAccuracy: {:.2}%\nHit: {}\nMiss: {}\nAverage hit time: {:.2}\nHit taken: {}\nNear miss: {}\nHeadshot rate: {:.2}%\nEnvironment: {}\n{}\n\n",
+                            y.score, y.accuracy, y.hit, y.miss, y.avg_hit_time, y.hit_taken, y.near_miss, y.headshot_rate, y.background_type.get_name(), y.created_at
+                        );
+                        let text = Text::new(&s);
+                        item.set(text, &mut ui_cell);
+                    }
+                }
+                GameModeScores::Gridshot(x) => {
+                    let mut score_list_event = score_list(x.len());
+                    while let Some(item) = score_list_event.next(&ui_cell) {
+                        let y = &x[item.i];
+                        let s = format!(
+                            "Score: {}\nAccuracy: {:.2}%\nHit: {}\nMiss: {}\nTargets per second: {:.2}\nAverage angle between targets: {:.1}°\nEnvironment: {}\n{}\n\n",
+                            y.score, y.accuracy, y.hit, y.miss, y.targets_per_second, y.avg_inter_target_angle, y.background_type.get_name(), y.created_at
+                        );
+                        let text = Text::new(&s);
+                        item.set(text, &mut ui_cell);
+                    }
+                }
+                GameModeScores::Shrinking(x) => {
+                    let mut score_list_event = score_list(x.len());
+                    while let Some(item) = score_list_event.next(&ui_cell) {
+                        let y = &x[item.i];
+                        let s = format!(
+                            "Score: {}\nAccuracy: {:.2}%\nHit: {}\nMiss: {}\nAverage target radius on hit: {:.2}m\nEnvironment: {}\n{}\n\n",
+                            y.score, y.accuracy, y.hit, y.miss, y.avg_hit_radius, y.background_type.get_name(), y.created_at
+                        );
+                        let text = Text::new(&s);
+                        item.set(text, &mut ui_cell);
+                    }
+                }
+                GameModeScores::Survival(x) => {
+                    let mut score_list_event = score_list(x.len());
+                    while let Some(item) = score_list_event.next(&ui_cell) {
+                        let y = &x[item.i];
+                        let s = format!(
+                            "Waves cleared: {}\nTime survived: {:02}:{:02}\nAccuracy: {:.2}%\nHit: {}\nMiss: {}\nEnvironment: {}\n{}\n\n",
+                            y.waves_cleared, (y.time_survived / 60.0) as i32, (y.time_survived % 60.0) as i32, y.accuracy, y.hit, y.miss, y.background_type.get_name(), y.created_at
+                        );
+                        let text = Text::new(&s);
+                        item.set(text, &mut ui_cell);
+                    }
+                }
+                GameModeScores::SprayControl(x) => {
+                    let mut score_list_event = score_list(x.len());
+                    while let Some(item) = score_list_event.next(&ui_cell) {
+                        let y = &x[item.i];
+                        let s = format!(
+                            "Accuracy: {:.2}%\nHit: {}\nMiss: {}\nAverage compensation error: {:.2}°\nEnvironment: {}\n{}\n\n",
+                            y.accuracy, y.hit, y.miss, y.avg_compensation_error, y.background_type.get_name(), y.created_at
+                        );
+                        let text = Text::new(&s);
+                        item.set(text, &mut ui_cell);
+                    }
+                }
+                GameModeScores::Lead(x) => {
+                    let mut score_list_event = score_list(x.len());
+                    while let Some(item) = score_list_event.next(&ui_cell) {
+                        let y = &x[item.i];
+                        let s = format!(
+                            "Score: {}\nAccuracy: {:.2}%\nHit: {}\nMiss: {}\nAverage hit distance: {:.1}m\nEnvironment: {}\n{}\n\n",
+                            y.score, y.accuracy, y.hit, y.miss, y.avg_hit_distance, y.background_type.get_name(), y.created_at
                         );
                         let text = Text::new(&s);
                         item.set(text, &mut ui_cell);
@@ -178,7 +238,16 @@ impl Scene for ScoreHistoryScene {
                 }
             }
 
-            const MODES: &[&str; 3] = &["Classic", "Elimination", "Hit and Dodge"];
+            const MODES: &[&str; 8] = &[
+                "Classic",
+                "Elimination",
+                "Hit and Dodge",
+                "Gridshot",
+                "Shrinking Target",
+                "Survival",
+                "Spray Control",
+                "Lead the Target",
+            ];
             const DIFFICULTY: &[&str; 3] = &["Easy", "Medium", "Hard"];
 
             if let Some(new_idx) = DropDownList::new(MODES, Some(self.mode_selection))
@@ -1,17 +1,22 @@
 use conrod_core::widget::envelope_editor::EnvelopePoint;
-use conrod_core::widget::{Canvas, Scrollbar, Slider, Text};
+use conrod_core::widget::text_box::Event as TextBoxEvent;
+use conrod_core::widget::{Canvas, DropDownList, Scrollbar, Slider, Text, TextBox};
 use conrod_core::{Colorable, Dimensions, Labelable, Positionable, Sizeable, Widget};
 use std::collections::HashMap;
+use winit::event::VirtualKeyCode;
 use winit::event_loop::ControlFlow;
 
 use crate::audio::AudioContext;
 use crate::database::Database;
 use crate::gui::ConrodHandle;
+use crate::input_binding::{Action, Binding};
 use crate::input_manager::InputManager;
 
+use crate::camera::{get_fov_of_type, get_vertical_fov, Camera, FovType, FOV_TYPE_NAMES};
 use crate::renderer::Renderer;
 
 use crate::scene::{MaybeMessage, Scene, SceneOp, Value, MARGIN};
+use crate::sensitivity::{from_game_sensitivity, to_game_sensitivity, GAME_YAWS};
 use crate::util::any_sized_as_u8_slice;
 use crate::window::Window;
 use conrod_core::widget_ids;
@@ -41,8 +46,42 @@ widget_ids! {
         ambient_occlusion_sample_slider,
 
         mouse_sensitivity_canvas,
-        mouse_sensitivity_slider_label,
-        mouse_sensitivity_slider,
+        mouse_sensitivity_label,
+        mouse_sensitivity_text_box,
+
+        mouse_dpi_canvas,
+        mouse_dpi_label,
+        mouse_dpi_text_box,
+
+        sensitivity_converter_canvas,
+        sensitivity_converter_label,
+        sensitivity_converter_game_list,
+        sensitivity_converter_text_box,
+
+        fov_canvas,
+        fov_label,
+        fov_type_list,
+        fov_slider,
+
+        kick_intensity_canvas,
+        kick_intensity_slider_label,
+        kick_intensity_slider,
+
+        shake_intensity_canvas,
+        shake_intensity_slider_label,
+        shake_intensity_slider,
+
+        flinch_intensity_canvas,
+        flinch_intensity_slider_label,
+        flinch_intensity_slider,
+
+        zoom_sensitivity_canvas,
+        zoom_sensitivity_slider_label,
+        zoom_sensitivity_slider,
+
+        zoom_sensitivity_matched_canvas,
+        zoom_sensitivity_matched_label,
+        zoom_sensitivity_matched_toggle,
 
         volume_canvas,
         volume_slider_label,
@@ -103,19 +142,71 @@ widget_ids! {
 
         crosshair_preview_canvas,
         crosshair_preview_image,
+
+        controls_canvas,
+        controls_canvas_scrollbar,
+        controls_header_canvas,
+        controls_label,
+        controls_message_label,
+        reset_controls_button,
+        binding_canvases[],
+        binding_labels[],
+        binding_buttons[],
     }
 }
 
 pub struct SettingsScene {
     ids: SettingsSceneIds,
+    // Action waiting for the next key or mouse button to be bound to it
+    listening: Option<Action>,
+    controls_message: String,
+    // Contents of the sensitivity text boxes while they are edited, applied on enter
+    degrees_per_count_text: String,
+    mouse_dpi_text: String,
+    game_sensitivity_text: String,
+    // Index in `GAME_YAWS` of the game the sensitivity is converted from and to
+    converter_game_idx: usize,
 }
 
 impl SettingsScene {
     pub fn new(_renderer: &mut Renderer, conrod_handle: &mut ConrodHandle) -> Self {
         Self {
             ids: SettingsSceneIds::new(conrod_handle.get_ui_mut().widget_id_generator()),
+            listening: None,
+            controls_message: String::new(),
+            degrees_per_count_text: String::new(),
+            mouse_dpi_text: String::new(),
+            game_sensitivity_text: String::new(),
+            converter_game_idx: 0,
         }
     }
+
+    // Shows the current sensitivity in every text box, dropping what wasn't applied.
+    fn reset_sensitivity_texts(&mut self, camera: &Camera) {
+        self.degrees_per_count_text = format!("{}", camera.degrees_per_count);
+        self.mouse_dpi_text = format!("{}", camera.mouse_dpi);
+        self.game_sensitivity_text = format!(
+            "{}",
+            to_game_sensitivity(self.converter_game_idx, camera.degrees_per_count)
+        );
+    }
+}
+
+// Label of a camera effect intensity slider, the lowest value turns the effect off.
+fn intensity_label(intensity: f32) -> String {
+    if intensity <= 0.0 {
+        "Off".to_string()
+    } else {
+        format!("{:.0}%", intensity * 100.0)
+    }
+}
+
+// Parses the text of a sensitivity text box, only accepting usable values.
+fn parse_positive(text: &str) -> Option<f32> {
+    text.trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite() && *value > 0.0)
 }
 
 fn settings_item_canvas() -> Canvas<'static> {
@@ -135,6 +226,8 @@ impl Scene for SettingsScene {
         renderer.is_render_gui = true;
         renderer.is_render_game = false;
 
+        self.reset_sensitivity_texts(&renderer.camera);
+
         renderer.rendering_info.reso_time.x = 200.0f32;
         renderer.rendering_info.reso_time.y = 200.0f32;
         renderer.queue.write_buffer(
@@ -148,13 +241,34 @@ impl Scene for SettingsScene {
         &mut self,
         _window: &mut Window,
         renderer: &mut Renderer,
-        _input_manager: &InputManager,
+        input_manager: &mut InputManager,
         _delta_time: f32,
         conrod_handle: &mut ConrodHandle,
         audio_context: &mut AudioContext,
         _control_flow: &mut ControlFlow,
-        _database: &mut Database,
+        database: &mut Database,
     ) -> SceneOp {
+        if let Some(action) = self.listening {
+            if let Some(binding) = input_manager.get_pressed_binding() {
+                // Escape cancels, unless it's what pause is being bound back to
+                if binding == Binding::Key(VirtualKeyCode::Escape) && action != Action::Pause {
+                    self.controls_message = String::new();
+                } else {
+                    self.controls_message = match input_manager.bindings.rebind(action, binding) {
+                        Some(other) => format!(
+                            "{} was bound to {}, it now uses {}",
+                            other.label(),
+                            binding.name(),
+                            input_manager.bindings.get(other).name()
+                        ),
+                        None => String::new(),
+                    };
+                    database.save_bindings(&input_manager.bindings);
+                }
+                self.listening = None;
+            }
+        }
+
         let crosshair_texture_id = *conrod_handle.get_image_id_map().get("crosshair").unwrap();
         let crosshair_image = conrod_handle
             .get_image_map()
@@ -203,6 +317,23 @@ impl Scene for SettingsScene {
 
         let ropa_font_id = *conrod_handle.get_font_id_map().get("ropa").unwrap();
         let mut ui_cell = conrod_handle.get_ui_mut().set_widgets();
+        for ids in [
+            &mut self.ids.binding_canvases,
+            &mut self.ids.binding_labels,
+            &mut self.ids.binding_buttons,
+        ] {
+            ids.resize(Action::ALL.len(), &mut ui_cell.widget_id_generator());
+        }
+        let mut controls_items = vec![(
+            self.ids.controls_header_canvas,
+            Canvas::new().length(120.0).pad_top(10.0).pad_bottom(10.0),
+        )];
+        controls_items.extend(
+            self.ids
+                .binding_canvases
+                .iter()
+                .map(|id| (*id, settings_item_canvas())),
+        );
         Canvas::new()
             .flow_down(&[
                 (
@@ -242,6 +373,20 @@ impl Scene for SettingsScene {
                                             // ]),
                                     ),
                                     (self.ids.mouse_sensitivity_canvas, settings_item_canvas()),
+                                    (self.ids.mouse_dpi_canvas, settings_item_canvas()),
+                                    (
+                                        self.ids.sensitivity_converter_canvas,
+                                        settings_item_canvas(),
+                                    ),
+                                    (self.ids.fov_canvas, settings_item_canvas()),
+                                    (self.ids.zoom_sensitivity_canvas, settings_item_canvas()),
+                                    (
+                                        self.ids.zoom_sensitivity_matched_canvas,
+                                        settings_item_canvas(),
+                                    ),
+                                    (self.ids.kick_intensity_canvas, settings_item_canvas()),
+                                    (self.ids.shake_intensity_canvas, settings_item_canvas()),
+                                    (self.ids.flinch_intensity_canvas, settings_item_canvas()),
                                     (self.ids.volume_canvas, settings_item_canvas()),
                                     // Center dot
                                     (self.ids.center_dot_enable_canvas, settings_item_canvas()),
@@ -259,6 +404,12 @@ impl Scene for SettingsScene {
                                     (self.ids.outer_line_offset_canvas, settings_item_canvas()),
                                 ]),
                         ),
+                        (
+                            self.ids.controls_canvas,
+                            Canvas::new()
+                                .scroll_kids_vertically()
+                                .flow_down(&controls_items),
+                        ),
                         (
                             self.ids.crosshair_preview_canvas,
                             Canvas::new().length(250.0),
@@ -274,6 +425,11 @@ impl Scene for SettingsScene {
             .h_of(self.ids.settings_canvas)
             .set(self.ids.settings_canvas_scrollbar, &mut ui_cell);
 
+        Scrollbar::y_axis(self.ids.controls_canvas)
+            .rgb(1.0, 0.0, 0.0)
+            .h_of(self.ids.controls_canvas)
+            .set(self.ids.controls_canvas_scrollbar, &mut ui_cell);
+
         Text::new("Settings")
             .font_id(ropa_font_id)
             .middle_of(self.ids.header_canvas)
@@ -322,18 +478,192 @@ impl Scene for SettingsScene {
             renderer.rendering_info.queuecount_raymarchmaxstep_aostep.z = value.round() as u32;
         }
 
-        Text::new("Mouse sensitivity")
-            .font_id(ropa_font_id)
-            .mid_top_of(self.ids.mouse_sensitivity_canvas)
-            .set(self.ids.mouse_sensitivity_slider_label, &mut ui_cell);
+        Text::new(&format!(
+            "Degrees per mouse count ({:.2} cm/360)",
+            renderer.camera.get_cm_per_360()
+        ))
+        .font_id(ropa_font_id)
+        .mid_top_of(self.ids.mouse_sensitivity_canvas)
+        .set(self.ids.mouse_sensitivity_label, &mut ui_cell);
 
-        if let Some(value) = Slider::new(renderer.camera.sensitivity, 0.1f32, 100f32)
+        let mut is_sensitivity_changed = false;
+        for event in TextBox::new(&self.degrees_per_count_text)
             .mid_bottom_of(self.ids.mouse_sensitivity_canvas)
-            .label(&format!("{:.3}", renderer.camera.sensitivity))
             .wh(Dimensions::new(200.0, 30.0))
-            .set(self.ids.mouse_sensitivity_slider, &mut ui_cell)
+            .set(self.ids.mouse_sensitivity_text_box, &mut ui_cell)
+        {
+            match event {
+                TextBoxEvent::Update(text) => self.degrees_per_count_text = text,
+                TextBoxEvent::Enter => {
+                    if let Some(value) = parse_positive(&self.degrees_per_count_text) {
+                        renderer.camera.degrees_per_count = value;
+                    }
+                    is_sensitivity_changed = true;
+                }
+            }
+        }
+
+        Text::new("Mouse DPI")
+            .font_id(ropa_font_id)
+            .mid_top_of(self.ids.mouse_dpi_canvas)
+            .set(self.ids.mouse_dpi_label, &mut ui_cell);
+
+        for event in TextBox::new(&self.mouse_dpi_text)
+            .mid_bottom_of(self.ids.mouse_dpi_canvas)
+            .wh(Dimensions::new(200.0, 30.0))
+            .set(self.ids.mouse_dpi_text_box, &mut ui_cell)
+        {
+            match event {
+                TextBoxEvent::Update(text) => self.mouse_dpi_text = text,
+                TextBoxEvent::Enter => {
+                    if let Some(value) = parse_positive(&self.mouse_dpi_text) {
+                        renderer.camera.mouse_dpi = value;
+                    }
+                    is_sensitivity_changed = true;
+                }
+            }
+        }
+
+        Text::new("Sensitivity in another game")
+            .font_id(ropa_font_id)
+            .mid_top_of(self.ids.sensitivity_converter_canvas)
+            .set(self.ids.sensitivity_converter_label, &mut ui_cell);
+
+        let game_names = GAME_YAWS.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        if let Some(new_idx) = DropDownList::new(&game_names, Some(self.converter_game_idx))
+            .bottom_left_of(self.ids.sensitivity_converter_canvas)
+            .wh(Dimensions::new(160.0, 30.0))
+            .scrollbar_next_to()
+            .set(self.ids.sensitivity_converter_game_list, &mut ui_cell)
+        {
+            self.converter_game_idx = new_idx;
+            is_sensitivity_changed = true;
+        }
+
+        // Shows the equivalent of the current sensitivity, typing one in imports it
+        for event in TextBox::new(&self.game_sensitivity_text)
+            .right_from(self.ids.sensitivity_converter_game_list, 10.0)
+            .wh(Dimensions::new(120.0, 30.0))
+            .set(self.ids.sensitivity_converter_text_box, &mut ui_cell)
+        {
+            match event {
+                TextBoxEvent::Update(text) => self.game_sensitivity_text = text,
+                TextBoxEvent::Enter => {
+                    if let Some(value) = parse_positive(&self.game_sensitivity_text) {
+                        renderer.camera.degrees_per_count =
+                            from_game_sensitivity(self.converter_game_idx, value);
+                    }
+                    is_sensitivity_changed = true;
+                }
+            }
+        }
+
+        if is_sensitivity_changed {
+            self.reset_sensitivity_texts(&renderer.camera);
+        }
+
+        let aspect_ratio = renderer.surface_and_window_config.get_aspect_ratio();
+        let vertical_fov =
+            get_vertical_fov(renderer.camera.fov, renderer.camera.fov_type, aspect_ratio);
+        Text::new(&format!(
+            "Field of view ({:.1} vertical, {:.1} on 4:3, {:.1} on 16:9)",
+            vertical_fov.to_degrees(),
+            get_fov_of_type(vertical_fov, FovType::Horizontal4By3, aspect_ratio),
+            get_fov_of_type(vertical_fov, FovType::Horizontal16By9, aspect_ratio),
+        ))
+        .font_id(ropa_font_id)
+        .mid_top_of(self.ids.fov_canvas)
+        .set(self.ids.fov_label, &mut ui_cell);
+
+        // Switching how the field of view is measured keeps the same view
+        if let Some(new_idx) =
+            DropDownList::new(FOV_TYPE_NAMES, Some(renderer.camera.fov_type as usize))
+                .bottom_left_of(self.ids.fov_canvas)
+                .wh(Dimensions::new(160.0, 30.0))
+                .scrollbar_next_to()
+                .set(self.ids.fov_type_list, &mut ui_cell)
+        {
+            renderer.camera.fov_type = FovType::from(new_idx);
+            renderer.camera.fov =
+                get_fov_of_type(vertical_fov, renderer.camera.fov_type, aspect_ratio);
+        }
+
+        if let Some(value) = Slider::new(renderer.camera.fov, 40f32, 140f32)
+            .right_from(self.ids.fov_type_list, 10.0)
+            .label(&format!("{:.1}", renderer.camera.fov))
+            .wh(Dimensions::new(200.0, 30.0))
+            .set(self.ids.fov_slider, &mut ui_cell)
+        {
+            renderer.camera.fov = value;
+        }
+
+        Text::new("Zoom sensitivity multiplier")
+            .font_id(ropa_font_id)
+            .mid_top_of(self.ids.zoom_sensitivity_canvas)
+            .set(self.ids.zoom_sensitivity_slider_label, &mut ui_cell);
+
+        if let Some(value) = Slider::new(renderer.camera.zoom_sensitivity, 0.1f32, 3f32)
+            .mid_bottom_of(self.ids.zoom_sensitivity_canvas)
+            .label(&format!("{:.2}", renderer.camera.zoom_sensitivity))
+            .wh(Dimensions::new(200.0, 30.0))
+            .set(self.ids.zoom_sensitivity_slider, &mut ui_cell)
+        {
+            renderer.camera.zoom_sensitivity = value;
+        }
+
+        Text::new("Match zoom sensitivity to monitor distance")
+            .font_id(ropa_font_id)
+            .mid_top_of(self.ids.zoom_sensitivity_matched_canvas)
+            .set(self.ids.zoom_sensitivity_matched_label, &mut ui_cell);
+
+        for value in conrod_core::widget::Toggle::new(renderer.camera.is_zoom_sensitivity_matched)
+            .mid_bottom_of(self.ids.zoom_sensitivity_matched_canvas)
+            .wh(Dimensions::new(40.0, 40.0))
+            .set(self.ids.zoom_sensitivity_matched_toggle, &mut ui_cell)
+        {
+            renderer.camera.is_zoom_sensitivity_matched = value;
+        }
+
+        Text::new("View kick")
+            .font_id(ropa_font_id)
+            .mid_top_of(self.ids.kick_intensity_canvas)
+            .set(self.ids.kick_intensity_slider_label, &mut ui_cell);
+
+        if let Some(value) = Slider::new(renderer.camera.effects.kick_intensity, 0f32, 2f32)
+            .mid_bottom_of(self.ids.kick_intensity_canvas)
+            .label(&intensity_label(renderer.camera.effects.kick_intensity))
+            .wh(Dimensions::new(200.0, 30.0))
+            .set(self.ids.kick_intensity_slider, &mut ui_cell)
+        {
+            renderer.camera.effects.kick_intensity = value;
+        }
+
+        Text::new("Screen shake")
+            .font_id(ropa_font_id)
+            .mid_top_of(self.ids.shake_intensity_canvas)
+            .set(self.ids.shake_intensity_slider_label, &mut ui_cell);
+
+        if let Some(value) = Slider::new(renderer.camera.effects.shake_intensity, 0f32, 2f32)
+            .mid_bottom_of(self.ids.shake_intensity_canvas)
+            .label(&intensity_label(renderer.camera.effects.shake_intensity))
+            .wh(Dimensions::new(200.0, 30.0))
+            .set(self.ids.shake_intensity_slider, &mut ui_cell)
+        {
+            renderer.camera.effects.shake_intensity = value;
+        }
+
+        Text::new("Hit flinch")
+            .font_id(ropa_font_id)
+            .mid_top_of(self.ids.flinch_intensity_canvas)
+            .set(self.ids.flinch_intensity_slider_label, &mut ui_cell);
+
+        if let Some(value) = Slider::new(renderer.camera.effects.flinch_intensity, 0f32, 2f32)
+            .mid_bottom_of(self.ids.flinch_intensity_canvas)
+            .label(&intensity_label(renderer.camera.effects.flinch_intensity))
+            .wh(Dimensions::new(200.0, 30.0))
+            .set(self.ids.flinch_intensity_slider, &mut ui_cell)
         {
-            renderer.camera.sensitivity = value;
+            renderer.camera.effects.flinch_intensity = value;
         }
 
         Text::new("Audio Volume")
@@ -563,6 +893,51 @@ impl Scene for SettingsScene {
             renderer.crosshair.color.z = value;
         }
 
+        Text::new("Controls")
+            .font_id(ropa_font_id)
+            .mid_top_of(self.ids.controls_header_canvas)
+            .set(self.ids.controls_label, &mut ui_cell);
+
+        Text::new(&self.controls_message)
+            .font_id(ropa_font_id)
+            .font_size(12)
+            .middle_of(self.ids.controls_header_canvas)
+            .set(self.ids.controls_message_label, &mut ui_cell);
+
+        for _press in conrod_core::widget::Button::new()
+            .label("Reset controls")
+            .mid_bottom_of(self.ids.controls_header_canvas)
+            .wh(Dimensions::new(200.0, 30.0))
+            .set(self.ids.reset_controls_button, &mut ui_cell)
+        {
+            input_manager.bindings.reset();
+            database.save_bindings(&input_manager.bindings);
+            self.listening = None;
+            self.controls_message = "Controls reset to their defaults".to_string();
+        }
+
+        for (idx, action) in Action::ALL.iter().enumerate() {
+            Text::new(action.label())
+                .font_id(ropa_font_id)
+                .mid_top_of(self.ids.binding_canvases[idx])
+                .set(self.ids.binding_labels[idx], &mut ui_cell);
+
+            let label = if self.listening == Some(*action) {
+                "Press a key or button".to_string()
+            } else {
+                input_manager.bindings.get(*action).name()
+            };
+            for _press in conrod_core::widget::Button::new()
+                .label(&label)
+                .mid_bottom_of(self.ids.binding_canvases[idx])
+                .wh(Dimensions::new(200.0, 30.0))
+                .set(self.ids.binding_buttons[idx], &mut ui_cell)
+            {
+                self.listening = Some(*action);
+                self.controls_message = String::new();
+            }
+        }
+
         conrod_core::widget::Image::new(crosshair_texture_id)
             .middle_of(self.ids.crosshair_preview_canvas)
             .wh(Dimensions::new(200.0, 200.0))
@@ -618,7 +993,15 @@ impl Scene for SettingsScene {
                 outer_line_thickness = {},\
                 outer_line_length = {},\
                 outer_line_offset = {},\
-                mouse_sensitivity = {}",
+                degrees_per_count = {},\
+                mouse_dpi = {},\
+                zoom_sensitivity = {},\
+                zoom_sensitivity_matched = {},\
+                fov = {},\
+                fov_type = {},\
+                kick_intensity = {},\
+                shake_intensity = {},\
+                flinch_intensity = {}",
                 audio_context.volume,
                 renderer.rendering_info.queuecount_raymarchmaxstep_aostep.y,
                 renderer.rendering_info.queuecount_raymarchmaxstep_aostep.z,
@@ -635,7 +1018,15 @@ impl Scene for SettingsScene {
                 renderer.crosshair.outer_line_thickness,
                 renderer.crosshair.outer_line_length,
                 renderer.crosshair.outer_line_offset,
-                renderer.camera.sensitivity,
+                renderer.camera.degrees_per_count,
+                renderer.camera.mouse_dpi,
+                renderer.camera.zoom_sensitivity,
+                renderer.camera.is_zoom_sensitivity_matched,
+                renderer.camera.fov,
+                renderer.camera.fov_type as u32,
+                renderer.camera.effects.kick_intensity,
+                renderer.camera.effects.shake_intensity,
+                renderer.camera.effects.flinch_intensity,
             ))
             .unwrap();
     }
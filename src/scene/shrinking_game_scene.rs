@@ -0,0 +1,614 @@
+use conrod_core::{color, Color, Colorable, Positionable, Sizeable, Widget};
+
+use chrono::Utc;
+use std::io::{BufReader, Cursor};
+
+use hecs::{Entity, World};
+
+use rapier3d::prelude::*;
+use winit::event_loop::ControlFlow;
+
+use crate::animation::InOutAnimation;
+use crate::audio::Sink;
+use crate::audio::{AudioContext, AUDIO_FILE_SHOOT};
+use crate::database::Database;
+use crate::entity::target::{Patrol, Shrink, SphereTarget};
+
+use crate::gui::ConrodHandle;
+use crate::input_binding::Action;
+use crate::input_manager::InputManager;
+use crate::physics::GamePhysics;
+
+use crate::renderer::render_objects::{MaterialType, ShapeType};
+use crate::renderer::rendering_info::BackgroundType;
+use crate::renderer::Renderer;
+use crate::scene::game_score_scene::{GameModeScore, GameScoreScene, ShrinkingGameScoreDisplay};
+use crate::scene::pause_scene::PauseScene;
+use crate::scene::{
+    GameDifficulty, GameState, MaybeMessage, Scene, SceneOp, Value, FINISHING_DURATION,
+    IN_SHOOT_ANIM_DURATION, MARGIN, OUT_SHOOT_ANIM_DURATION, PREPARE_DURATION,
+};
+use crate::systems::player::{init_player, setup_player_collider};
+use crate::systems::target::{enqueue_target, spawn_target, update_target};
+use crate::systems::update_player_movement::update_player_position;
+use crate::systems::wall::{enqueue_wall, spawn_wall};
+use crate::timer::Timer;
+use crate::util::clamp;
+
+use crate::weapon::{Weapon, WeaponType};
+use crate::window::Window;
+use conrod_core::widget::envelope_editor::EnvelopePoint;
+use conrod_core::widget::{Canvas, Text};
+use conrod_core::widget_ids;
+
+use crate::camera::Camera;
+use crate::character_controller::CharacterController;
+use crate::systems::shoot_ray::shoot_ray;
+use crate::systems::shootanim::shootanim;
+use nalgebra::{Rotation3, Unit, Vector3};
+use rand::distributions::Uniform;
+use rand::prelude::SmallRng;
+use rand::{Rng, SeedableRng};
+
+widget_ids! {
+    pub struct ShrinkingGameSceneIds {
+        // The main canvas
+        canvas,
+        start_duration_label,
+        ammo_label,
+
+        indicator_canvas,
+
+        duration_canvas,
+        duration_label,
+        score_canvas,
+        score_label,
+        accuracy_canvas,
+        accuracy_label,
+    }
+}
+
+pub struct Score {
+    pub hit: u16,
+    pub miss: u16,
+    pub score: i32,
+    pub total_hit_radius: f32,
+}
+
+impl Score {
+    pub fn new() -> Self {
+        Self {
+            hit: 0,
+            miss: 0,
+            score: 0,
+            total_hit_radius: 0.0,
+        }
+    }
+}
+
+pub const GAME_DURATION: f32 = 60.0;
+
+// Distance from the player at which targets are spawned.
+const SPAWN_DISTANCE: f32 = 12.0;
+// Maximum angular offset (in degrees) from the crosshair per meter of target radius.
+const OFFSET_DEGREE_PER_RADIUS: f32 = 16.0;
+
+pub struct ShrinkingGameScene {
+    ids: ShrinkingGameSceneIds,
+    world: World,
+    physics: GamePhysics,
+    player_rigid_body_handle: RigidBodyHandle,
+    player_controller: CharacterController,
+    game_state: GameState,
+    weapon: Weapon,
+    background_type: BackgroundType,
+    score: Score,
+    rng: SmallRng,
+    round_timer: Timer,
+    shoot_animation: InOutAnimation,
+    shrink: Shrink,
+    entity_to_remove: Vec<Entity>,
+    freeze: bool,
+    difficulty: GameDifficulty,
+}
+
+impl ShrinkingGameScene {
+    pub fn new(
+        _renderer: &mut Renderer,
+        conrod_handle: &mut ConrodHandle,
+        difficulty: GameDifficulty,
+    ) -> Self {
+        let mut world = World::new();
+        let mut physics = GamePhysics::new();
+
+        // Ground
+        physics
+            .collider_set
+            .insert(ColliderBuilder::new(SharedShape::cuboid(10.0, 1.0, 10.0)).build());
+
+        let player_rigid_body_handle =
+            setup_player_collider(&mut physics, Vector3::new(0.0, 2.6, 0.0));
+
+        spawn_wall(
+            &mut world,
+            &mut physics,
+            Vector3::new(0.0, 1.4, 9.5),
+            Vector3::new(10.0, 0.398, 0.5),
+        );
+        spawn_wall(
+            &mut world,
+            &mut physics,
+            Vector3::new(9.5, 1.4, 0.0),
+            Vector3::new(0.5, 0.398, 9.99),
+        );
+        spawn_wall(
+            &mut world,
+            &mut physics,
+            Vector3::new(-9.5, 1.4, 0.0),
+            Vector3::new(0.5, 0.398, 9.99),
+        );
+
+        let shrink = match difficulty {
+            GameDifficulty::Easy => Shrink {
+                start_radius: 0.6,
+                end_radius: 0.1,
+                duration: 3.0,
+            },
+            GameDifficulty::Medium => Shrink {
+                start_radius: 0.45,
+                end_radius: 0.08,
+                duration: 2.5,
+            },
+            GameDifficulty::Hard => Shrink {
+                start_radius: 0.3,
+                end_radius: 0.05,
+                duration: 2.0,
+            },
+        };
+
+        spawn_target(
+            &mut world,
+            &mut physics,
+            Vector3::new(0.0, 3.5, -SPAWN_DISTANCE),
+            SphereTarget::new_shrinking(shrink.clone(), None, Patrol::None),
+        );
+
+        Self {
+            world,
+            physics,
+            player_rigid_body_handle,
+            player_controller: CharacterController::new(),
+            ids: ShrinkingGameSceneIds::new(conrod_handle.get_ui_mut().widget_id_generator()),
+            score: Score::new(),
+            game_state: GameState::Preround,
+            weapon: Weapon::new(WeaponType::Pistol),
+            background_type: BackgroundType::Forest,
+            rng: SmallRng::from_entropy(),
+            shoot_animation: InOutAnimation::new(IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION),
+            shrink,
+            entity_to_remove: Vec::new(),
+            round_timer: Timer::new(GAME_DURATION),
+            freeze: false,
+            difficulty,
+        }
+    }
+}
+
+impl Scene for ShrinkingGameScene {
+    fn init(
+        &mut self,
+        message: MaybeMessage,
+        window: &mut Window,
+        renderer: &mut Renderer,
+        _conrod_handle: &mut ConrodHandle,
+        audio_context: &mut AudioContext,
+        _database: &mut Database,
+    ) {
+        renderer.is_render_gui = true;
+        renderer.is_render_game = true;
+
+        init_player(&mut self.physics, renderer, self.player_rigid_body_handle);
+
+        // Ground
+        let (objects, ref mut bound) = renderer.render_objects.next_static();
+        objects.position = nalgebra::Vector3::new(0.0, 0.0, 0.0);
+        objects.shape_type_material_ids.0 = ShapeType::Box;
+        objects.shape_type_material_ids.1 = MaterialType::CobblestonePaving;
+        objects.shape_data1 = nalgebra::Vector4::new(10.0, 1.0, 10.0, 0.0);
+        *bound = objects.get_bounding_sphere_radius();
+
+        // Back Wall
+        let (objects, ref mut bound) = renderer.render_objects.next_static();
+        objects.position = nalgebra::Vector3::new(0.0, 0.0, -14.0);
+        objects.shape_type_material_ids.0 = ShapeType::Box;
+        objects.shape_type_material_ids.1 = MaterialType::StoneWall;
+        objects.shape_data1 = nalgebra::Vector4::new(10.0, 8.0, 1.0, 0.0);
+        *bound = objects.get_bounding_sphere_radius();
+
+        window.set_is_cursor_grabbed(true);
+        audio_context.global_sinks_map.remove("bgm");
+
+        if let Some(m) = message {
+            if let Some(Value::I64(background_type)) = m.get("background_type") {
+                self.background_type = BackgroundType::from(*background_type as usize);
+            }
+            if let Some(Value::I64(weapon_type)) = m.get("weapon_type") {
+                self.weapon = Weapon::new(WeaponType::from(*weapon_type as usize));
+            }
+            if m.contains_key("from_pause") {
+                self.freeze = true;
+                renderer.game_renderer.render_crosshair = false;
+                self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION))
+            }
+        }
+
+        renderer.rendering_info.background_type = self.background_type;
+        renderer.rendering_info.weapon_type = self.weapon.get_type() as u32;
+    }
+
+    fn update(
+        &mut self,
+        _window: &mut Window,
+        renderer: &mut Renderer,
+        input_manager: &mut InputManager,
+        delta_time: f32,
+        conrod_handle: &mut ConrodHandle,
+        audio_context: &mut AudioContext,
+        _control_flow: &mut ControlFlow,
+        _database: &mut Database,
+    ) -> SceneOp {
+        let mut shoot_trigger = false;
+
+        let round_timer_sec = self.round_timer.get_duration();
+
+        let mut ui_cell = conrod_handle.get_ui_mut().set_widgets();
+        {
+            Canvas::new()
+                .color(Color::Rgba(0.0, 0.0, 0.0, 0.0))
+                .set(self.ids.canvas, &mut ui_cell);
+
+            Canvas::new()
+                .color(Color::Rgba(1.0, 1.0, 1.0, 0.3))
+                .mid_top_of(self.ids.canvas)
+                .flow_right(&[
+                    (
+                        self.ids.score_canvas,
+                        Canvas::new()
+                            .length_weight(0.3)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.2)),
+                    ),
+                    (
+                        self.ids.duration_canvas,
+                        Canvas::new()
+                            .length_weight(0.4)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.4)),
+                    ),
+                    (
+                        self.ids.accuracy_canvas,
+                        Canvas::new()
+                            .length_weight(0.3)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.2)),
+                    ),
+                ])
+                .wh(conrod_core::Dimensions::new(200.0, 30.0))
+                .set(self.ids.indicator_canvas, &mut ui_cell);
+
+            Text::new(&format!(
+                "{:02}:{:02}",
+                (round_timer_sec / 60.0) as i32,
+                (round_timer_sec % 60.0) as i32
+            ))
+            .color(color::BLACK)
+            .middle_of(self.ids.duration_canvas)
+            .set(self.ids.duration_label, &mut ui_cell);
+
+            Text::new(&format!("{}", self.score.score))
+                .font_size(12)
+                .color(color::BLACK)
+                .middle_of(self.ids.score_canvas)
+                .set(self.ids.score_label, &mut ui_cell);
+            Text::new(&format!(
+                "{:.2}%",
+                (self.score.hit) as f32 / (self.score.hit + self.score.miss).max(1) as f32 * 100.0
+            ))
+            .font_size(12)
+            .color(color::BLACK)
+            .middle_of(self.ids.accuracy_canvas)
+            .set(self.ids.accuracy_label, &mut ui_cell);
+        }
+
+        Text::new(&self.weapon.get_ammo_text())
+            .font_size(14)
+            .color(color::WHITE)
+            .bottom_right_with_margin_on(self.ids.canvas, MARGIN)
+            .set(self.ids.ammo_label, &mut ui_cell);
+
+        let mut game_finished = false;
+
+        let mut scene_op = SceneOp::None;
+
+        if !self.freeze {
+            renderer.camera.move_direction(input_manager.mouse_movement);
+
+            let _player_position = update_player_position(
+                delta_time,
+                input_manager,
+                &mut renderer.camera,
+                &mut self.physics,
+                self.player_rigid_body_handle,
+                &mut self.player_controller,
+            );
+        }
+
+        match self.game_state {
+            GameState::Preround => {
+                Text::new("Press any mouse key to start")
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if input_manager.is_any_mouse_press() {
+                    self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION));
+                }
+            }
+            GameState::Prepare(ref mut timer) => {
+                timer.update(delta_time);
+
+                Text::new(&format!("{:.1}", timer.get_duration()))
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if timer.is_finished() {
+                    self.freeze = false;
+                    self.game_state = GameState::Round;
+                    renderer.game_renderer.render_crosshair = true;
+                }
+            }
+            GameState::Round => {
+                self.round_timer.update(delta_time);
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
+
+                update_target(
+                    &mut self.world,
+                    &mut self.physics,
+                    delta_time,
+                    &mut self.rng,
+                );
+
+                self.target_disposal(&renderer.camera);
+
+                shootanim(
+                    &mut self.shoot_animation,
+                    &mut renderer.rendering_info,
+                    delta_time,
+                );
+
+                shoot_trigger = true;
+
+                if self.round_timer.is_finished() {
+                    self.game_state = GameState::Finishing(Timer::new(FINISHING_DURATION));
+                }
+            }
+            GameState::Finishing(ref mut timer) => {
+                renderer.game_renderer.render_crosshair = false;
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
+                timer.update(delta_time);
+
+                Text::new("Time out!")
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if timer.is_finished() {
+                    game_finished = true;
+                }
+            }
+        };
+
+        if !self.freeze {
+            self.physics.physics_pipeline.step(
+                &self.physics.gravity,
+                &self.physics.integration_parameters,
+                &mut self.physics.island_manager,
+                &mut self.physics.broad_phase,
+                &mut self.physics.narrow_phase,
+                &mut self.physics.rigid_body_set,
+                &mut self.physics.collider_set,
+                &mut self.physics.joint_set,
+                &mut self.physics.ccd_solver,
+                &(),
+                &(),
+            );
+            self.physics.query_pipeline.update(
+                &self.physics.island_manager,
+                &self.physics.rigid_body_set,
+                &self.physics.collider_set,
+            );
+
+            if shoot_trigger {
+                self.shoot(
+                    input_manager,
+                    audio_context,
+                    &mut renderer.camera,
+                    delta_time,
+                );
+            }
+        }
+
+        drop(ui_cell);
+
+        if game_finished {
+            scene_op = SceneOp::Replace(
+                Box::new(GameScoreScene::new(
+                    conrod_handle,
+                    GameModeScore::Shrinking(ShrinkingGameScoreDisplay {
+                        accuracy: (self.score.hit) as f32
+                            / (self.score.hit + self.score.miss).max(1) as f32
+                            * 100.0,
+                        hit: self.score.hit,
+                        miss: self.score.miss,
+                        score: self.score.score,
+                        avg_hit_radius: self.score.total_hit_radius / self.score.hit.max(1) as f32,
+                        created_at: Utc::now().naive_utc(),
+                        background_type: self.background_type,
+                    }),
+                    self.difficulty,
+                )),
+                None,
+            );
+        }
+
+        if input_manager.is_action_press(Action::Pause) {
+            scene_op = SceneOp::Push(Box::new(PauseScene::new(renderer, conrod_handle)), None);
+        }
+
+        scene_op
+    }
+
+    fn prerender(
+        &mut self,
+        renderer: &mut Renderer,
+        _input_manager: &InputManager,
+        _delta_time: f32,
+        _conrod_handle: &mut ConrodHandle,
+        _audio_context: &mut AudioContext,
+    ) {
+        enqueue_target(&mut self.world, &mut self.physics, renderer);
+        enqueue_wall(
+            &mut self.world,
+            &mut self.physics,
+            renderer,
+            MaterialType::StoneWall,
+        );
+    }
+
+    fn deinit(
+        &mut self,
+        window: &mut Window,
+        renderer: &mut Renderer,
+        _conrod_handle: &mut ConrodHandle,
+        _audio_context: &mut AudioContext,
+        _database: &mut Database,
+    ) {
+        renderer.rendering_info.fov_shootanim.y = 0.0;
+        renderer.camera.reset_zoom();
+        renderer.camera.effects.clear();
+        renderer.render_objects.clear();
+        renderer.game_renderer.render_crosshair = false;
+        window.set_is_cursor_grabbed(false);
+    }
+}
+
+impl ShrinkingGameScene {
+    fn shoot(
+        &mut self,
+        input_manager: &InputManager,
+        audio_context: &mut AudioContext,
+        camera: &mut Camera,
+        _delta_time: f32,
+    ) {
+        if let Some(direction) = self.weapon.fire(input_manager, camera) {
+            self.shoot_animation.trigger();
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let sink = rodio::Sink::try_new(&audio_context.output_stream_handle).unwrap();
+                sink.append(
+                    rodio::Decoder::new(BufReader::new(Cursor::new(AUDIO_FILE_SHOOT.to_vec())))
+                        .unwrap(),
+                );
+                audio_context.push(Sink::Regular(sink));
+            }
+
+            if let Some((handle, _distance)) = shoot_ray(&self.physics, camera, direction) {
+                let collider = self.physics.collider_set.get(handle).unwrap();
+
+                // The floor has no entity, hitting it is a miss.
+                let mut hit_radius = None;
+                if let Some(mut target) = Entity::from_bits(collider.user_data as u64)
+                    .and_then(|entity| self.world.get_mut::<SphereTarget>(entity).ok())
+                {
+                    if !target.is_shooted() && target.try_shoot(audio_context) {
+                        hit_radius = Some((target.get_radius(), target.get_shrink_ratio()));
+                    }
+                }
+
+                if let Some((radius, shrink_ratio)) = hit_radius {
+                    self.score.hit += 1;
+                    self.score.total_hit_radius += radius;
+                    // The smaller the target was when it got hit, the more it is worth.
+                    self.score.score += (100.0 * shrink_ratio) as i32;
+
+                    self.spawn_next(camera, radius);
+                } else {
+                    // Hit other than live target
+                    self.score.miss += 1;
+                }
+            } else {
+                self.score.miss += 1;
+            }
+        }
+    }
+
+    fn target_disposal(&mut self, camera: &Camera) {
+        let mut shrunk = false;
+        for (id, (target, collider_handle)) in
+            self.world.query_mut::<(&SphereTarget, &ColliderHandle)>()
+        {
+            if target.is_need_to_be_deleted() || target.is_shrunk() {
+                self.entity_to_remove.push(id);
+                self.physics.collider_set.remove(
+                    *collider_handle,
+                    &mut self.physics.island_manager,
+                    &mut self.physics.rigid_body_set,
+                    false,
+                );
+                shrunk |= target.is_shrunk();
+            }
+        }
+        for entity in self.entity_to_remove.iter() {
+            self.world.despawn(*entity).unwrap();
+        }
+        self.entity_to_remove.clear();
+
+        if shrunk {
+            // The target vanished before it got hit
+            self.score.miss += 1;
+            let end_radius = self.shrink.end_radius;
+            self.spawn_next(camera, end_radius);
+        }
+    }
+
+    /// Spawns a target around the crosshair, closer to it the smaller `last_radius`, the radius
+    /// the previous target had when it was hit or vanished.
+    fn spawn_next(&mut self, camera: &Camera, last_radius: f32) {
+        let max_offset = (last_radius * OFFSET_DEGREE_PER_RADIUS).to_radians();
+        let offset = self.rng.sample(Uniform::new(max_offset * 0.3, max_offset));
+        let around = self
+            .rng
+            .sample(Uniform::new(-std::f32::consts::PI, std::f32::consts::PI));
+
+        let dir = camera.get_direction();
+        let right = camera.get_direction_right();
+        let up = Unit::new_normalize(right.cross(&dir));
+        let axis =
+            Unit::new_normalize(right.into_inner() * around.sin() + up.into_inner() * around.cos());
+        let spawn_dir = Rotation3::from_axis_angle(&axis, offset) * dir.into_inner();
+
+        // Keep the target inside the arena
+        let mut pos = camera.position + spawn_dir * SPAWN_DISTANCE;
+        pos.x = clamp(pos.x, -8.5, 8.5);
+        pos.y = pos.y.max(1.5);
+        pos.z = clamp(pos.z, -12.5, 8.5);
+
+        spawn_target(
+            &mut self.world,
+            &mut self.physics,
+            pos,
+            SphereTarget::new_shrinking(self.shrink.clone(), None, Patrol::None),
+        );
+    }
+}
@@ -0,0 +1,543 @@
+use conrod_core::{color, Color, Colorable, Positionable, Sizeable, Widget};
+
+use chrono::Utc;
+use std::io::{BufReader, Cursor};
+
+use hecs::{Entity, World};
+
+use rapier3d::prelude::*;
+use winit::event_loop::ControlFlow;
+
+use crate::animation::InOutAnimation;
+use crate::audio::Sink;
+use crate::audio::{AudioContext, AUDIO_FILE_SHOOT};
+use crate::database::Database;
+use crate::entity::Wall;
+
+use crate::gui::ConrodHandle;
+use crate::input_binding::Action;
+use crate::input_manager::InputManager;
+use crate::physics::GamePhysics;
+
+use crate::renderer::render_objects::{MaterialType, ShapeType};
+use crate::renderer::rendering_info::BackgroundType;
+use crate::renderer::Renderer;
+use crate::scene::game_score_scene::{
+    GameModeScore, GameScoreScene, SprayControlGameScoreDisplay, SprayShot,
+};
+use crate::scene::pause_scene::PauseScene;
+use crate::scene::{
+    GameDifficulty, GameState, MaybeMessage, Scene, SceneOp, Value, FINISHING_DURATION,
+    IN_SHOOT_ANIM_DURATION, MARGIN, OUT_SHOOT_ANIM_DURATION, PREPARE_DURATION,
+};
+use crate::systems::player::{init_player, setup_player_collider};
+use crate::systems::update_player_movement::update_player_position;
+use crate::systems::wall::{enqueue_wall, spawn_wall};
+use crate::timer::Timer;
+
+use crate::weapon::{Weapon, WeaponType};
+use crate::window::Window;
+use conrod_core::widget::envelope_editor::EnvelopePoint;
+use conrod_core::widget::{Canvas, Text};
+use conrod_core::widget_ids;
+
+use crate::camera::Camera;
+use crate::character_controller::CharacterController;
+use crate::systems::shoot_ray::shoot_ray;
+use crate::systems::shootanim::shootanim;
+use nalgebra::{Vector2, Vector3};
+
+widget_ids! {
+    pub struct SprayControlGameSceneIds {
+        // The main canvas
+        canvas,
+        start_duration_label,
+        ammo_label,
+
+        indicator_canvas,
+
+        spray_canvas,
+        spray_label,
+        hit_canvas,
+        hit_label,
+        accuracy_canvas,
+        accuracy_label,
+    }
+}
+
+pub struct Score {
+    pub hit: u16,
+    pub miss: u16,
+    pub total_compensation_error: f32,
+    pub shot_count: u16,
+}
+
+impl Score {
+    pub fn new() -> Self {
+        Self {
+            hit: 0,
+            miss: 0,
+            total_compensation_error: 0.0,
+            shot_count: 0,
+        }
+    }
+}
+
+pub const SPRAY_COUNT: usize = 5;
+
+// Center of the target painted on the back wall, level with the player's eyes.
+const TARGET_POSITION: Vector3<f32> = Vector3::new(0.0, 2.5, -9.5);
+
+pub struct SprayControlGameScene {
+    ids: SprayControlGameSceneIds,
+    world: World,
+    physics: GamePhysics,
+    player_rigid_body_handle: RigidBodyHandle,
+    player_controller: CharacterController,
+    game_state: GameState,
+    weapon: Weapon,
+    background_type: BackgroundType,
+    score: Score,
+    shoot_animation: InOutAnimation,
+    target_radius: f32,
+    spray_start: Vector2<f32>,
+    current_spray: Vec<SprayShot>,
+    sprays: Vec<Vec<SprayShot>>,
+    freeze: bool,
+    difficulty: GameDifficulty,
+}
+
+impl SprayControlGameScene {
+    pub fn new(
+        _renderer: &mut Renderer,
+        conrod_handle: &mut ConrodHandle,
+        difficulty: GameDifficulty,
+    ) -> Self {
+        let mut world = World::new();
+        let mut physics = GamePhysics::new();
+
+        // Ground
+        physics
+            .collider_set
+            .insert(ColliderBuilder::new(SharedShape::cuboid(10.0, 1.0, 10.0)).build());
+
+        let player_rigid_body_handle =
+            setup_player_collider(&mut physics, Vector3::new(0.0, 2.6, 0.0));
+
+        // Back wall that records the impacts
+        spawn_wall(
+            &mut world,
+            &mut physics,
+            Vector3::new(0.0, 3.0, -10.0),
+            Vector3::new(6.0, 3.0, 0.5),
+        );
+
+        let target_radius = match difficulty {
+            GameDifficulty::Easy => 0.6,
+            GameDifficulty::Medium => 0.4,
+            GameDifficulty::Hard => 0.25,
+        };
+
+        Self {
+            world,
+            physics,
+            player_rigid_body_handle,
+            player_controller: CharacterController::new(),
+            ids: SprayControlGameSceneIds::new(conrod_handle.get_ui_mut().widget_id_generator()),
+            score: Score::new(),
+            game_state: GameState::Preround,
+            weapon: Weapon::new(WeaponType::Rifle).without_spread(),
+            background_type: BackgroundType::Forest,
+            shoot_animation: InOutAnimation::new(IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION),
+            target_radius,
+            spray_start: Vector2::new(0.0, 0.0),
+            current_spray: Vec::new(),
+            sprays: Vec::new(),
+            freeze: false,
+            difficulty,
+        }
+    }
+}
+
+impl Scene for SprayControlGameScene {
+    fn init(
+        &mut self,
+        message: MaybeMessage,
+        window: &mut Window,
+        renderer: &mut Renderer,
+        _conrod_handle: &mut ConrodHandle,
+        audio_context: &mut AudioContext,
+        _database: &mut Database,
+    ) {
+        renderer.is_render_gui = true;
+        renderer.is_render_game = true;
+
+        init_player(&mut self.physics, renderer, self.player_rigid_body_handle);
+
+        // Ground
+        let (objects, ref mut bound) = renderer.render_objects.next_static();
+        objects.position = nalgebra::Vector3::new(0.0, 0.0, 0.0);
+        objects.shape_type_material_ids.0 = ShapeType::Box;
+        objects.shape_type_material_ids.1 = MaterialType::CobblestonePaving;
+        objects.shape_data1 = nalgebra::Vector4::new(10.0, 1.0, 10.0, 0.0);
+        *bound = objects.get_bounding_sphere_radius();
+
+        // Target, only drawn since the impacts are taken on the wall behind it
+        let (objects, ref mut bound) = renderer.render_objects.next_static();
+        objects.position = TARGET_POSITION;
+        objects.shape_type_material_ids.0 = ShapeType::Sphere;
+        objects.shape_type_material_ids.1 = MaterialType::Target;
+        objects.shape_data1 = nalgebra::Vector4::new(self.target_radius, 0.0, 0.0, 0.0);
+        *bound = objects.get_bounding_sphere_radius();
+
+        window.set_is_cursor_grabbed(true);
+        audio_context.global_sinks_map.remove("bgm");
+
+        if let Some(m) = message {
+            if let Some(Value::I64(background_type)) = m.get("background_type") {
+                self.background_type = BackgroundType::from(*background_type as usize);
+            }
+            if let Some(Value::I64(weapon_type)) = m.get("weapon_type") {
+                self.weapon = Weapon::new(WeaponType::from(*weapon_type as usize)).without_spread();
+            }
+            if m.contains_key("from_pause") {
+                self.freeze = true;
+                renderer.game_renderer.render_crosshair = false;
+                self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION))
+            }
+        }
+
+        renderer.rendering_info.background_type = self.background_type;
+        renderer.rendering_info.weapon_type = self.weapon.get_type() as u32;
+    }
+
+    fn update(
+        &mut self,
+        _window: &mut Window,
+        renderer: &mut Renderer,
+        input_manager: &mut InputManager,
+        delta_time: f32,
+        conrod_handle: &mut ConrodHandle,
+        audio_context: &mut AudioContext,
+        _control_flow: &mut ControlFlow,
+        _database: &mut Database,
+    ) -> SceneOp {
+        let mut shoot_trigger = false;
+
+        let mut ui_cell = conrod_handle.get_ui_mut().set_widgets();
+        {
+            Canvas::new()
+                .color(Color::Rgba(0.0, 0.0, 0.0, 0.0))
+                .set(self.ids.canvas, &mut ui_cell);
+
+            Canvas::new()
+                .color(Color::Rgba(1.0, 1.0, 1.0, 0.3))
+                .mid_top_of(self.ids.canvas)
+                .flow_right(&[
+                    (
+                        self.ids.hit_canvas,
+                        Canvas::new()
+                            .length_weight(0.3)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.2)),
+                    ),
+                    (
+                        self.ids.spray_canvas,
+                        Canvas::new()
+                            .length_weight(0.4)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.4)),
+                    ),
+                    (
+                        self.ids.accuracy_canvas,
+                        Canvas::new()
+                            .length_weight(0.3)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.2)),
+                    ),
+                ])
+                .wh(conrod_core::Dimensions::new(200.0, 30.0))
+                .set(self.ids.indicator_canvas, &mut ui_cell);
+
+            Text::new(&format!(
+                "Spray {}/{}",
+                (self.sprays.len() + 1).min(SPRAY_COUNT),
+                SPRAY_COUNT
+            ))
+            .color(color::BLACK)
+            .middle_of(self.ids.spray_canvas)
+            .set(self.ids.spray_label, &mut ui_cell);
+
+            Text::new(&format!("{}", self.score.hit))
+                .font_size(12)
+                .color(color::BLACK)
+                .middle_of(self.ids.hit_canvas)
+                .set(self.ids.hit_label, &mut ui_cell);
+            Text::new(&format!(
+                "{:.2}%",
+                (self.score.hit) as f32 / (self.score.hit + self.score.miss).max(1) as f32 * 100.0
+            ))
+            .font_size(12)
+            .color(color::BLACK)
+            .middle_of(self.ids.accuracy_canvas)
+            .set(self.ids.accuracy_label, &mut ui_cell);
+        }
+
+        Text::new(&self.weapon.get_ammo_text())
+            .font_size(14)
+            .color(color::WHITE)
+            .bottom_right_with_margin_on(self.ids.canvas, MARGIN)
+            .set(self.ids.ammo_label, &mut ui_cell);
+
+        let mut game_finished = false;
+
+        let mut scene_op = SceneOp::None;
+
+        if !self.freeze {
+            renderer.camera.move_direction(input_manager.mouse_movement);
+
+            let _player_position = update_player_position(
+                delta_time,
+                input_manager,
+                &mut renderer.camera,
+                &mut self.physics,
+                self.player_rigid_body_handle,
+                &mut self.player_controller,
+            );
+        }
+
+        match self.game_state {
+            GameState::Preround => {
+                Text::new("Hold fire on the target and pull against the recoil")
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if input_manager.is_any_mouse_press() {
+                    self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION));
+                }
+            }
+            GameState::Prepare(ref mut timer) => {
+                timer.update(delta_time);
+
+                Text::new(&format!("{:.1}", timer.get_duration()))
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if timer.is_finished() {
+                    self.freeze = false;
+                    self.game_state = GameState::Round;
+                    renderer.game_renderer.render_crosshair = true;
+                }
+            }
+            GameState::Round => {
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
+
+                // A spray is over once the recoil pattern resets or the magazine runs dry.
+                if !self.current_spray.is_empty()
+                    && (self.weapon.is_recoil_reset() || self.weapon.is_reloading())
+                {
+                    self.sprays.push(std::mem::take(&mut self.current_spray));
+                }
+
+                shootanim(
+                    &mut self.shoot_animation,
+                    &mut renderer.rendering_info,
+                    delta_time,
+                );
+
+                shoot_trigger = true;
+
+                if self.sprays.len() >= SPRAY_COUNT {
+                    shoot_trigger = false;
+                    self.game_state = GameState::Finishing(Timer::new(FINISHING_DURATION));
+                }
+            }
+            GameState::Finishing(ref mut timer) => {
+                renderer.game_renderer.render_crosshair = false;
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
+                timer.update(delta_time);
+
+                Text::new("Drill complete!")
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if timer.is_finished() {
+                    game_finished = true;
+                }
+            }
+        };
+
+        if !self.freeze {
+            self.physics.physics_pipeline.step(
+                &self.physics.gravity,
+                &self.physics.integration_parameters,
+                &mut self.physics.island_manager,
+                &mut self.physics.broad_phase,
+                &mut self.physics.narrow_phase,
+                &mut self.physics.rigid_body_set,
+                &mut self.physics.collider_set,
+                &mut self.physics.joint_set,
+                &mut self.physics.ccd_solver,
+                &(),
+                &(),
+            );
+            self.physics.query_pipeline.update(
+                &self.physics.island_manager,
+                &self.physics.rigid_body_set,
+                &self.physics.collider_set,
+            );
+
+            if shoot_trigger {
+                self.shoot(
+                    input_manager,
+                    audio_context,
+                    &mut renderer.camera,
+                    delta_time,
+                );
+            }
+        }
+
+        drop(ui_cell);
+
+        if game_finished {
+            scene_op = SceneOp::Replace(
+                Box::new(GameScoreScene::new(
+                    conrod_handle,
+                    GameModeScore::SprayControl(SprayControlGameScoreDisplay {
+                        accuracy: (self.score.hit) as f32
+                            / (self.score.hit + self.score.miss).max(1) as f32
+                            * 100.0,
+                        hit: self.score.hit,
+                        miss: self.score.miss,
+                        avg_compensation_error: self.score.total_compensation_error
+                            / self.score.shot_count.max(1) as f32,
+                        created_at: Utc::now().naive_utc(),
+                        background_type: self.background_type,
+                        target_radius: (self.target_radius / -TARGET_POSITION.z)
+                            .atan()
+                            .to_degrees(),
+                        last_spray: self.sprays.pop().unwrap_or_default(),
+                    }),
+                    self.difficulty,
+                )),
+                None,
+            );
+        }
+
+        if input_manager.is_action_press(Action::Pause) {
+            scene_op = SceneOp::Push(Box::new(PauseScene::new(renderer, conrod_handle)), None);
+        }
+
+        scene_op
+    }
+
+    fn prerender(
+        &mut self,
+        renderer: &mut Renderer,
+        _input_manager: &InputManager,
+        _delta_time: f32,
+        _conrod_handle: &mut ConrodHandle,
+        _audio_context: &mut AudioContext,
+    ) {
+        enqueue_wall(
+            &mut self.world,
+            &mut self.physics,
+            renderer,
+            MaterialType::StoneWall,
+        );
+    }
+
+    fn deinit(
+        &mut self,
+        window: &mut Window,
+        renderer: &mut Renderer,
+        _conrod_handle: &mut ConrodHandle,
+        _audio_context: &mut AudioContext,
+        _database: &mut Database,
+    ) {
+        renderer.rendering_info.fov_shootanim.y = 0.0;
+        renderer.camera.reset_zoom();
+        renderer.camera.effects.clear();
+        renderer.render_objects.clear();
+        renderer.game_renderer.render_crosshair = false;
+        window.set_is_cursor_grabbed(false);
+    }
+}
+
+impl SprayControlGameScene {
+    fn shoot(
+        &mut self,
+        input_manager: &InputManager,
+        audio_context: &mut AudioContext,
+        camera: &mut Camera,
+        _delta_time: f32,
+    ) {
+        let is_new_spray = self.weapon.is_recoil_reset();
+        let view_angles = camera.get_view_angles();
+        // The kick of this shot doesn't need compensating yet, only the ones before it.
+        let ideal = if is_new_spray {
+            Vector2::new(0.0, 0.0)
+        } else {
+            -camera.get_recoil()
+        };
+
+        if let Some(direction) = self.weapon.fire(input_manager, camera) {
+            self.shoot_animation.trigger();
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let sink = rodio::Sink::try_new(&audio_context.output_stream_handle).unwrap();
+                sink.append(
+                    rodio::Decoder::new(BufReader::new(Cursor::new(AUDIO_FILE_SHOOT.to_vec())))
+                        .unwrap(),
+                );
+                audio_context.push(Sink::Regular(sink));
+            }
+
+            if is_new_spray {
+                self.spray_start = view_angles;
+            }
+            // Whatever moved the view besides the recoil is the player's own compensation.
+            let actual = camera.get_view_angles() - self.spray_start - camera.get_recoil();
+
+            let mut impact = None;
+            if let Some((handle, distance)) = shoot_ray(&self.physics, camera, direction) {
+                let collider = self.physics.collider_set.get(handle).unwrap();
+                // The floor has no entity, the spray often ends up there.
+                let is_wall = Entity::from_bits(collider.user_data as u64)
+                    .is_some_and(|entity| self.world.get::<Wall>(entity).is_ok());
+                if is_wall {
+                    // The ray starts one unit in front of the camera.
+                    let point = camera.position + direction.into_inner() * (1.0 + distance);
+                    let offset = point.xy() - TARGET_POSITION.xy();
+                    let wall_distance = (TARGET_POSITION.z - camera.position.z).abs();
+                    impact = Some(Vector2::new(
+                        (offset.x / wall_distance).atan().to_degrees(),
+                        (offset.y / wall_distance).atan().to_degrees(),
+                    ));
+
+                    if offset.norm() <= self.target_radius {
+                        self.score.hit += 1;
+                    } else {
+                        self.score.miss += 1;
+                    }
+                } else {
+                    self.score.miss += 1;
+                }
+            } else {
+                self.score.miss += 1;
+            }
+
+            self.score.total_compensation_error += (actual - ideal).norm();
+            self.score.shot_count += 1;
+            self.current_spray.push(SprayShot {
+                // Positive yaw turns the view left, the overlay wants right to be positive.
+                ideal: Vector2::new(-ideal.x, ideal.y),
+                actual: Vector2::new(-actual.x, actual.y),
+                impact,
+            });
+        }
+    }
+}
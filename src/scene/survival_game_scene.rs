@@ -0,0 +1,768 @@
+use conrod_core::{color, Color, Colorable, Positionable, Sizeable, Widget};
+
+use chrono::Utc;
+use conrod_core::widget::envelope_editor::EnvelopePoint;
+use conrod_core::widget::{Canvas, Text};
+use std::io::{BufReader, Cursor};
+
+use hecs::{Entity, World};
+
+use rapier3d::prelude::*;
+use winit::event_loop::ControlFlow;
+
+use crate::animation::InOutAnimation;
+use crate::audio::Sink;
+use crate::audio::{AudioContext, AUDIO_FILE_SHOOT};
+use crate::database::Database;
+use crate::entity::enemy::behaviour::{load_archetypes, EnemyArchetype};
+use crate::entity::enemy::gunman::{Bullet, Gunman, GunmanAccuracy};
+use crate::entity::enemy::scripted::ScriptedEnemy;
+use crate::entity::enemy::swordman::Swordman;
+use crate::entity::enemy::{HitZone, SHOT_DAMAGE};
+
+use crate::gui::ConrodHandle;
+use crate::input_binding::Action;
+use crate::input_manager::InputManager;
+use crate::navigation::NavGrid;
+use crate::physics::GamePhysics;
+use crate::renderer::render_objects::MaterialType;
+use crate::renderer::render_objects::ShapeType;
+use crate::renderer::rendering_info::BackgroundType;
+use crate::renderer::Renderer;
+use crate::scene::game_score_scene::{GameModeScore, GameScoreScene, SurvivalGameScoreDisplay};
+use crate::scene::pause_scene::PauseScene;
+use crate::scene::{
+    GameDifficulty, GameState, MaybeMessage, Scene, SceneOp, Value, FINISHING_DURATION,
+    IN_SHOOT_ANIM_DURATION, MARGIN, OUT_SHOOT_ANIM_DURATION, PREPARE_DURATION,
+};
+use crate::systems::gunman::{
+    despawn_dead_gunmans, enqueue_bullet, enqueue_gunman, spawn_gunman, update_gunmans,
+};
+use crate::systems::player::{init_player, setup_player_collider};
+use crate::systems::scripted_enemy::{
+    despawn_dead_scripted_enemies, enqueue_scripted_enemy, spawn_scripted_enemy,
+    update_scripted_enemies,
+};
+use crate::systems::swordman::{
+    despawn_dead_swordmans, enqueue_swordman, spawn_swordman, update_swordmans,
+};
+use crate::systems::update_player_movement::update_player_position;
+use crate::systems::wall::{enqueue_wall, spawn_wall};
+use crate::timer::{Stopwatch, Timer};
+
+use crate::weapon::{Weapon, WeaponType};
+use crate::window::Window;
+use conrod_core::widget_ids;
+
+use nalgebra::{Vector2, Vector3};
+
+use crate::camera::Camera;
+use crate::character_controller::CharacterController;
+use crate::systems::shoot_ray::shoot_ray;
+use crate::systems::shootanim::shootanim;
+use rand::prelude::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+
+widget_ids! {
+    pub struct SurvivalGameSceneIds {
+        // The main canvas
+        canvas,
+        start_duration_label,
+        ammo_label,
+        wave_label,
+
+        indicator_canvas,
+
+        duration_canvas,
+        duration_label,
+        wave_canvas,
+        wave_indicator_label,
+        health_canvas,
+        health_label,
+    }
+}
+
+pub struct Score {
+    pub hit: u16,
+    pub miss: u16,
+    pub waves_cleared: u16,
+    pub bullet_hit_taken: u16,
+    pub sword_hit_taken: u16,
+}
+
+impl Score {
+    pub fn new() -> Self {
+        Self {
+            hit: 0,
+            miss: 0,
+            waves_cleared: 0,
+            bullet_hit_taken: 0,
+            sword_hit_taken: 0,
+        }
+    }
+}
+
+pub const PLAYER_HEALTH: f32 = 100.0;
+pub const BULLET_DAMAGE: f32 = 10.0;
+pub const SWORD_DAMAGE: f32 = 20.0;
+pub const INTERMISSION_DURATION: f32 = 5.0;
+// Enemies enter the arena just inside the walls.
+pub const SPAWN_EDGE: f32 = 8.0;
+
+pub enum WaveState {
+    Fighting,
+    Intermission(Timer),
+}
+
+/// Enemy composition and tuning of a single wave.
+pub struct Wave {
+    pub gunman_count: usize,
+    pub swordman_count: usize,
+    // Enemies of a random archetype, see `EnemyArchetype`
+    pub scripted_count: usize,
+    pub gunman_speed: f32,
+    pub gunman_focus_time: f32,
+    pub swordman_speed: f32,
+}
+
+impl Wave {
+    /// Waves are numbered from 1. Every wave adds enemies and makes them faster, while the
+    /// gunmen spend less time aiming before they shoot.
+    pub fn new(number: u16, difficulty: GameDifficulty) -> Self {
+        let n = (number - 1) as f32;
+        let (base_gunman_count, base_focus_time, base_speed) = match difficulty {
+            GameDifficulty::Easy => (1, 0.5, 2.5),
+            GameDifficulty::Medium => (2, 0.3, 3.0),
+            GameDifficulty::Hard => (2, 0.1, 3.5),
+        };
+        Self {
+            gunman_count: base_gunman_count + number as usize / 2,
+            swordman_count: (number as usize - 1).min(6),
+            scripted_count: (number as usize).saturating_sub(2).min(4),
+            gunman_speed: (base_speed + n * 0.25).min(6.0),
+            gunman_focus_time: (base_focus_time - n * 0.05).max(0.0),
+            swordman_speed: (base_speed + n * 0.2).min(5.0),
+        }
+    }
+}
+
+pub struct SurvivalGameScene {
+    ids: SurvivalGameSceneIds,
+    world: World,
+    physics: GamePhysics,
+    player_rigid_body_handle: RigidBodyHandle,
+    player_controller: CharacterController,
+    nav_grid: NavGrid,
+    archetypes: Vec<Arc<EnemyArchetype>>,
+    weapon: Weapon,
+    background_type: BackgroundType,
+    score: Score,
+    health: f32,
+    wave_number: u16,
+    wave_state: WaveState,
+    survived_time: Stopwatch,
+    rng: SmallRng,
+    shoot_animation: InOutAnimation,
+    entity_to_remove: Vec<Entity>,
+    freeze: bool,
+    game_state: GameState,
+    difficulty: GameDifficulty,
+}
+
+impl SurvivalGameScene {
+    pub fn new(
+        _renderer: &mut Renderer,
+        conrod_handle: &mut ConrodHandle,
+        difficulty: GameDifficulty,
+    ) -> Self {
+        let mut world = World::new();
+        let mut physics = GamePhysics::new();
+
+        // Ground
+        physics
+            .collider_set
+            .insert(ColliderBuilder::new(SharedShape::cuboid(1000.0, 1.0, 1000.0)).build());
+
+        let player_rigid_body_handle =
+            setup_player_collider(&mut physics, Vector3::new(0.0, 2.6, 0.0));
+
+        spawn_wall(
+            &mut world,
+            &mut physics,
+            Vector3::new(0.0, 1.4, -9.5),
+            Vector3::new(10.0, 0.398, 0.5),
+        );
+        spawn_wall(
+            &mut world,
+            &mut physics,
+            Vector3::new(0.0, 1.4, 9.5),
+            Vector3::new(10.0, 0.398, 0.5),
+        );
+        spawn_wall(
+            &mut world,
+            &mut physics,
+            Vector3::new(9.5, 1.4, 0.0),
+            Vector3::new(0.5, 0.398, 9.99),
+        );
+        spawn_wall(
+            &mut world,
+            &mut physics,
+            Vector3::new(-9.5, 1.4, 0.0),
+            Vector3::new(0.5, 0.398, 9.99),
+        );
+
+        let nav_grid = NavGrid::new(&physics, Vector2::new(0.0, 0.0), 10.0);
+
+        let mut scene = Self {
+            world,
+            physics,
+            player_rigid_body_handle,
+            player_controller: CharacterController::new(),
+            nav_grid,
+            archetypes: load_archetypes(),
+            ids: SurvivalGameSceneIds::new(conrod_handle.get_ui_mut().widget_id_generator()),
+            score: Score::new(),
+            health: PLAYER_HEALTH,
+            wave_number: 1,
+            wave_state: WaveState::Fighting,
+            survived_time: Stopwatch::new(),
+            weapon: Weapon::new(WeaponType::Rifle),
+            background_type: BackgroundType::Forest,
+            rng: SmallRng::from_entropy(),
+            shoot_animation: InOutAnimation::new(IN_SHOOT_ANIM_DURATION, OUT_SHOOT_ANIM_DURATION),
+            entity_to_remove: Vec::new(),
+            freeze: false,
+            game_state: GameState::Preround,
+            difficulty,
+        };
+        scene.spawn_wave();
+        scene
+    }
+}
+
+impl Scene for SurvivalGameScene {
+    fn init(
+        &mut self,
+        message: MaybeMessage,
+        window: &mut Window,
+        renderer: &mut Renderer,
+        _conrod_handle: &mut ConrodHandle,
+        audio_context: &mut AudioContext,
+        _database: &mut Database,
+    ) {
+        renderer.is_render_gui = true;
+        renderer.is_render_game = true;
+
+        init_player(&mut self.physics, renderer, self.player_rigid_body_handle);
+
+        // Ground
+        let (objects, ref mut bound) = renderer.render_objects.next_static();
+        objects.position = nalgebra::Vector3::new(0.0, 0.0, 0.0);
+        objects.shape_type_material_ids.0 = ShapeType::Box;
+        objects.shape_type_material_ids.1 = MaterialType::CobblestonePaving;
+        objects.shape_data1 = nalgebra::Vector4::new(10.0, 1.0, 10.0, 0.0);
+        *bound = objects.get_bounding_sphere_radius();
+
+        window.set_is_cursor_grabbed(true);
+        audio_context.global_sinks_map.remove("bgm");
+
+        if let Some(m) = message {
+            if let Some(Value::I64(background_type)) = m.get("background_type") {
+                self.background_type = BackgroundType::from(*background_type as usize);
+            }
+            if let Some(Value::I64(weapon_type)) = m.get("weapon_type") {
+                self.weapon = Weapon::new(WeaponType::from(*weapon_type as usize));
+            }
+            if m.contains_key("from_pause") {
+                self.freeze = true;
+                renderer.game_renderer.render_crosshair = false;
+                self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION))
+            }
+        }
+
+        renderer.rendering_info.background_type = self.background_type;
+        renderer.rendering_info.weapon_type = self.weapon.get_type() as u32;
+    }
+
+    fn update(
+        &mut self,
+        _window: &mut Window,
+        renderer: &mut Renderer,
+        input_manager: &mut InputManager,
+        delta_time: f32,
+        conrod_handle: &mut ConrodHandle,
+        audio_context: &mut AudioContext,
+        _control_flow: &mut ControlFlow,
+        _database: &mut Database,
+    ) -> SceneOp {
+        let mut shoot_trigger = false;
+
+        let survived_time_sec = self.survived_time.get_duration();
+
+        let mut ui_cell = conrod_handle.get_ui_mut().set_widgets();
+        {
+            Canvas::new()
+                .color(Color::Rgba(0.0, 0.0, 0.0, 0.0))
+                .set(self.ids.canvas, &mut ui_cell);
+
+            Canvas::new()
+                .color(Color::Rgba(1.0, 1.0, 1.0, 0.3))
+                .mid_top_of(self.ids.canvas)
+                .flow_right(&[
+                    (
+                        self.ids.wave_canvas,
+                        Canvas::new()
+                            .length_weight(0.3)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.2)),
+                    ),
+                    (
+                        self.ids.duration_canvas,
+                        Canvas::new()
+                            .length_weight(0.4)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.4)),
+                    ),
+                    (
+                        self.ids.health_canvas,
+                        Canvas::new()
+                            .length_weight(0.3)
+                            .color(Color::Rgba(1.0, 1.0, 1.0, 0.2)),
+                    ),
+                ])
+                .wh(conrod_core::Dimensions::new(200.0, 30.0))
+                .set(self.ids.indicator_canvas, &mut ui_cell);
+
+            Text::new(&format!(
+                "{:02}:{:02}",
+                (survived_time_sec / 60.0) as i32,
+                (survived_time_sec % 60.0) as i32
+            ))
+            .color(color::BLACK)
+            .middle_of(self.ids.duration_canvas)
+            .set(self.ids.duration_label, &mut ui_cell);
+
+            Text::new(&format!("Wave {}", self.wave_number))
+                .font_size(12)
+                .color(color::BLACK)
+                .middle_of(self.ids.wave_canvas)
+                .set(self.ids.wave_indicator_label, &mut ui_cell);
+            Text::new(&format!("HP {}", self.health.max(0.0) as i32))
+                .font_size(12)
+                .color(color::BLACK)
+                .middle_of(self.ids.health_canvas)
+                .set(self.ids.health_label, &mut ui_cell);
+        }
+
+        Text::new(&self.weapon.get_ammo_text())
+            .font_size(14)
+            .color(color::WHITE)
+            .bottom_right_with_margin_on(self.ids.canvas, MARGIN)
+            .set(self.ids.ammo_label, &mut ui_cell);
+
+        let mut game_finished = false;
+
+        let mut scene_op = SceneOp::None;
+
+        if !self.freeze {
+            renderer.camera.move_direction(input_manager.mouse_movement);
+
+            let _player_position = update_player_position(
+                delta_time,
+                input_manager,
+                &mut renderer.camera,
+                &mut self.physics,
+                self.player_rigid_body_handle,
+                &mut self.player_controller,
+            );
+        }
+
+        match self.game_state {
+            GameState::Preround => {
+                Text::new("Press any mouse key to start")
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if input_manager.is_any_mouse_press() {
+                    self.game_state = GameState::Prepare(Timer::new(PREPARE_DURATION));
+                }
+            }
+            GameState::Prepare(ref mut timer) => {
+                timer.update(delta_time);
+
+                Text::new(&format!("{:.1}", timer.get_duration()))
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if timer.is_finished() {
+                    self.freeze = false;
+                    self.game_state = GameState::Round;
+                    renderer.game_renderer.render_crosshair = true;
+                }
+            }
+            GameState::Round => {
+                self.survived_time.update(delta_time);
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
+
+                update_gunmans(
+                    &mut self.world,
+                    &mut self.physics,
+                    delta_time,
+                    &renderer.camera.position,
+                    &self.player_controller.get_velocity(),
+                    &self.nav_grid,
+                    &mut self.rng,
+                );
+                let prev_sword_hit_taken = self.score.sword_hit_taken;
+                update_swordmans(
+                    &mut self.world,
+                    &mut self.physics,
+                    &mut self.score.sword_hit_taken,
+                    delta_time,
+                    &renderer.camera.position,
+                );
+                update_scripted_enemies(
+                    &mut self.world,
+                    &mut self.physics,
+                    &mut self.score.sword_hit_taken,
+                    delta_time,
+                    &renderer.camera.position,
+                    &self.player_controller.get_velocity(),
+                    &self.nav_grid,
+                    &mut self.rng,
+                );
+                self.health -=
+                    (self.score.sword_hit_taken - prev_sword_hit_taken) as f32 * SWORD_DAMAGE;
+                if self.score.sword_hit_taken > prev_sword_hit_taken {
+                    renderer.camera.effects.add_flinch();
+                }
+
+                despawn_dead_gunmans(&mut self.world, &mut self.physics);
+                despawn_dead_swordmans(&mut self.world, &mut self.physics);
+                despawn_dead_scripted_enemies(&mut self.world, &mut self.physics);
+
+                match self.wave_state {
+                    WaveState::Fighting => {
+                        if self.remaining_enemy_count() == 0 {
+                            self.score.waves_cleared += 1;
+                            self.wave_state =
+                                WaveState::Intermission(Timer::new(INTERMISSION_DURATION));
+                        }
+                    }
+                    WaveState::Intermission(ref mut timer) => {
+                        timer.update(delta_time);
+
+                        Text::new(&format!(
+                            "Wave {} cleared\nNext wave in {:.1}",
+                            self.wave_number,
+                            timer.get_duration()
+                        ))
+                        .center_justify()
+                        .align_middle_x_of(self.ids.canvas)
+                        .align_middle_y_of(self.ids.canvas)
+                        .set(self.ids.wave_label, &mut ui_cell);
+
+                        if timer.is_finished() {
+                            self.wave_number += 1;
+                            self.wave_state = WaveState::Fighting;
+                            self.spawn_wave();
+                        }
+                    }
+                };
+
+                shootanim(
+                    &mut self.shoot_animation,
+                    &mut renderer.rendering_info,
+                    delta_time,
+                );
+
+                shoot_trigger = true;
+
+                if self.health <= 0.0 {
+                    self.game_state = GameState::Finishing(Timer::new(FINISHING_DURATION));
+                }
+            }
+            GameState::Finishing(ref mut timer) => {
+                renderer.game_renderer.render_crosshair = false;
+                self.weapon
+                    .update(input_manager, &mut renderer.camera, delta_time);
+                timer.update(delta_time);
+
+                Text::new("Game over!")
+                    .align_middle_x_of(self.ids.canvas)
+                    .align_middle_y_of(self.ids.canvas)
+                    .set(self.ids.start_duration_label, &mut ui_cell);
+
+                if timer.is_finished() {
+                    game_finished = true;
+                }
+            }
+        };
+
+        if !self.freeze {
+            self.physics.physics_pipeline.step(
+                &self.physics.gravity,
+                &self.physics.integration_parameters,
+                &mut self.physics.island_manager,
+                &mut self.physics.broad_phase,
+                &mut self.physics.narrow_phase,
+                &mut self.physics.rigid_body_set,
+                &mut self.physics.collider_set,
+                &mut self.physics.joint_set,
+                &mut self.physics.ccd_solver,
+                &(),
+                &self.physics.event_handler,
+            );
+            self.physics.query_pipeline.update(
+                &self.physics.island_manager,
+                &self.physics.rigid_body_set,
+                &self.physics.collider_set,
+            );
+
+            if shoot_trigger {
+                self.shoot(
+                    input_manager,
+                    audio_context,
+                    &mut renderer.camera,
+                    delta_time,
+                );
+            }
+        }
+
+        self.bullet_disposal(&mut renderer.camera);
+
+        drop(ui_cell);
+
+        if game_finished {
+            scene_op = SceneOp::Replace(
+                Box::new(GameScoreScene::new(
+                    conrod_handle,
+                    GameModeScore::Survival(SurvivalGameScoreDisplay {
+                        accuracy: self.score.hit as f32
+                            / (self.score.hit + self.score.miss).max(1) as f32
+                            * 100.0,
+                        hit: self.score.hit,
+                        miss: self.score.miss,
+                        waves_cleared: self.score.waves_cleared,
+                        time_survived: self.survived_time.get_duration(),
+                        created_at: Utc::now().naive_utc(),
+                        background_type: self.background_type,
+                    }),
+                    self.difficulty,
+                )),
+                None,
+            );
+        }
+
+        if input_manager.is_action_press(Action::Pause) {
+            scene_op = SceneOp::Push(Box::new(PauseScene::new(renderer, conrod_handle)), None);
+        }
+
+        scene_op
+    }
+
+    fn prerender(
+        &mut self,
+        renderer: &mut Renderer,
+        _input_manager: &InputManager,
+        _delta_time: f32,
+        _conrod_handle: &mut ConrodHandle,
+        _audio_context: &mut AudioContext,
+    ) {
+        enqueue_gunman(&mut self.world, &mut self.physics, renderer);
+        enqueue_bullet(&mut self.world, &mut self.physics, renderer);
+        enqueue_swordman(&mut self.world, &mut self.physics, renderer);
+        enqueue_scripted_enemy(&mut self.world, &mut self.physics, renderer);
+        enqueue_wall(
+            &mut self.world,
+            &mut self.physics,
+            renderer,
+            MaterialType::StoneWall,
+        );
+    }
+
+    fn deinit(
+        &mut self,
+        window: &mut Window,
+        renderer: &mut Renderer,
+        _conrod_handle: &mut ConrodHandle,
+        _audio_context: &mut AudioContext,
+        _database: &mut Database,
+    ) {
+        renderer.rendering_info.fov_shootanim.y = 0.0;
+        renderer.camera.reset_zoom();
+        renderer.camera.effects.clear();
+        renderer.render_objects.clear();
+        window.set_is_cursor_grabbed(false);
+    }
+}
+
+impl SurvivalGameScene {
+    fn spawn_wave(&mut self) {
+        let wave = Wave::new(self.wave_number, self.difficulty);
+        for _ in 0..wave.gunman_count {
+            let position = self.random_edge_position();
+            let gunman = Gunman::new(
+                wave.gunman_focus_time,
+                wave.gunman_speed,
+                GunmanAccuracy::new(self.difficulty),
+            );
+            spawn_gunman(&mut self.world, &mut self.physics, position, gunman);
+        }
+        for _ in 0..wave.swordman_count {
+            let position = self.random_edge_position();
+            spawn_swordman(
+                &mut self.world,
+                &mut self.physics,
+                position,
+                Swordman::new(wave.swordman_speed),
+            );
+        }
+        for _ in 0..wave.scripted_count {
+            let position = self.random_edge_position();
+            let archetype = self.archetypes[self.rng.gen_range(0..self.archetypes.len())].clone();
+            spawn_scripted_enemy(
+                &mut self.world,
+                &mut self.physics,
+                position,
+                ScriptedEnemy::new(archetype, GunmanAccuracy::new(self.difficulty)),
+            );
+        }
+    }
+
+    // Picks a random point along one of the four arena edges.
+    fn random_edge_position(&mut self) -> Vector3<f32> {
+        let along = self.rng.gen_range(-SPAWN_EDGE..SPAWN_EDGE);
+        let (x, z) = match self.rng.gen_range(0..4) {
+            0 => (along, -SPAWN_EDGE),
+            1 => (along, SPAWN_EDGE),
+            2 => (-SPAWN_EDGE, along),
+            _ => (SPAWN_EDGE, along),
+        };
+        Vector3::new(x, 2.5, z)
+    }
+
+    fn remaining_enemy_count(&mut self) -> usize {
+        self.world.query_mut::<&Gunman>().into_iter().count()
+            + self.world.query_mut::<&Swordman>().into_iter().count()
+            + self.world.query_mut::<&ScriptedEnemy>().into_iter().count()
+    }
+
+    fn shoot(
+        &mut self,
+        input_manager: &InputManager,
+        audio_context: &mut AudioContext,
+        camera: &mut Camera,
+        _delta_time: f32,
+    ) {
+        if let Some(direction) = self.weapon.fire(input_manager, camera) {
+            self.shoot_animation.trigger();
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let sink = rodio::Sink::try_new(&audio_context.output_stream_handle).unwrap();
+                sink.append(
+                    rodio::Decoder::new(BufReader::new(Cursor::new(AUDIO_FILE_SHOOT.to_vec())))
+                        .unwrap(),
+                );
+                audio_context.push(Sink::Regular(sink));
+            }
+            if let Some((handle, _distance)) = shoot_ray(&self.physics, camera, direction) {
+                let collider = self.physics.collider_set.get(handle).unwrap();
+                let zone = HitZone::from_user_data(collider.user_data);
+                let entity = Entity::from_bits(collider.user_data as u64);
+                let is_hit = match (entity, zone) {
+                    (Some(entity), Some(zone)) => {
+                        if let Ok(mut gunman) = self.world.get_mut::<Gunman>(entity) {
+                            if gunman.is_dead() {
+                                false
+                            } else {
+                                gunman.hit(SHOT_DAMAGE, zone);
+                                true
+                            }
+                        } else if let Ok(mut swordman) = self.world.get_mut::<Swordman>(entity) {
+                            if swordman.is_dead() {
+                                false
+                            } else {
+                                swordman.hit(SHOT_DAMAGE, zone);
+                                true
+                            }
+                        } else if let Ok(mut enemy) = self.world.get_mut::<ScriptedEnemy>(entity) {
+                            if enemy.is_dead() {
+                                false
+                            } else {
+                                enemy.hit(SHOT_DAMAGE, zone);
+                                true
+                            }
+                        } else {
+                            false
+                        }
+                    }
+                    _ => false,
+                };
+
+                if is_hit {
+                    self.score.hit += 1;
+                } else {
+                    self.score.miss += 1;
+                }
+            } else {
+                // Hit other than gunman & swordman
+                self.score.miss += 1;
+            }
+        }
+    }
+
+    fn bullet_disposal(&mut self, camera: &mut Camera) {
+        while let Ok(contact_event) = self.physics.contact_recv.try_recv() {
+            match contact_event {
+                ContactEvent::Started(a_collider, b_collider) => {
+                    let mut res = None;
+                    let mut has_player = false;
+
+                    if let Some(collider) = self.physics.collider_set.get(a_collider) {
+                        has_player |= collider.user_data == u128::MAX;
+                        if let Some(entity) = Entity::from_bits(collider.user_data as u64) {
+                            if self.world.get::<Bullet>(entity).is_ok() {
+                                res = Some((collider.parent().unwrap(), entity));
+                            }
+                        }
+                    }
+
+                    if let Some(collider) = self.physics.collider_set.get(b_collider) {
+                        has_player |= collider.user_data == u128::MAX;
+                        if let Some(entity) = Entity::from_bits(collider.user_data as u64) {
+                            if self.world.get::<Bullet>(entity).is_ok() {
+                                res = Some((collider.parent().unwrap(), entity));
+                            }
+                        }
+                    }
+
+                    if let Some((rb, e)) = res {
+                        self.physics.rigid_body_set.remove(
+                            rb,
+                            &mut self.physics.island_manager,
+                            &mut self.physics.collider_set,
+                            &mut self.physics.joint_set,
+                        );
+                        self.entity_to_remove.push(e);
+
+                        if has_player {
+                            self.score.bullet_hit_taken += 1;
+                            camera.effects.add_flinch();
+                            if let GameState::Round = self.game_state {
+                                self.health -= BULLET_DAMAGE;
+                            }
+                        }
+                    }
+                }
+                ContactEvent::Stopped(_, _) => {}
+            };
+        }
+        for entity in self.entity_to_remove.iter() {
+            self.world.despawn(*entity).unwrap();
+        }
+        self.entity_to_remove.clear();
+    }
+}
@@ -0,0 +1,61 @@
+/// Yaw of popular shooters, the degrees their view turns per mouse count at an in-game
+/// sensitivity of 1. Multiplying an in-game sensitivity by its yaw gives degrees per count.
+pub const GAME_YAWS: [(&str, f32); 8] = [
+    ("Counter-Strike", 0.022),
+    ("Apex Legends", 0.022),
+    ("Quake Champions", 0.022),
+    ("Titanfall 2", 0.022),
+    ("Valorant", 0.07),
+    ("Overwatch 2", 0.0066),
+    ("Call of Duty", 0.0066),
+    ("Destiny 2", 0.0066),
+];
+
+const CM_PER_INCH: f32 = 2.54;
+
+/// Degrees per count matching `sensitivity` in the game of `GAME_YAWS` at `game_idx`.
+pub fn from_game_sensitivity(game_idx: usize, sensitivity: f32) -> f32 {
+    sensitivity * GAME_YAWS[game_idx].1
+}
+
+/// In-game sensitivity of the game of `GAME_YAWS` at `game_idx` matching `degrees_per_count`.
+pub fn to_game_sensitivity(game_idx: usize, degrees_per_count: f32) -> f32 {
+    degrees_per_count / GAME_YAWS[game_idx].1
+}
+
+/// Distance the mouse travels for a full turn, in centimeters.
+pub fn cm_per_360(degrees_per_count: f32, mouse_dpi: f32) -> f32 {
+    360.0 / (degrees_per_count * mouse_dpi) * CM_PER_INCH
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_sensitivity_round_trips() {
+        for game_idx in 0..GAME_YAWS.len() {
+            let degrees_per_count = from_game_sensitivity(game_idx, 1.5);
+            assert!((to_game_sensitivity(game_idx, degrees_per_count) - 1.5).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn counter_strike_sensitivity_to_degrees_per_count() {
+        let degrees_per_count = from_game_sensitivity(0, 2.0);
+        assert!((degrees_per_count - 0.044).abs() < 1e-6);
+    }
+
+    #[test]
+    fn cm_per_360_of_known_setup() {
+        // 0.022 degrees per count at 800 DPI turns 17.6 degrees per inch
+        let cm = cm_per_360(0.022, 800.0);
+        assert!((cm - 360.0 / 17.6 * 2.54).abs() < 1e-3);
+    }
+
+    #[test]
+    fn cm_per_360_halves_when_dpi_doubles() {
+        let cm = cm_per_360(0.05, 400.0);
+        assert!((cm_per_360(0.05, 800.0) - cm / 2.0).abs() < 1e-3);
+    }
+}
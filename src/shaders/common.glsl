@@ -30,17 +30,29 @@ layout(std140, binding = 0) uniform rendering_info {
     vec3 cam_pos;
     vec3 cam_dir;
     vec2 fov_shootanim;
+    uint weapon_type;
     uvec4 queuecount_raymarchmaxstep_aostep_background_type;
 };
 
+// The web only has uniform buffers, which are too small for every object. The queue is split
+// in chunks and the scene is drawn once per chunk, the depth buffer keeps the closest hit.
+// A chunk only sees its own objects, and the background only with the first chunk, so shadows
+// and ambient occlusion don't cross chunks.
 #ifdef IS_WEB
-layout(std140, binding = 1) uniform render_queue {
-    RenderQueue queue[60];
+#define QUEUE_CHUNK_SIZE 60
+layout(std140, set = 1, binding = 0) uniform render_queue {
+    // Number of objects in the chunk and index of the chunk
+    uvec4 queue_chunk_count_index;
+    RenderQueue queue[QUEUE_CHUNK_SIZE];
 };
+#define QUEUE_COUNT queue_chunk_count_index.x
+#define IS_FIRST_QUEUE_CHUNK (queue_chunk_count_index.y == 0u)
 #else
-layout(std430, binding = 1) readonly buffer render_queue {
-    RenderQueue queue[130];
+layout(std430, set = 1, binding = 0) readonly buffer render_queue {
+    RenderQueue queue[];
 };
+#define QUEUE_COUNT queuecount_raymarchmaxstep_aostep_background_type.x
+#define IS_FIRST_QUEUE_CHUNK true
 #endif
 
 #define MATERIAL_GREEN 0
@@ -64,6 +76,7 @@ layout(std430, binding = 1) readonly buffer render_queue {
 #define MATERIAL_RGBA_NOISE_MEDIUM 18
 #define MATERIAL_GRAY_NOISE_SMALL 19
 #define MATERIAL_ASPHALT 20
+#define MATERIAL_SNOW 21
 
 layout(binding = 2) uniform sampler common_sampler;
 //layout(binding = 3) uniform texture2D checker_texture;
@@ -209,7 +222,8 @@ float ambient_ocl(in vec3 pos, in vec3 nor) {
 
 vec3 ray_view_dir(vec2 size, vec2 coord) {
     vec2 xy = coord - size / 2.0;
-    float z = size.y / tan(fov_shootanim.x / 2.0);
+    // fov_shootanim.x is the vertical field of view
+    float z = size.y / 2.0 / tan(fov_shootanim.x / 2.0);
     return normalize(vec3(xy, z));
 }
 
@@ -5,6 +5,7 @@ layout(std140, binding = 0) uniform rendering_info {
     vec3 cam_pos;
     vec3 cam_dir;
     vec2 fov_shootanim;
+    uint weapon_type;
     uvec3 queuecount_raymarchmaxstep_aostep;
 };
 
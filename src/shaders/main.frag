@@ -318,10 +318,27 @@ Distance sd_trees(vec3 p, uint trunk_material, uint greenery_material) {
     return d;
 }
 
+const vec3 SNOW_SKY_COLOR = vec3(.82, .85, .9);
+
+// Screen space snowflakes, a few layers falling at different speeds for some depth.
+vec3 snowfall(vec2 uv, float time) {
+    float flakes = 0.;
+    for (uint i = 0u; i < 3u; ++i) {
+        float scale = 12. + float(i) * 8.;
+        vec2 q = uv * scale + vec2(sin(time * .5 + float(i)) * .5, time * (1.5 - float(i) * .3));
+        vec2 cell = floor(q);
+        vec2 offset = vec2(hash1(cell), hash1(cell, float(i))) * .6 + .2;
+        float d = length(fract(q) - offset);
+        flakes += (1. - smoothstep(.02, .06, d)) * step(.6, hash1(cell + 7.3)) * (1. - float(i) * .25);
+    }
+    return vec3(min(flakes, 1.) * .8);
+}
+
 Distance scene_dist(vec3 pos) {
-    Distance m;
+    Distance m = Distance(MAX_DISTANCE, MATERIAL_GREEN, SENTINEL_IDX);
 
-    switch (queuecount_raymarchmaxstep_aostep_background_type.w) {
+    // The background is drawn with the first chunk only
+    switch (IS_FIRST_QUEUE_CHUNK ? queuecount_raymarchmaxstep_aostep_background_type.w : uint(SCENE_NONE)) {
         case SCENE_FOREST:
         {
             m = Distance(pos.y - sd_terrain(pos.xz), MATERIAL_GRASS, SENTINEL_IDX);
@@ -335,14 +352,16 @@ Distance scene_dist(vec3 pos) {
         }
         case SCENE_SNOW:
         {
-            m = Distance(pos.y + sd_terrain(pos.xz), MATERIAL_GRASS, SENTINEL_IDX);
+            // Wider drifts than the forest floor, with snow laden pines.
+            m = Distance(pos.y - sd_terrain(pos.xz * .5), MATERIAL_SNOW, SENTINEL_IDX);
+            m = sd_union(m, sd_trees(pos - vec3(0., -1.8, 0.), MATERIAL_TREE_BARK, MATERIAL_SNOW));
             break;
         }
         default:
             break;
     }
 
-    for (uint i = 0u; i < queuecount_raymarchmaxstep_aostep_background_type.x; i++) {
+    for (uint i = 0u; i < QUEUE_COUNT; i++) {
         vec3 pos_transformed = (queue[i].rotation * vec4(pos - queue[i].position_scale.xyz, 1.)).xyz;
         pos_transformed /= queue[i].position_scale.w;
 
@@ -588,6 +607,14 @@ vec3 color_mapping(vec3 ray_hit_pos, vec3 normal, Distance d)
             col = texture_map_triplanar(asphalt_texture, ray_hit_pos, normal);
             break;
         }
+        case MATERIAL_SNOW:
+        {
+            // Slightly blue in the hollows and sparkling where it faces the sky.
+            float sparkle = pow(hash1(floor(ray_hit_pos.xz * 40.)), 40.) * max(normal.y, 0.);
+            col = mix(vec3(.75, .82, .95), vec3(.95, .97, 1.), .5 + .5 * noise(ray_hit_pos.xz * 2.));
+            col += sparkle;
+            break;
+        }
         default:
         break;
     }
@@ -602,6 +629,14 @@ void main()
 
     Distance d = ray_march(cam_pos, ray_world_dir);
 
+    #ifdef IS_WEB
+    // Later chunks only draw their objects over what the previous ones drew
+    if (!IS_FIRST_QUEUE_CHUNK && d.distance > MAX_DISTANCE - EPS) {
+        discard;
+    }
+    gl_FragDepth = min(d.distance / MAX_DISTANCE, 1.);
+    #endif
+
     vec3 ray_hit_pos = cam_pos + d.distance * ray_world_dir;
     vec3 normal = get_normal(ray_hit_pos);
 
@@ -621,6 +656,14 @@ void main()
                 sky_color = get_night_sky(ray_world_dir);
                 break;
             }
+            case SCENE_SNOW:
+            {
+                // Same clouds as the forest but washed out into an overcast sky.
+                float h_f = SKY_HEIGHT / ray_world_dir.y;
+                vec3 ray_hit_sky_pos = cam_pos + h_f * ray_world_dir;
+                sky_color = mix(clouds(ray_hit_sky_pos), SNOW_SKY_COLOR, .6);
+                break;
+            }
             default:
             break;
         }
@@ -647,4 +690,8 @@ void main()
         outColor = vec4(post_color, 1.0);
     }
 
+    if (queuecount_raymarchmaxstep_aostep_background_type.w == SCENE_SNOW) {
+        outColor.rgb += snowfall(gl_FragCoord.xy / reso_time.y, reso_time.z);
+    }
+
 }
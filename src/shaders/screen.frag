@@ -7,6 +7,11 @@
 #define MATERIAL_GUN 0
 #define MATERIAL_SKIN 1
 
+#define WEAPON_PISTOL 0
+#define WEAPON_RIFLE 1
+#define WEAPON_SMG 2
+#define WEAPON_SNIPER 3
+
 float sd_capsule_line(vec3 p, vec3 a, vec3 b, float r)
 {
     vec3 pa = p - a, ba = b - a;
@@ -252,11 +257,47 @@ float sd_holding_hand(vec3 p)
     return d;
 }
 
+// Extra parts built on top of the Silver Horn frame, where the muzzle points to -x.
+float sd_weapon(vec3 pos)
+{
+    float d = sd_silver_horn(pos);
+    pos *= rot_y(radians(-90.));
+
+    switch (int(weapon_type)) {
+        case WEAPON_RIFLE:
+            // Barrel
+            d = min(d, sd_round_box(pos - vec3(-2.6, 0.1, 0.), vec3(1.1, 0.1, 0.08)));
+            // Magazine
+            d = min(d, sd_round_box(rot_z(radians(-15)) * (pos - vec3(0.2, -0.7, 0.)), vec3(0.2, 0.5, 0.1)));
+            // Stock
+            d = min(d, sd_round_box(pos - vec3(2.4, -0.1, 0.), vec3(0.8, 0.25, 0.1)));
+            break;
+        case WEAPON_SMG:
+            // Barrel
+            d = min(d, sd_round_box(pos - vec3(-2.0, 0.1, 0.), vec3(0.5, 0.12, 0.1)));
+            // Magazine
+            d = min(d, sd_round_box(pos - vec3(0.1, -1.0, 0.), vec3(0.15, 0.8, 0.1)));
+            break;
+        case WEAPON_SNIPER:
+            // Barrel
+            d = min(d, sd_round_box(pos - vec3(-3.4, 0.1, 0.), vec3(1.9, 0.08, 0.08)));
+            // Scope
+            d = min(d, sd_round_box(pos - vec3(0.0, 0.6, 0.), vec3(0.9, 0.18, 0.18)));
+            // Stock
+            d = min(d, sd_round_box(pos - vec3(2.6, -0.2, 0.), vec3(1.0, 0.3, 0.1)));
+            break;
+        default:
+            break;
+    }
+
+    return d;
+}
+
 Distance scene_dist(vec3 pos)
 {
     vec3 gun_pos = vec3(1., -0.5, -5.);
     mat3 rot = rot_x(-fov_shootanim.y);
-    Distance m = Distance(sd_silver_horn(rot * pos - gun_pos), MATERIAL_GUN, SENTINEL_IDX);
+    Distance m = Distance(sd_weapon(rot * pos - gun_pos), MATERIAL_GUN, SENTINEL_IDX);
     m = sd_union(m, Distance(sd_holding_hand(rot * pos - gun_pos), MATERIAL_SKIN, SENTINEL_IDX));
     return m;
 }
@@ -0,0 +1,93 @@
+use crate::entity::enemy::HitZone;
+use crate::physics::GamePhysics;
+use hecs::Entity;
+use nalgebra::{Point3, Vector3};
+use rapier3d::prelude::*;
+
+/// Inserts the rigid body and hit zone colliders shared by every humanoid enemy.
+pub fn spawn_enemy_body(
+    physics: &mut GamePhysics,
+    entity: Entity,
+    pos: Vector3<f32>,
+) -> RigidBodyHandle {
+    let rigid_body_handle = physics.rigid_body_set.insert(
+        RigidBodyBuilder::new(RigidBodyType::Dynamic)
+            .translation(pos)
+            .lock_rotations()
+            .build(),
+    );
+
+    // Body
+    physics.collider_set.insert_with_parent(
+        ColliderBuilder::new(SharedShape::cuboid(
+            0.3 * 3.1 * 0.2,
+            1.4 * 1.4 * 0.2,
+            0.4 * 3.0 * 0.2,
+        ))
+        .user_data(HitZone::Body.user_data(entity))
+        .build(),
+        rigid_body_handle,
+        &mut physics.rigid_body_set,
+    );
+
+    // Arms
+    // Left
+    physics.collider_set.insert_with_parent(
+        ColliderBuilder::new(SharedShape::capsule(
+            Point3::<f32>::new(1.1, 1.4, 0.0) * 0.2,
+            Point3::<f32>::new(1.1, 1.4 - 2.3, 0.0) * 0.2,
+            0.4 * 0.2,
+        ))
+        .user_data(HitZone::Limb.user_data(entity))
+        .build(),
+        rigid_body_handle,
+        &mut physics.rigid_body_set,
+    );
+    // Right
+    physics.collider_set.insert_with_parent(
+        ColliderBuilder::new(SharedShape::capsule(
+            Point3::<f32>::new(-1.1, 1.6, 0.0) * 0.2,
+            Point3::<f32>::new(-1.1, 1.6, 2.3) * 0.2,
+            0.4 * 0.2,
+        ))
+        .user_data(HitZone::Limb.user_data(entity))
+        .build(),
+        rigid_body_handle,
+        &mut physics.rigid_body_set,
+    );
+    // Head
+    physics.collider_set.insert_with_parent(
+        ColliderBuilder::new(SharedShape::ball(0.2))
+            .translation(Vector3::new(0.0, 3.1, 0.0) * 0.2)
+            .user_data(HitZone::Head.user_data(entity))
+            .build(),
+        rigid_body_handle,
+        &mut physics.rigid_body_set,
+    );
+    // Legs
+    // Left
+    physics.collider_set.insert_with_parent(
+        ColliderBuilder::new(SharedShape::capsule(
+            Point3::<f32>::new(0.5, -2.0, 0.0) * 0.2,
+            Point3::<f32>::new(0.5, -2.0 - 2.5, 0.0) * 0.2,
+            0.4 * 0.2,
+        ))
+        .user_data(HitZone::Limb.user_data(entity))
+        .build(),
+        rigid_body_handle,
+        &mut physics.rigid_body_set,
+    );
+    // Right
+    physics.collider_set.insert_with_parent(
+        ColliderBuilder::new(SharedShape::capsule(
+            Point3::<f32>::new(-0.5, -2.0, 0.0) * 0.2,
+            Point3::<f32>::new(-0.5, -2.0 - 2.5, 0.0) * 0.2,
+            0.4 * 0.2,
+        ))
+        .user_data(HitZone::Limb.user_data(entity))
+        .build(),
+        rigid_body_handle,
+        &mut physics.rigid_body_set,
+    );
+    rigid_body_handle
+}
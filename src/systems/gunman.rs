@@ -1,13 +1,17 @@
 use crate::entity::enemy::gunman::{Bullet, Gunman, GunmanOp, BULLET_RAD, BULLET_SPEED};
 use crate::entity::HasMaterial;
 use crate::frustum::ObjectBound;
+use crate::navigation::{has_line_of_sight, NavGrid};
 use crate::physics::GamePhysics;
 use crate::renderer::render_objects::MaterialType;
 use crate::renderer::render_objects::ShapeType;
 use crate::renderer::Renderer;
-use hecs::World;
+use crate::systems::enemy::spawn_enemy_body;
+use hecs::{Entity, World};
 use nalgebra::{Point3, Vector3};
 use rand::prelude::SmallRng;
+use rapier3d::parry::query;
+use rapier3d::parry::shape::Capsule;
 use rapier3d::prelude::*;
 
 
@@ -18,82 +22,7 @@ pub fn spawn_gunman(
     gunman: Gunman,
 ) {
     let entity = world.reserve_entity();
-    let rigid_body_handle = physics.rigid_body_set.insert(
-        RigidBodyBuilder::new(RigidBodyType::Dynamic)
-            .translation(pos)
-            .lock_rotations()
-            .build(),
-    );
-    physics.collider_set.insert_with_parent(
-        ColliderBuilder::new(SharedShape::cuboid(
-            0.3 * 3.1 * 0.2,
-            1.4 * 1.4 * 0.2,
-            0.4 * 3.0 * 0.2,
-        ))
-        .user_data(entity.to_bits().get() as u128)
-        .build(),
-        rigid_body_handle,
-        &mut physics.rigid_body_set,
-    );
-    // Arms
-    // Left
-    physics.collider_set.insert_with_parent(
-        ColliderBuilder::new(SharedShape::capsule(
-            Point3::<f32>::new(1.1, 1.4, 0.0) * 0.2,
-            Point3::<f32>::new(1.1, 1.4 - 2.3, 0.0) * 0.2,
-            0.4 * 0.2,
-        ))
-        .user_data(entity.to_bits().get() as u128)
-        .build(),
-        rigid_body_handle,
-        &mut physics.rigid_body_set,
-    );
-    // Right
-    physics.collider_set.insert_with_parent(
-        ColliderBuilder::new(SharedShape::capsule(
-            Point3::<f32>::new(-1.1, 1.6, 0.0) * 0.2,
-            Point3::<f32>::new(-1.1, 1.6, 2.3) * 0.2,
-            0.4 * 0.2,
-        ))
-        .user_data(entity.to_bits().get() as u128)
-        .build(),
-        rigid_body_handle,
-        &mut physics.rigid_body_set,
-    );
-    // Head
-    physics.collider_set.insert_with_parent(
-        ColliderBuilder::new(SharedShape::ball(0.2))
-            .translation(Vector3::new(0.0, 3.1, 0.0) * 0.2)
-            .user_data(entity.to_bits().get() as u128)
-            .build(),
-        rigid_body_handle,
-        &mut physics.rigid_body_set,
-    );
-    // Legs
-    // Left
-    physics.collider_set.insert_with_parent(
-        ColliderBuilder::new(SharedShape::capsule(
-            Point3::<f32>::new(0.5, -2.0, 0.0) * 0.2,
-            Point3::<f32>::new(0.5, -2.0 - 2.5, 0.0) * 0.2,
-            0.4 * 0.2,
-        ))
-        .user_data(entity.to_bits().get() as u128)
-        .build(),
-        rigid_body_handle,
-        &mut physics.rigid_body_set,
-    );
-    // Right
-    physics.collider_set.insert_with_parent(
-        ColliderBuilder::new(SharedShape::capsule(
-            Point3::<f32>::new(-0.5, -2.0, 0.0) * 0.2,
-            Point3::<f32>::new(-0.5, -2.0 - 2.5, 0.0) * 0.2,
-            0.4 * 0.2,
-        ))
-        .user_data(entity.to_bits().get() as u128)
-        .build(),
-        rigid_body_handle,
-        &mut physics.rigid_body_set,
-    );
+    let rigid_body_handle = spawn_enemy_body(physics, entity, pos);
     world.spawn_at(entity, (gunman, rigid_body_handle));
 }
 
@@ -119,13 +48,42 @@ pub fn update_gunmans(
     physics: &mut GamePhysics,
     delta_time: f32,
     player_position: &Vector3<f32>,
+    player_velocity: &Vector3<f32>,
+    nav_grid: &NavGrid,
     rng: &mut SmallRng,
 ) {
     let mut gunman_ops = Vec::new();
     for (_id, (gunman, rb_handle)) in world.query_mut::<(&mut Gunman, &RigidBodyHandle)>() {
+        let mut gunman_pos = *physics
+            .rigid_body_set
+            .get(*rb_handle)
+            .unwrap()
+            .translation();
+        let line_of_sight = has_line_of_sight(physics, gunman_pos, *player_position);
+        if gunman.needs_path(line_of_sight) {
+            let firing_position = if line_of_sight {
+                None
+            } else {
+                nav_grid.find_firing_position(physics, gunman_pos, *player_position)
+            };
+            let destination = firing_position.or_else(|| nav_grid.random_walkable_position(rng));
+            gunman.set_path(
+                destination
+                    .map(|destination| nav_grid.find_path(gunman_pos.xz(), destination))
+                    .unwrap_or_default(),
+                !line_of_sight,
+            );
+        }
+
         let gunman_rigid_body = physics.rigid_body_set.get_mut(*rb_handle).unwrap();
-        let mut gunman_pos = *gunman_rigid_body.translation();
-        gunman_ops.push(gunman.update(rng, delta_time, &mut gunman_pos, player_position));
+        gunman_ops.push(gunman.update(
+            rng,
+            delta_time,
+            &mut gunman_pos,
+            player_position,
+            player_velocity,
+            line_of_sight,
+        ));
         gunman_rigid_body.set_translation(gunman_pos, true);
         gunman_rigid_body.set_rotation(gunman.get_rotation(), true);
     }
@@ -133,8 +91,7 @@ pub fn update_gunmans(
     for op in gunman_ops {
         match op {
             GunmanOp::None => {}
-            GunmanOp::Shoot { pos, mut dir } => {
-                dir.y = 0.0;
+            GunmanOp::Shoot { pos, dir } => {
                 spawn_bullet(world, physics, pos + dir * 1.0, dir);
             }
         }
@@ -163,7 +120,7 @@ pub fn spawn_bullet(
         rigid_body_handle,
         &mut physics.rigid_body_set,
     );
-    world.spawn_at(entity, (Bullet, rigid_body_handle));
+    world.spawn_at(entity, (Bullet::new(), rigid_body_handle));
 }
 
 pub fn enqueue_bullet(world: &mut World, physics: &mut GamePhysics, renderer: &mut Renderer) {
@@ -187,3 +144,58 @@ pub fn enqueue_bullet(world: &mut World, physics: &mut GamePhysics, renderer: &m
         *bound = ObjectBound::Sphere(collider.radius);
     }
 }
+
+/// Measures how close each bullet's path during the last step came to the player capsule.
+pub fn update_bullet_near_misses(
+    world: &mut World,
+    physics: &GamePhysics,
+    player_rigid_body_handle: RigidBodyHandle,
+) {
+    let player_rigid_body = physics
+        .rigid_body_set
+        .get(player_rigid_body_handle)
+        .unwrap();
+    let player_collider = physics
+        .collider_set
+        .get(player_rigid_body.colliders()[0])
+        .unwrap();
+    for (_id, (bullet, rb_handle)) in world.query_mut::<(&mut Bullet, &RigidBodyHandle)>() {
+        let rb = physics.rigid_body_set.get(*rb_handle).unwrap();
+        let position = *rb.translation();
+        // The physics always steps by a fixed dt, whatever the frame time
+        let previous_position = position - *rb.linvel() * physics.integration_parameters.dt;
+        let swept_path = Capsule::new(
+            Point3::from(previous_position),
+            Point3::from(position),
+            BULLET_RAD,
+        );
+        let distance = query::distance(
+            &Isometry::identity(),
+            &swept_path,
+            player_collider.position(),
+            player_collider.shape(),
+        )
+        .unwrap();
+        bullet.update_closest_approach(distance);
+    }
+}
+
+/// Removes every gunman whose death animation has finished and returns how many were removed.
+pub fn despawn_dead_gunmans(world: &mut World, physics: &mut GamePhysics) -> usize {
+    let mut entity_to_remove = Vec::<Entity>::new();
+    for (id, (gunman, rb_handle)) in world.query_mut::<(&Gunman, &RigidBodyHandle)>() {
+        if gunman.is_need_to_be_deleted() {
+            physics.rigid_body_set.remove(
+                *rb_handle,
+                &mut physics.island_manager,
+                &mut physics.collider_set,
+                &mut physics.joint_set,
+            );
+            entity_to_remove.push(id);
+        }
+    }
+    for entity in entity_to_remove.iter() {
+        world.despawn(*entity).unwrap();
+    }
+    entity_to_remove.len()
+}
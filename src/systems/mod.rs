@@ -1,7 +1,10 @@
 pub mod container;
 pub mod crate_box;
+pub mod enemy;
 pub mod gunman;
 pub mod player;
+pub mod projectile;
+pub mod scripted_enemy;
 pub mod shoot_ray;
 pub mod shootanim;
 pub mod swordman;
@@ -3,23 +3,35 @@ use crate::renderer::Renderer;
 use nalgebra::{Point3, Vector3};
 use rapier3d::prelude::*;
 
+// How much lower the top of the capsule and the eye are while crouching.
+pub const PLAYER_CROUCH_DEPTH: f32 = 0.7;
+
+pub fn player_capsule(crouching: bool) -> SharedShape {
+    let top = if crouching {
+        0.5 - PLAYER_CROUCH_DEPTH
+    } else {
+        0.5
+    };
+    SharedShape::capsule(
+        Point3::<f32>::new(0.0, -1.0, 0.0),
+        Point3::<f32>::new(0.0, top, 0.0),
+        0.5,
+    )
+}
+
 pub fn setup_player_collider(physics: &mut GamePhysics, position: Vector3<f32>) -> RigidBodyHandle {
     let player_rigid_body_handle = physics.rigid_body_set.insert(
-        RigidBodyBuilder::new(RigidBodyType::Dynamic)
+        RigidBodyBuilder::new(RigidBodyType::KinematicPositionBased)
             .translation(position)
             .user_data(u128::MAX)
             .lock_rotations()
             .build(),
     );
     physics.collider_set.insert_with_parent(
-        ColliderBuilder::new(SharedShape::capsule(
-            Point3::<f32>::new(0.0, -1.0, 0.0),
-            Point3::<f32>::new(0.0, 0.5, 0.0),
-            0.5,
-        ))
-        .active_events(ActiveEvents::CONTACT_EVENTS)
-        .user_data(u128::MAX)
-        .build(),
+        ColliderBuilder::new(player_capsule(false))
+            .active_events(ActiveEvents::CONTACT_EVENTS)
+            .user_data(u128::MAX)
+            .build(),
         player_rigid_body_handle,
         &mut physics.rigid_body_set,
     );
@@ -0,0 +1,140 @@
+use crate::entity::projectile::{Projectile, PROJECTILE_RAD};
+use crate::entity::HasMaterial;
+use crate::frustum::ObjectBound;
+use crate::physics::GamePhysics;
+use crate::renderer::render_objects::ShapeType;
+use crate::renderer::Renderer;
+use crate::weapon::Ballistics;
+use hecs::{Entity, World};
+use nalgebra::{Unit, Vector3};
+use rapier3d::prelude::*;
+
+/// Projectile which touched something during the last physics step.
+pub struct ProjectileHit {
+    /// Entity owning the touched collider, `None` for the ground and other untracked colliders.
+    pub entity: Option<Entity>,
+    /// Distance flown from the muzzle.
+    pub distance: f32,
+}
+
+pub fn spawn_projectile(
+    world: &mut World,
+    physics: &mut GamePhysics,
+    pos: Vector3<f32>,
+    dir: Unit<Vector3<f32>>,
+    ballistics: Ballistics,
+) {
+    let entity = world.reserve_entity();
+    let rigid_body_handle = physics.rigid_body_set.insert(
+        RigidBodyBuilder::new(RigidBodyType::Dynamic)
+            .user_data(entity.to_bits().get() as u128)
+            .translation(pos)
+            .linvel(dir.into_inner() * ballistics.speed)
+            .gravity_scale(ballistics.gravity_scale)
+            // Fast and small, it would tunnel through the targets otherwise.
+            .ccd_enabled(true)
+            .build(),
+    );
+    physics.collider_set.insert_with_parent(
+        ColliderBuilder::new(SharedShape::ball(PROJECTILE_RAD))
+            .user_data(entity.to_bits().get() as u128)
+            .active_events(ActiveEvents::CONTACT_EVENTS)
+            .build(),
+        rigid_body_handle,
+        &mut physics.rigid_body_set,
+    );
+    world.spawn_at(entity, (Projectile::new(pos), rigid_body_handle));
+}
+
+pub fn enqueue_projectile(world: &mut World, physics: &mut GamePhysics, renderer: &mut Renderer) {
+    for (_id, (projectile, rb_handle)) in world.query_mut::<(&Projectile, &RigidBodyHandle)>() {
+        let rb = physics.rigid_body_set.get(*rb_handle).unwrap();
+
+        let (objects, ref mut bound) = renderer.render_objects.next();
+        objects.position = *rb.translation();
+        objects.shape_data1.x = PROJECTILE_RAD;
+        objects.shape_type_material_ids.0 = ShapeType::Sphere;
+        objects.shape_type_material_ids.1 = projectile.get_material();
+        objects.rotation = rb.rotation().inverse().to_homogeneous();
+
+        *bound = ObjectBound::Sphere(PROJECTILE_RAD);
+    }
+}
+
+/// Removes the projectiles which flew for too long and returns how many were removed.
+pub fn update_projectiles(world: &mut World, physics: &mut GamePhysics, delta_time: f32) -> usize {
+    let mut entity_to_remove = Vec::<Entity>::new();
+    for (id, (projectile, rb_handle)) in world.query_mut::<(&mut Projectile, &RigidBodyHandle)>() {
+        projectile.update(delta_time);
+        if projectile.is_expired() {
+            physics.rigid_body_set.remove(
+                *rb_handle,
+                &mut physics.island_manager,
+                &mut physics.collider_set,
+                &mut physics.joint_set,
+            );
+            entity_to_remove.push(id);
+        }
+    }
+    for entity in entity_to_remove.iter() {
+        world.despawn(*entity).unwrap();
+    }
+    entity_to_remove.len()
+}
+
+/// Drains the contact events of the last step and removes every projectile which touched
+/// something. The physics step has to be given `physics.event_handler` for this to see anything.
+pub fn resolve_projectile_contacts(
+    world: &mut World,
+    physics: &mut GamePhysics,
+) -> Vec<ProjectileHit> {
+    let mut hits = Vec::new();
+    while let Ok(contact_event) = physics.contact_recv.try_recv() {
+        let (a_collider, b_collider) = match contact_event {
+            ContactEvent::Started(a, b) => (a, b),
+            ContactEvent::Stopped(_, _) => continue,
+        };
+
+        let entity_of = |handle: ColliderHandle| {
+            physics
+                .collider_set
+                .get(handle)
+                .and_then(|collider| Entity::from_bits(collider.user_data as u64))
+        };
+        let is_projectile = |entity: Option<Entity>| {
+            entity.is_some_and(|entity| world.get::<Projectile>(entity).is_ok())
+        };
+
+        let a = entity_of(a_collider);
+        let b = entity_of(b_collider);
+        let (projectile_entity, other) = if is_projectile(a) {
+            (a.unwrap(), b)
+        } else if is_projectile(b) {
+            (b.unwrap(), a)
+        } else {
+            continue;
+        };
+
+        let rb_handle = *world.get::<RigidBodyHandle>(projectile_entity).unwrap();
+        let distance = {
+            let projectile = world.get::<Projectile>(projectile_entity).unwrap();
+            let rb = physics.rigid_body_set.get(rb_handle).unwrap();
+            (rb.translation() - projectile.get_origin()).norm()
+        };
+
+        physics.rigid_body_set.remove(
+            rb_handle,
+            &mut physics.island_manager,
+            &mut physics.collider_set,
+            &mut physics.joint_set,
+        );
+        world.despawn(projectile_entity).unwrap();
+
+        hits.push(ProjectileHit {
+            // Two projectiles touching each other is a miss for both.
+            entity: other.filter(|entity| world.get::<Projectile>(*entity).is_err()),
+            distance,
+        });
+    }
+    hits
+}
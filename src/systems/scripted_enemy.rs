@@ -0,0 +1,145 @@
+use crate::entity::enemy::behaviour::{Activity, EnemyModel};
+use crate::entity::enemy::is_melee_hitting;
+use crate::entity::enemy::scripted::{ScriptedEnemy, ScriptedEnemyOp};
+use crate::entity::HasMaterial;
+use crate::frustum::ObjectBound;
+use crate::navigation::{has_line_of_sight, NavGrid};
+use crate::physics::GamePhysics;
+use crate::renderer::render_objects::MaterialType;
+use crate::renderer::render_objects::ShapeType;
+use crate::renderer::Renderer;
+use crate::systems::enemy::spawn_enemy_body;
+use crate::systems::gunman::spawn_bullet;
+use hecs::{Entity, World};
+use nalgebra::Vector3;
+use rand::prelude::SmallRng;
+use rapier3d::prelude::*;
+
+pub fn spawn_scripted_enemy(
+    world: &mut World,
+    physics: &mut GamePhysics,
+    pos: Vector3<f32>,
+    enemy: ScriptedEnemy,
+) {
+    let entity = world.reserve_entity();
+    let rigid_body_handle = spawn_enemy_body(physics, entity, pos);
+    world.spawn_at(entity, (enemy, rigid_body_handle));
+}
+
+pub fn enqueue_scripted_enemy(
+    world: &mut World,
+    physics: &mut GamePhysics,
+    renderer: &mut Renderer,
+) {
+    for (_id, (enemy, rb_handle)) in world.query_mut::<(&ScriptedEnemy, &RigidBodyHandle)>() {
+        let rb = physics.rigid_body_set.get(*rb_handle).unwrap();
+
+        let (objects, ref mut bound) = renderer.render_objects.next();
+        objects.position = *rb.translation();
+        objects.scale = 0.2;
+        match enemy.get_model() {
+            EnemyModel::Gunman => {
+                objects.shape_data1.x = enemy.actionanim() * 1.5;
+                objects.shape_type_material_ids.0 = ShapeType::Gunman;
+                objects.shape_type_material_ids.2 = MaterialType::Black;
+            }
+            EnemyModel::Swordman => {
+                objects.shape_data1.x = enemy.actionanim();
+                objects.shape_type_material_ids.0 = ShapeType::Swordman;
+                objects.shape_type_material_ids.2 = MaterialType::Green;
+            }
+        }
+        objects.shape_type_material_ids.1 = enemy.get_material();
+        objects.rotation = rb.rotation().inverse().to_homogeneous();
+
+        *bound = ObjectBound::Sphere(3.0);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_scripted_enemies(
+    world: &mut World,
+    physics: &mut GamePhysics,
+    hit_taken: &mut u16,
+    delta_time: f32,
+    player_position: &Vector3<f32>,
+    player_velocity: &Vector3<f32>,
+    nav_grid: &NavGrid,
+    rng: &mut SmallRng,
+) {
+    let mut enemy_ops = Vec::new();
+    for (_id, (enemy, rb_handle)) in world.query_mut::<(&mut ScriptedEnemy, &RigidBodyHandle)>() {
+        let mut enemy_pos = *physics
+            .rigid_body_set
+            .get(*rb_handle)
+            .unwrap()
+            .translation();
+        let line_of_sight = has_line_of_sight(physics, enemy_pos, *player_position);
+        if enemy.needs_path(line_of_sight) {
+            let destination = match enemy.get_activity() {
+                // The player's own cell is blocked when they stand against a wall
+                Activity::Chase => nav_grid.nearest_walkable_position(player_position.xz()),
+                _ => nav_grid.random_walkable_position(rng),
+            };
+            enemy.set_path(
+                destination
+                    .map(|destination| nav_grid.find_path(enemy_pos.xz(), destination))
+                    .unwrap_or_default(),
+            );
+        }
+
+        let op = enemy.update(
+            rng,
+            delta_time,
+            &mut enemy_pos,
+            player_position,
+            player_velocity,
+            line_of_sight,
+        );
+        if let ScriptedEnemyOp::Melee = op {
+            if is_melee_hitting(
+                physics,
+                &enemy_pos,
+                &enemy.get_direction(),
+                enemy.get_rotation(),
+            ) {
+                *hit_taken += 1;
+            }
+        }
+        enemy_ops.push(op);
+
+        let enemy_rigid_body = physics.rigid_body_set.get_mut(*rb_handle).unwrap();
+        enemy_rigid_body.set_translation(enemy_pos, true);
+        enemy_rigid_body.set_rotation(enemy.get_rotation(), true);
+    }
+
+    for op in enemy_ops {
+        match op {
+            ScriptedEnemyOp::Fire { pos, dir } => {
+                spawn_bullet(world, physics, pos + dir * 1.0, dir);
+            }
+            ScriptedEnemyOp::None | ScriptedEnemyOp::Melee => {}
+        }
+    }
+}
+
+/// Removes every scripted enemy whose death animation has finished and returns how many were
+/// removed.
+pub fn despawn_dead_scripted_enemies(world: &mut World, physics: &mut GamePhysics) -> usize {
+    let mut entity_to_remove = Vec::<Entity>::new();
+    for (id, (enemy, rb_handle)) in world.query_mut::<(&ScriptedEnemy, &RigidBodyHandle)>() {
+        if enemy.is_need_to_be_deleted() {
+            physics.rigid_body_set.remove(
+                *rb_handle,
+                &mut physics.island_manager,
+                &mut physics.collider_set,
+                &mut physics.joint_set,
+            );
+            entity_to_remove.push(id);
+        }
+    }
+    for entity in entity_to_remove.iter() {
+        world.despawn(*entity).unwrap();
+    }
+    entity_to_remove.len()
+}
@@ -1,16 +1,20 @@
 use crate::camera::Camera;
 use crate::physics::GamePhysics;
-use nalgebra::Point;
+use nalgebra::{Point, Unit, Vector3};
 use rapier3d::geometry::ColliderHandle;
 use rapier3d::math::Real;
 use rapier3d::prelude::Ray;
 
 pub const MAX_RAYCAST_DISTANCE: f32 = 1000.0;
 
-pub fn shoot_ray(physics: &GamePhysics, camera: &Camera) -> Option<(ColliderHandle, Real)> {
+pub fn shoot_ray(
+    physics: &GamePhysics,
+    camera: &Camera,
+    direction: Unit<Vector3<f32>>,
+) -> Option<(ColliderHandle, Real)> {
     let ray = Ray::new(
-        Point::from(camera.position + camera.get_direction().into_inner() * 1.0),
-        camera.get_direction().into_inner(),
+        Point::from(camera.position + direction.into_inner() * 1.0),
+        direction.into_inner(),
     );
     physics.query_pipeline.cast_ray(
         &physics.collider_set,
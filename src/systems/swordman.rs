@@ -5,84 +5,26 @@ use crate::physics::GamePhysics;
 use crate::renderer::render_objects::MaterialType;
 use crate::renderer::render_objects::ShapeType;
 use crate::renderer::Renderer;
-use crate::scene::hit_and_dodge_scene::Score;
-use hecs::World;
-use nalgebra::{Point3, Vector3};
+use crate::systems::enemy::spawn_enemy_body;
+use hecs::{Entity, World};
+use nalgebra::Vector3;
 use rapier3d::prelude::*;
 
+// Approach angles handed out to the swordmen in spawn order, in degrees. They stay under 90 so
+// every swordman keeps closing in on the player.
+const FLANK_ANGLES: [f32; 5] = [0.0, 35.0, -35.0, 70.0, -70.0];
+
 pub fn spawn_swordman(
     world: &mut World,
     physics: &mut GamePhysics,
     pos: Vector3<f32>,
-    swordman: Swordman,
+    mut swordman: Swordman,
 ) {
+    // Picked once, so a swordman dying doesn't make the others change course
+    let count = world.query_mut::<&Swordman>().into_iter().count();
+    swordman.set_flank_angle(FLANK_ANGLES[count % FLANK_ANGLES.len()].to_radians());
     let entity = world.reserve_entity();
-    let rigid_body_handle = physics.rigid_body_set.insert(
-        RigidBodyBuilder::new(RigidBodyType::Dynamic)
-            .translation(pos)
-            .lock_rotations()
-            .build(),
-    );
-
-    // Arms
-    // Left
-    physics.collider_set.insert_with_parent(
-        ColliderBuilder::new(SharedShape::capsule(
-            Point3::<f32>::new(1.1, 1.4, 0.0) * 0.2,
-            Point3::<f32>::new(1.1, 1.4 - 2.3, 0.0) * 0.2,
-            0.4 * 0.2,
-        ))
-        .user_data(entity.to_bits().get() as u128)
-        .build(),
-        rigid_body_handle,
-        &mut physics.rigid_body_set,
-    );
-    // Right
-    physics.collider_set.insert_with_parent(
-        ColliderBuilder::new(SharedShape::capsule(
-            Point3::<f32>::new(-1.1, 1.6, 0.0) * 0.2,
-            Point3::<f32>::new(-1.1, 1.6, 2.3) * 0.2,
-            0.4 * 0.2,
-        ))
-        .user_data(entity.to_bits().get() as u128)
-        .build(),
-        rigid_body_handle,
-        &mut physics.rigid_body_set,
-    );
-    // Head
-    physics.collider_set.insert_with_parent(
-        ColliderBuilder::new(SharedShape::ball(0.2))
-            .translation(Vector3::new(0.0, 3.1, 0.0) * 0.2)
-            .user_data(entity.to_bits().get() as u128)
-            .build(),
-        rigid_body_handle,
-        &mut physics.rigid_body_set,
-    );
-    // Legs
-    // Left
-    physics.collider_set.insert_with_parent(
-        ColliderBuilder::new(SharedShape::capsule(
-            Point3::<f32>::new(0.5, -2.0, 0.0) * 0.2,
-            Point3::<f32>::new(0.5, -2.0 - 2.5, 0.0) * 0.2,
-            0.4 * 0.2,
-        ))
-        .user_data(entity.to_bits().get() as u128)
-        .build(),
-        rigid_body_handle,
-        &mut physics.rigid_body_set,
-    );
-    // Right
-    physics.collider_set.insert_with_parent(
-        ColliderBuilder::new(SharedShape::capsule(
-            Point3::<f32>::new(-0.5, -2.0, 0.0) * 0.2,
-            Point3::<f32>::new(-0.5, -2.0 - 2.5, 0.0) * 0.2,
-            0.4 * 0.2,
-        ))
-        .user_data(entity.to_bits().get() as u128)
-        .build(),
-        rigid_body_handle,
-        &mut physics.rigid_body_set,
-    );
+    let rigid_body_handle = spawn_enemy_body(physics, entity, pos);
     world.spawn_at(entity, (swordman, rigid_body_handle));
 }
 
@@ -106,10 +48,21 @@ pub fn enqueue_swordman(world: &mut World, physics: &mut GamePhysics, renderer:
 pub fn update_swordmans(
     world: &mut World,
     physics: &mut GamePhysics,
-    score: &mut Score,
+    hit_taken: &mut u16,
     delta_time: f32,
     player_position: &Vector3<f32>,
 ) {
+    let positions = world
+        .query_mut::<(&Swordman, &RigidBodyHandle)>()
+        .into_iter()
+        .map(|(_id, (_swordman, rb_handle))| {
+            *physics
+                .rigid_body_set
+                .get(*rb_handle)
+                .unwrap()
+                .translation()
+        })
+        .collect::<Vec<_>>();
     for (_id, (swordman, rb_handle)) in world.query_mut::<(&mut Swordman, &RigidBodyHandle)>() {
         let mut swordman_pos = {
             let swordman_rigid_body = physics.rigid_body_set.get_mut(*rb_handle).unwrap();
@@ -119,11 +72,32 @@ pub fn update_swordmans(
             delta_time,
             &mut swordman_pos,
             player_position,
+            &positions,
             physics,
-            score,
+            hit_taken,
         );
         let swordman_rigid_body = physics.rigid_body_set.get_mut(*rb_handle).unwrap();
         swordman_rigid_body.set_translation(swordman_pos, true);
         swordman_rigid_body.set_rotation(swordman.get_rotation(), true);
     }
 }
+
+/// Removes every swordman whose death animation has finished and returns how many were removed.
+pub fn despawn_dead_swordmans(world: &mut World, physics: &mut GamePhysics) -> usize {
+    let mut entity_to_remove = Vec::<Entity>::new();
+    for (id, (swordman, rb_handle)) in world.query_mut::<(&Swordman, &RigidBodyHandle)>() {
+        if swordman.is_need_to_be_deleted() {
+            physics.rigid_body_set.remove(
+                *rb_handle,
+                &mut physics.island_manager,
+                &mut physics.collider_set,
+                &mut physics.joint_set,
+            );
+            entity_to_remove.push(id);
+        }
+    }
+    for entity in entity_to_remove.iter() {
+        world.despawn(*entity).unwrap();
+    }
+    entity_to_remove.len()
+}
@@ -18,7 +18,7 @@ pub fn spawn_target(
         entity,
         (
             physics.collider_set.insert(
-                ColliderBuilder::new(SharedShape::ball(0.5))
+                ColliderBuilder::new(SharedShape::ball(target.get_radius()))
                     .user_data(entity.to_bits().get() as u128)
                     .translation(pos)
                     .build(),
@@ -66,5 +66,10 @@ pub fn update_target(
         let mut target_pos = *target_collider.translation();
         target.update(delta_time, &mut target_pos);
         target_collider.set_translation(target_pos);
+
+        // Keep the collider in sync with shrinking targets.
+        if target_collider.shape().as_ball().unwrap().radius != target.get_radius() {
+            target_collider.set_shape(SharedShape::ball(target.get_radius()));
+        }
     }
 }
@@ -1,11 +1,49 @@
 use crate::camera::Camera;
+use crate::character_controller::CharacterController;
+use crate::input_binding::Action;
 use crate::input_manager::InputManager;
 use crate::physics::GamePhysics;
+use crate::timer::Timer;
 use nalgebra::Vector3;
 use rapier3d::prelude::RigidBodyHandle;
-use winit::event::VirtualKeyCode;
 
-const SPEED: f32 = 5.0;
+// Speeds are in m/s
+const WALK_SPEED: f32 = 5.0;
+const SPRINT_SPEED: f32 = 7.5;
+const CROUCH_SPEED: f32 = 2.5;
+const JUMP_SPEED: f32 = 5.0;
+
+// Fraction of the wished speed gained per second, on the ground and in the air
+const GROUND_ACCELERATION: f32 = 10.0;
+const AIR_ACCELERATION: f32 = 2.0;
+const FRICTION: f32 = 6.0;
+// Below this speed, friction acts as if the player moved at this speed, so they stop quickly.
+const STOP_SPEED: f32 = 2.0;
+
+const DASH_SPEED: f32 = 15.0;
+pub const DASH_COOLDOWN: f32 = 2.0;
+
+pub fn action_axis(input_manager: &InputManager, negative: Action, positive: Action) -> f32 {
+    let mut axis = 0.0;
+    if input_manager.is_action_press(negative) {
+        axis -= 1.0;
+    }
+    if input_manager.is_action_press(positive) {
+        axis += 1.0;
+    }
+    axis
+}
+
+fn wish_direction(input_manager: &InputManager, camera: &Camera) -> Vector3<f32> {
+    // Opposite keys cancel each other out, which is what counter-strafing relies on
+    let direction = action_axis(input_manager, Action::MoveLeft, Action::MoveRight)
+        * *camera.get_direction_right()
+        + action_axis(input_manager, Action::MoveBackward, Action::MoveForward)
+            * *camera.get_direction_without_pitch();
+    direction
+        .try_normalize(f32::EPSILON)
+        .unwrap_or_else(Vector3::zeros)
+}
 
 pub fn update_player_position(
     delta_time: f32,
@@ -13,22 +51,55 @@ pub fn update_player_position(
     camera: &mut Camera,
     physics: &mut GamePhysics,
     player_rigid_body_handle: RigidBodyHandle,
+    player_controller: &mut CharacterController,
 ) -> Vector3<f32> {
-    let player_rigid_body = physics
-        .rigid_body_set
-        .get_mut(player_rigid_body_handle)
-        .unwrap();
-    camera.position = *player_rigid_body.translation();
-    if input_manager.is_keyboard_press(&VirtualKeyCode::A) {
-        camera.position -= SPEED * delta_time * *camera.get_direction_right();
-    } else if input_manager.is_keyboard_press(&VirtualKeyCode::D) {
-        camera.position += SPEED * delta_time * *camera.get_direction_right();
-    }
-    if input_manager.is_keyboard_press(&VirtualKeyCode::W) {
-        camera.position += SPEED * delta_time * *camera.get_direction_without_pitch();
-    } else if input_manager.is_keyboard_press(&VirtualKeyCode::S) {
-        camera.position -= SPEED * delta_time * *camera.get_direction_without_pitch();
+    player_controller.set_crouching(
+        physics,
+        player_rigid_body_handle,
+        input_manager.is_action_press(Action::Crouch),
+    );
+
+    let wish_direction = wish_direction(input_manager, camera);
+    let wish_speed = if player_controller.is_crouching() {
+        CROUCH_SPEED
+    } else if input_manager.is_action_press(Action::Sprint) {
+        SPRINT_SPEED
+    } else {
+        WALK_SPEED
+    };
+
+    if player_controller.is_grounded() {
+        player_controller.apply_friction(FRICTION, STOP_SPEED, delta_time);
+        player_controller.accelerate(wish_direction, wish_speed, GROUND_ACCELERATION, delta_time);
+        if input_manager.is_action_pressed(Action::Jump) {
+            player_controller.jump(JUMP_SPEED);
+        }
+    } else {
+        player_controller.accelerate(wish_direction, wish_speed, AIR_ACCELERATION, delta_time);
     }
-    player_rigid_body.set_translation(camera.position, true);
+
+    camera.position = player_controller.update(delta_time, physics, player_rigid_body_handle);
     camera.position
 }
+
+/// Dashes along the movement keys, or forward when none is held, once the cooldown is over.
+pub fn update_player_dash(
+    delta_time: f32,
+    input_manager: &InputManager,
+    camera: &Camera,
+    player_controller: &mut CharacterController,
+    dash_cooldown: &mut Timer,
+) {
+    dash_cooldown.update(delta_time);
+    if dash_cooldown.is_finished()
+        && player_controller.is_grounded()
+        && input_manager.is_action_pressed(Action::Dash)
+    {
+        let mut direction = wish_direction(input_manager, camera);
+        if direction == Vector3::zeros() {
+            direction = *camera.get_direction_without_pitch();
+        }
+        player_controller.dash(direction, DASH_SPEED);
+        dash_cooldown.reset(DASH_COOLDOWN);
+    }
+}
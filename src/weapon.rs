@@ -0,0 +1,373 @@
+use crate::camera::Camera;
+use crate::input_binding::Action;
+use crate::input_manager::InputManager;
+use crate::timer::Timer;
+use crate::util::lerp;
+use nalgebra::{Unit, Vector3};
+use rand::distributions::Uniform;
+use rand::prelude::SmallRng;
+use rand::{Rng, SeedableRng};
+
+// Time without firing after which the recoil pattern starts again from the first shot.
+pub const RECOIL_RESET_DURATION: f32 = 0.4;
+// Time to go from the hip to fully aimed down sights, and back.
+const AIM_DURATION: f32 = 0.15;
+// Shake trauma of a shot per degree of recoil pitch.
+const SHOT_TRAUMA_PER_DEGREE: f32 = 0.05;
+// Part of the recoil the view is punched by on top of it, the recoil already moves the aim.
+const SHOT_KICK_FRACTION: f32 = 0.2;
+
+pub const WEAPON_NAMES: &[&str; 4] = &["Pistol", "Rifle", "SMG", "Sniper"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u32)]
+pub enum WeaponType {
+    Pistol = 0,
+    Rifle = 1,
+    Smg = 2,
+    Sniper = 3,
+}
+
+impl From<usize> for WeaponType {
+    fn from(x: usize) -> Self {
+        match x {
+            0 => WeaponType::Pistol,
+            1 => WeaponType::Rifle,
+            2 => WeaponType::Smg,
+            3 => WeaponType::Sniper,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FireMode {
+    SemiAutomatic,
+    Automatic,
+}
+
+/// Flight of a weapon which fires physical projectiles instead of instant rays.
+#[derive(Debug, Clone, Copy)]
+pub struct Ballistics {
+    pub speed: f32,
+    /// Fraction of the world gravity applied to the projectile, 0.0 for a straight flight.
+    pub gravity_scale: f32,
+}
+
+/// Static description of how a weapon behaves.
+pub struct WeaponDefinition {
+    pub fire_interval: f32,
+    pub fire_mode: FireMode,
+    /// Maximum deviation from the crosshair, in degrees.
+    pub spread: f32,
+    /// Camera kick of each consecutive shot as (yaw, pitch) in degrees. The last entry is
+    /// repeated once the spray goes past the end of the pattern.
+    pub recoil_pattern: &'static [(f32, f32)],
+    pub magazine_size: u16,
+    pub reload_duration: f32,
+    /// `None` for hitscan weapons.
+    pub projectile: Option<Ballistics>,
+    /// Magnification while aiming down sights.
+    pub zoom: f32,
+    /// Whether a scope covers the screen once fully aimed.
+    pub is_scoped: bool,
+}
+
+impl WeaponType {
+    pub fn get_definition(&self) -> WeaponDefinition {
+        match self {
+            WeaponType::Pistol => WeaponDefinition {
+                fire_interval: 0.4,
+                fire_mode: FireMode::SemiAutomatic,
+                spread: 0.0,
+                recoil_pattern: &[(0.0, 1.0)],
+                magazine_size: 12,
+                reload_duration: 1.5,
+                projectile: None,
+                zoom: 1.2,
+                is_scoped: false,
+            },
+            WeaponType::Rifle => WeaponDefinition {
+                fire_interval: 0.1,
+                fire_mode: FireMode::Automatic,
+                spread: 0.4,
+                recoil_pattern: &[
+                    (0.0, 0.6),
+                    (0.05, 0.8),
+                    (-0.05, 1.0),
+                    (0.1, 1.1),
+                    (0.15, 1.1),
+                    (0.2, 0.9),
+                    (0.3, 0.7),
+                    (0.4, 0.4),
+                    (-0.4, 0.3),
+                    (-0.6, 0.2),
+                    (-0.6, 0.2),
+                    (-0.4, 0.1),
+                    (0.5, 0.1),
+                    (0.6, 0.1),
+                    (0.3, 0.1),
+                ],
+                magazine_size: 30,
+                reload_duration: 2.5,
+                projectile: None,
+                zoom: 1.5,
+                is_scoped: false,
+            },
+            WeaponType::Smg => WeaponDefinition {
+                fire_interval: 0.07,
+                fire_mode: FireMode::Automatic,
+                spread: 1.0,
+                recoil_pattern: &[
+                    (0.0, 0.4),
+                    (0.05, 0.5),
+                    (-0.05, 0.5),
+                    (0.1, 0.4),
+                    (-0.1, 0.3),
+                    (0.15, 0.2),
+                    (-0.15, 0.2),
+                ],
+                magazine_size: 25,
+                reload_duration: 2.0,
+                projectile: None,
+                zoom: 1.3,
+                is_scoped: false,
+            },
+            WeaponType::Sniper => WeaponDefinition {
+                fire_interval: 1.2,
+                fire_mode: FireMode::SemiAutomatic,
+                spread: 0.0,
+                recoil_pattern: &[(0.0, 4.0)],
+                magazine_size: 5,
+                reload_duration: 3.0,
+                projectile: None,
+                zoom: 4.0,
+                is_scoped: true,
+            },
+        }
+    }
+}
+
+pub struct Weapon {
+    weapon_type: WeaponType,
+    definition: WeaponDefinition,
+    fire_timer: Timer,
+    reload_timer: Option<Timer>,
+    recoil_reset_timer: Timer,
+    recoil_idx: usize,
+    ammo: u16,
+    // 0.0 at the hip, 1.0 fully aimed down sights
+    aim_progress: f32,
+    // Seconds between the press which fired the last shot and the frame handling it
+    last_shot_delay: f32,
+    rng: SmallRng,
+}
+
+impl Weapon {
+    pub fn new(weapon_type: WeaponType) -> Self {
+        let definition = weapon_type.get_definition();
+        Self {
+            weapon_type,
+            ammo: definition.magazine_size,
+            definition,
+            fire_timer: Timer::new_finished(),
+            reload_timer: None,
+            recoil_reset_timer: Timer::new_finished(),
+            recoil_idx: 0,
+            aim_progress: 0.0,
+            last_shot_delay: 0.0,
+            rng: SmallRng::from_entropy(),
+        }
+    }
+
+    /// Same weapon with the random spread removed, so that only the recoil pattern moves
+    /// the shots.
+    pub fn without_spread(mut self) -> Self {
+        self.definition.spread = 0.0;
+        self
+    }
+
+    /// Same weapon firing projectiles which take time to reach their target.
+    pub fn with_projectile(mut self, ballistics: Ballistics) -> Self {
+        self.definition.projectile = Some(ballistics);
+        self
+    }
+
+    /// Also zooms `camera` in while the aim action is held.
+    pub fn update(&mut self, input_manager: &InputManager, camera: &mut Camera, delta_time: f32) {
+        self.fire_timer.update(delta_time);
+        self.recoil_reset_timer.update(delta_time);
+        if self.recoil_reset_timer.is_finished() {
+            self.recoil_idx = 0;
+        }
+
+        if let Some(ref mut timer) = self.reload_timer {
+            timer.update(delta_time);
+            if timer.is_finished() {
+                self.ammo = self.definition.magazine_size;
+                self.reload_timer = None;
+            }
+        } else if input_manager.is_action_pressed(Action::Reload)
+            && self.ammo < self.definition.magazine_size
+        {
+            self.reload();
+        }
+
+        // Reloading takes the weapon down from the sights
+        let aim_step = delta_time / AIM_DURATION;
+        self.aim_progress = if input_manager.is_action_press(Action::Aim) && !self.is_reloading() {
+            (self.aim_progress + aim_step).min(1.0)
+        } else {
+            (self.aim_progress - aim_step).max(0.0)
+        };
+        camera.zoom = lerp(1.0, self.definition.zoom, self.aim_progress);
+        camera.is_scope_visible = self.definition.is_scoped && self.aim_progress >= 1.0;
+    }
+
+    /// Fires when the trigger is held (or just pressed for semi-automatic weapons) and the
+    /// weapon is ready. Returns the direction of the shot after spread, and kicks the camera
+    /// by the next step of the recoil pattern.
+    ///
+    /// A shot fired by a press is aimed where the view pointed when the button went down, not
+    /// where it points at the start of the frame.
+    pub fn fire(
+        &mut self,
+        input_manager: &InputManager,
+        camera: &mut Camera,
+    ) -> Option<Unit<Vector3<f32>>> {
+        let press = input_manager.get_action_press_event(Action::Fire);
+        let is_trigger = match self.definition.fire_mode {
+            FireMode::SemiAutomatic => press.is_some(),
+            FireMode::Automatic => press.is_some() || input_manager.is_action_press(Action::Fire),
+        };
+        if !is_trigger || !self.fire_timer.is_finished() || self.is_reloading() {
+            return None;
+        }
+        if self.ammo == 0 {
+            self.reload();
+            return None;
+        }
+
+        self.ammo -= 1;
+        self.fire_timer.reset(self.definition.fire_interval);
+
+        // Undo the part of the frame's mouse movement that came after the press
+        let mut offset = match press {
+            Some(event) => {
+                self.last_shot_delay = input_manager.get_event_delay(event);
+                -camera.get_mouse_angles(input_manager.get_mouse_movement_since(event))
+            }
+            None => {
+                self.last_shot_delay = 0.0;
+                nalgebra::Vector2::new(0.0, 0.0)
+            }
+        };
+
+        let spread = self.definition.spread;
+        if spread > 0.0 {
+            let angle = self.rng.sample(Uniform::new(0.0, std::f32::consts::TAU));
+            let radius = spread * self.rng.sample(Uniform::new(0.0f32, 1.0)).sqrt();
+            offset += nalgebra::Vector2::new(radius * angle.cos(), radius * angle.sin());
+        }
+        // Shots go where the view is drawn, so that the crosshair stays on them
+        offset += camera.effects.get_offset();
+        let direction = camera.get_direction_with_offset(offset.x, offset.y);
+
+        if self.recoil_idx == 0 {
+            camera.reset_recoil();
+        }
+        let pattern = self.definition.recoil_pattern;
+        let (yaw, pitch) = pattern[self.recoil_idx.min(pattern.len() - 1)];
+        camera.add_recoil(yaw, pitch);
+        // On top of the recoil the view is punched a bit further and springs back, heavier kicks
+        // shake
+        camera
+            .effects
+            .add_kick(yaw * SHOT_KICK_FRACTION, pitch * SHOT_KICK_FRACTION);
+        camera.effects.add_trauma(pitch * SHOT_TRAUMA_PER_DEGREE);
+        self.recoil_idx += 1;
+        self.recoil_reset_timer.reset(RECOIL_RESET_DURATION);
+
+        if self.ammo == 0 {
+            self.reload();
+        }
+
+        Some(direction)
+    }
+
+    fn reload(&mut self) {
+        if self.reload_timer.is_none() {
+            self.reload_timer = Some(Timer::new(self.definition.reload_duration));
+        }
+    }
+
+    /// Seconds the last shot was fired before the frame that handled it, reaction times are
+    /// measured up to the press rather than up to the frame.
+    pub fn get_last_shot_delay(&self) -> f32 {
+        self.last_shot_delay
+    }
+
+    /// True once the spray is over and the next shot starts the recoil pattern again.
+    pub fn is_recoil_reset(&self) -> bool {
+        self.recoil_idx == 0
+    }
+
+    pub fn is_reloading(&self) -> bool {
+        self.reload_timer.is_some()
+    }
+
+    pub fn get_type(&self) -> WeaponType {
+        self.weapon_type
+    }
+
+    pub fn get_projectile(&self) -> Option<Ballistics> {
+        self.definition.projectile
+    }
+
+    /// Ammo indicator for the in-game HUD.
+    pub fn get_ammo_text(&self) -> String {
+        match self.reload_timer {
+            Some(ref timer) => format!("Reloading {:.1}", timer.get_duration()),
+            None => format!("{} / {}", self.ammo, self.definition.magazine_size),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::event::MouseButton;
+
+    fn fire_held(weapon: &mut Weapon, camera: &mut Camera) -> Option<Unit<Vector3<f32>>> {
+        let mut input_manager = InputManager::new();
+        input_manager.mouse_buttons.insert(MouseButton::Left);
+        weapon.fire(&input_manager, camera)
+    }
+
+    #[test]
+    fn recoil_index_advances_per_shot() {
+        let mut weapon = Weapon::new(WeaponType::Rifle);
+        let mut camera = Camera::new();
+        let input_manager = InputManager::new();
+        for shot in 0..3 {
+            assert_eq!(weapon.recoil_idx, shot);
+            assert!(fire_held(&mut weapon, &mut camera).is_some());
+            weapon.update(&input_manager, &mut camera, weapon.definition.fire_interval);
+        }
+        assert_eq!(weapon.recoil_idx, 3);
+        assert!(!weapon.is_recoil_reset());
+    }
+
+    #[test]
+    fn recoil_resets_after_a_pause() {
+        let mut weapon = Weapon::new(WeaponType::Rifle);
+        let mut camera = Camera::new();
+        let input_manager = InputManager::new();
+        fire_held(&mut weapon, &mut camera);
+
+        weapon.update(&input_manager, &mut camera, RECOIL_RESET_DURATION * 0.5);
+        assert!(!weapon.is_recoil_reset());
+        weapon.update(&input_manager, &mut camera, RECOIL_RESET_DURATION * 0.5);
+        assert!(weapon.is_recoil_reset());
+    }
+}